@@ -85,6 +85,14 @@ fn bench_insert_linear_500(c: &mut Criterion) {
     });
 }
 
+fn bench_from_sorted_iter_100000(c: &mut Criterion) {
+    c.bench_function("from_sorted_iter_100000", |b| {
+        b.iter(|| {
+            black_box(SkipList::<u32>::from_sorted_iter(0..100000));
+        })
+    });
+}
+
 fn bench_contains_500(c: &mut Criterion) {
     let mut sk = SkipList::<u32>::new();
     let upper = 500;
@@ -137,6 +145,19 @@ fn bench_contains_500000(c: &mut Criterion) {
     });
 }
 
+fn bench_contains_copied_500000(c: &mut Criterion) {
+    let mut sk = SkipList::<u32>::new();
+    let upper = 500000;
+    for i in 0..upper {
+        black_box(sk.insert(i));
+    }
+    c.bench_function("contains_copied_500000", |b| {
+        b.iter(|| {
+            black_box(sk.contains_copied(333033));
+        })
+    });
+}
+
 fn bench_at_index(c: &mut Criterion) {
     let mut sk = SkipList::<u32>::new();
     let upper = 5000;
@@ -172,10 +193,12 @@ criterion_group!(
     iter_range_with_bench,
     iter_index_range_bench,
     bench_insert_linear_500,
+    bench_from_sorted_iter_100000,
     bench_contains_500,
     bench_contains_5000,
     bench_contains_50000,
     bench_contains_500000,
+    bench_contains_copied_500000,
     bench_at_index,
     bench_index_of,
 );
@@ -0,0 +1,853 @@
+#[cfg(feature = "allocator_api")]
+use crate::pool::NodeAllocator;
+use crate::pool::NodePool;
+use crate::SkipList;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+#[cfg(feature = "allocator_api")]
+use std::alloc::Allocator;
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+/// The default cap on tower heights. With a promotion probability of `0.5`,
+/// this comfortably covers billions of elements.
+pub(crate) const DEFAULT_MAX_HEIGHT: usize = 32;
+
+/// An RNG that a skiplist can own, and copy when the skiplist is cloned.
+///
+/// It's `Sync` as a shared skiplist can be cloned from several threads at once.
+pub(crate) trait LevelRng: RngCore + Send + Sync {
+    fn clone_box(&self) -> Box<dyn LevelRng>;
+}
+
+impl<R: RngCore + Clone + Send + Sync + 'static> LevelRng for R {
+    fn clone_box(&self) -> Box<dyn LevelRng> {
+        Box::new(self.clone())
+    }
+}
+
+impl Clone for Box<dyn LevelRng> {
+    fn clone(&self) -> Self {
+        (**self).clone_box()
+    }
+}
+
+impl fmt::Debug for dyn LevelRng {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("LevelRng")
+    }
+}
+
+/// Decides how tall each new tower in a skiplist is.
+///
+/// Every tower has a height of at least one, and is promoted one level
+/// higher with `probability` until it reaches `max_height`.
+pub(crate) struct LevelGenerator {
+    probability: f64,
+    max_height: usize,
+    rng: Box<dyn LevelRng>,
+    /// Whether `rng` was handed to us, rather than seeded by us.
+    custom_rng: bool,
+    seed: Option<u64>,
+    /// When `probability` is `1 / 2^k`, this is `k`: each promotion is then
+    /// `k` zero bits in a row, so one random word decides the whole tower.
+    bits_per_level: Option<u32>,
+    /// Search lengths seen so far, if auto-tuning.
+    stats: Option<SearchStats>,
+}
+
+/// How long searches have been taking, to spot a skiplist whose towers have
+/// drifted far from the ideal shape.
+struct SearchStats {
+    searches: usize,
+    steps: usize,
+    /// How many searches to average before judging them.
+    window: usize,
+    drifted: bool,
+}
+
+impl Default for SearchStats {
+    fn default() -> Self {
+        SearchStats {
+            searches: 0,
+            steps: 0,
+            window: MIN_SEARCH_WINDOW,
+            drifted: false,
+        }
+    }
+}
+
+/// Searches are judged over at least this many samples.
+const MIN_SEARCH_WINDOW: usize = 256;
+
+/// A skiplist has drifted when searches average this many times the ideal length.
+const DRIFT_FACTOR: f64 = 2.0;
+
+impl LevelGenerator {
+    pub(crate) fn new(probability: f64, max_height: usize, seed: Option<u64>) -> Self {
+        let rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => fresh_rng(),
+        };
+        LevelGenerator {
+            probability,
+            max_height,
+            rng: Box::new(rng),
+            custom_rng: false,
+            seed,
+            bits_per_level: bits_per_level(probability),
+            stats: None,
+        }
+    }
+
+    pub(crate) fn with_rng(probability: f64, max_height: usize, rng: Box<dyn LevelRng>) -> Self {
+        LevelGenerator {
+            probability,
+            max_height,
+            rng,
+            custom_rng: true,
+            seed: None,
+            bits_per_level: bits_per_level(probability),
+            stats: None,
+        }
+    }
+
+    /// Get the height of the next tower.
+    ///
+    /// Runs in `O(1)` time when the probability is a power of two.
+    #[inline]
+    pub(crate) fn next_level(&mut self) -> usize {
+        if let Some(bits) = self.bits_per_level {
+            let word = self.rng.next_u64();
+            return self.level_from_word(word, bits);
+        }
+        let mut height = 1;
+        while height < self.max_height && self.rng.gen::<f64>() < self.probability {
+            height += 1;
+        }
+        height
+    }
+
+    /// Get the height of a tower from `hash`, rather than the RNG, so equal
+    /// hashes always get equal heights.
+    ///
+    /// `hash` seeds a SplitMix64 stream, which is drawn from like the RNG.
+    pub(crate) fn level_from_hash(&self, mut hash: u64) -> usize {
+        if let Some(bits) = self.bits_per_level {
+            return self.level_from_word(splitmix64(&mut hash), bits);
+        }
+        let mut height = 1;
+        while height < self.max_height {
+            // The top 53 bits, as a float in [0, 1).
+            let draw = (splitmix64(&mut hash) >> 11) as f64 / (1u64 << 53) as f64;
+            if draw >= self.probability {
+                break;
+            }
+            height += 1;
+        }
+        height
+    }
+
+    /// The height of the tower at (1-based) `rank` in a perfectly balanced
+    /// skiplist, if the probability allows one.
+    ///
+    /// With a probability of `1 / 2^k`, every `2^k`th tower reaches the second
+    /// level, every `2^2k`th the third, and so on.
+    #[inline]
+    pub(crate) fn balanced_level(&self, rank: usize) -> Option<usize> {
+        let bits = self.bits_per_level?;
+        Some(self.level_from_word(rank as u64, bits))
+    }
+
+    /// A tower is promoted once for every `bits` trailing zeros of `word`.
+    #[inline]
+    fn level_from_word(&self, word: u64, bits: u32) -> usize {
+        let promotions = (word.trailing_zeros() / bits) as usize;
+        (promotions + 1).min(self.max_height)
+    }
+
+    /// Start keeping track of search lengths.
+    pub(crate) fn auto_tune(&mut self) {
+        self.stats = Some(SearchStats::default());
+    }
+
+    /// Record a search through a skiplist of `len` elements that took `steps`
+    /// steps, if auto-tuning.
+    ///
+    /// Searches are averaged over a window at least as long as the skiplist,
+    /// so acting on a drift costs amortized `O(1)` time per search.
+    #[inline]
+    pub(crate) fn observe_search(&mut self, steps: usize, len: usize) {
+        let probability = self.probability;
+        if let Some(stats) = &mut self.stats {
+            stats.searches += 1;
+            stats.steps += steps;
+            if stats.searches >= stats.window {
+                // A search moves right fewer than `1 / p` times on each of the
+                // `log_{1/p}(n)` levels on average, plus a step down each level,
+                // so this errs long.
+                let levels = ((len + 1) as f64).ln() / (1.0 / probability).ln() + 2.0;
+                let ideal = levels * (1.0 / probability + 1.0);
+                stats.drifted = stats.steps as f64 / stats.searches as f64 > DRIFT_FACTOR * ideal;
+                stats.searches = 0;
+                stats.steps = 0;
+                stats.window = len.max(MIN_SEARCH_WINDOW);
+            }
+        }
+    }
+
+    /// Whether searches were found to be far longer than ideal, clearing the flag.
+    #[inline]
+    pub(crate) fn take_drifted(&mut self) -> bool {
+        self.stats
+            .as_mut()
+            .is_some_and(|stats| std::mem::take(&mut stats.drifted))
+    }
+
+    /// The tallest tower `next_level` can return.
+    #[inline]
+    pub(crate) fn max_height(&self) -> usize {
+        self.max_height
+    }
+
+    /// How many nodes `elements` towers are expected to need in total.
+    #[inline]
+    pub(crate) fn expected_nodes(&self, elements: usize) -> usize {
+        (elements as f64 / (1.0 - self.probability)).ceil() as usize
+    }
+
+    /// A generator with the same configuration, but freshly seeded.
+    ///
+    /// A custom RNG is cloned instead, so `thread_rng` is never needed, and
+    /// a seeded generator restarts from its seed, so clones are reproducible too.
+    pub(crate) fn fork(&self) -> Self {
+        let mut levels = if self.custom_rng {
+            LevelGenerator::with_rng(self.probability, self.max_height, self.rng.clone())
+        } else {
+            LevelGenerator::new(self.probability, self.max_height, self.seed)
+        };
+        if self.stats.is_some() {
+            levels.auto_tune();
+        }
+        levels
+    }
+}
+
+/// Seed an RNG for a skiplist that wasn't given a seed, from the best source
+/// the enabled features allow.
+pub(crate) fn fresh_rng() -> StdRng {
+    #[cfg(feature = "thread_rng")]
+    {
+        StdRng::from_rng(thread_rng()).expect("thread_rng never fails")
+    }
+    #[cfg(all(not(feature = "thread_rng"), feature = "getrandom"))]
+    {
+        StdRng::from_entropy()
+    }
+    #[cfg(not(any(feature = "thread_rng", feature = "getrandom")))]
+    {
+        // No source of entropy, so at least keep skiplists from sharing a seed.
+        use std::sync::atomic::{AtomicU64, Ordering};
+        static SKIPLISTS: AtomicU64 = AtomicU64::new(0);
+        let mut state = SKIPLISTS.fetch_add(1, Ordering::Relaxed);
+        StdRng::seed_from_u64(splitmix64(&mut state))
+    }
+}
+
+/// `k` if `probability` is exactly `1 / 2^k`.
+fn bits_per_level(probability: f64) -> Option<u32> {
+    let bits = (1.0 / probability).log2();
+    if bits.fract() == 0.0 && (1.0..=64.0).contains(&bits) {
+        Some(bits as u32)
+    } else {
+        None
+    }
+}
+
+/// Advance a SplitMix64 stream, returning its next word.
+#[inline]
+pub(crate) fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut word = *state;
+    word = (word ^ (word >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    word = (word ^ (word >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    word ^ (word >> 31)
+}
+
+impl Default for LevelGenerator {
+    fn default() -> Self {
+        LevelGenerator::new(0.5, DEFAULT_MAX_HEIGHT, None)
+    }
+}
+
+/// Configure a `SkipList` before constructing it.
+///
+/// Made with `SkipList::builder()`:
+///
+/// ```rust
+/// use convenient_skiplist::SkipList;
+/// let mut sk = SkipList::builder()
+///     .probability(0.25)
+///     .max_height(24)
+///     .seed(42)
+///     .build();
+/// sk.insert(0u32);
+/// assert!(sk.contains(&0));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SkipListBuilder<T> {
+    probability: f64,
+    max_height: usize,
+    seed: Option<u64>,
+    rng: Option<Box<dyn LevelRng>>,
+    level_hash: Option<fn(&T) -> u64>,
+    auto_tune: bool,
+    arena: bool,
+    #[cfg(feature = "allocator_api")]
+    allocator: Option<Box<dyn NodeAllocator>>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for SkipListBuilder<T> {
+    fn default() -> Self {
+        SkipListBuilder {
+            probability: 0.5,
+            max_height: DEFAULT_MAX_HEIGHT,
+            seed: None,
+            rng: None,
+            level_hash: None,
+            auto_tune: false,
+            arena: false,
+            #[cfg(feature = "allocator_api")]
+            allocator: None,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: PartialOrd> SkipListBuilder<T> {
+    /// Make a builder with the default configuration: a promotion probability of `0.5`,
+    /// towers at most 32 nodes tall, and a randomly seeded RNG.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the probability that a tower is promoted to the next level.
+    ///
+    /// Smaller probabilities use less memory at the cost of longer searches.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `probability` isn't strictly between 0 and 1.
+    pub fn probability(mut self, probability: f64) -> Self {
+        assert!(
+            probability > 0.0 && probability < 1.0,
+            "probability must be strictly between 0 and 1"
+        );
+        self.probability = probability;
+        self
+    }
+
+    /// Set the maximum height of a tower. Defaults to 32.
+    ///
+    /// This bounds the height of the whole skiplist, however unlucky the RNG is.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_height` is zero.
+    pub fn max_height(mut self, max_height: usize) -> Self {
+        assert!(max_height > 0, "max_height must be at least 1");
+        self.max_height = max_height;
+        self
+    }
+
+    /// Seed the RNG used to pick tower heights, so the shape of the
+    /// skiplist is reproducible.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Pick tower heights with `rng`, rather than an RNG seeded from `thread_rng`.
+    /// This takes priority over `seed`.
+    ///
+    /// Clones of the skiplist get a clone of `rng`, so `thread_rng` is never used.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// use rand::rngs::mock::StepRng;
+    /// // Never promote a tower, making the skiplist a sorted linked list.
+    /// let mut sk = SkipList::builder().rng(StepRng::new(u64::MAX, 0)).build();
+    /// sk.extend(0..100u32);
+    ///
+    /// assert_eq!(sk.iter_levels().count(), 3);
+    /// ```
+    pub fn rng<R: RngCore + Clone + Send + Sync + 'static>(mut self, rng: R) -> Self {
+        self.rng = Some(Box::new(rng));
+        self
+    }
+
+    /// Derive each tower's height from a hash of its element, rather than the RNG.
+    ///
+    /// Inserting the same elements in any order then builds the same structure,
+    /// so skiplists can be compared or serialized level by level. Removals can
+    /// still leave the skiplist with extra empty levels.
+    ///
+    /// Hashes come from `DefaultHasher::new()`, which is stable for a given
+    /// version of Rust. Elements with colliding hashes share a height.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let build = |items: Vec<u32>| {
+    ///     let mut sk = SkipList::builder().hashed_levels().build();
+    ///     sk.extend(items);
+    ///     sk
+    /// };
+    /// let forwards = build((0..100).collect());
+    /// let backwards = build((0..100).rev().collect());
+    ///
+    /// assert!(forwards.iter_levels().zip(backwards.iter_levels()).all(|(f, b)| f.eq(b)));
+    /// ```
+    pub fn hashed_levels(mut self) -> Self
+    where
+        T: Hash,
+    {
+        self.level_hash = Some(hash_element::<T>);
+        self
+    }
+
+    /// Keep track of how long searches take as elements are inserted and
+    /// removed, and rebuild every tower once they average far longer than
+    /// they should. The rebuild waits for the next insert or bulk load.
+    ///
+    /// This keeps long-lived, heavily churned skiplists fast: for instance
+    /// when removals happen to take out most of the tall towers. Rebuilding
+    /// takes `O(n)` time, but searches are judged over a window at least as
+    /// long as the skiplist, so it's amortized `O(1)` per insert.
+    ///
+    /// This has no effect with `hashed_levels`, as rebuilding wouldn't change anything.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let mut sk = SkipList::builder().auto_tune().build();
+    /// sk.extend(0..100u32);
+    ///
+    /// assert!(sk.contains(&50));
+    /// ```
+    pub fn auto_tune(mut self) -> Self {
+        self.auto_tune = true;
+        self
+    }
+
+    /// Allocate towers from an arena owned by the skiplist, which grows in
+    /// large chunks, instead of giving every element its own allocation.
+    ///
+    /// Building and dropping a large skiplist then takes a handful of
+    /// allocations and deallocations rather than one per element. The
+    /// storage of removed elements is only released when the skiplist is
+    /// dropped, though later inserts reuse it where they can, so this suits
+    /// skiplists that are built up and then mostly read.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let mut sk = SkipList::builder().arena().build();
+    /// sk.extend(0..10_000u32);
+    ///
+    /// assert_eq!(sk.len(), 10_000);
+    /// ```
+    pub fn arena(mut self) -> Self {
+        self.arena = true;
+        self
+    }
+
+    /// Allocate nodes through `allocator` instead of the global allocator.
+    ///
+    /// Clones of the skiplist allocate through a clone of `allocator`.
+    ///
+    /// Requires the nightly-only `allocator_api` feature.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// #![feature(allocator_api)]
+    /// use convenient_skiplist::SkipList;
+    /// use std::alloc::System;
+    ///
+    /// let mut sk = SkipList::builder().allocator(System).build();
+    /// sk.extend(0..100u32);
+    ///
+    /// assert_eq!(sk.len(), 100);
+    /// ```
+    #[cfg(feature = "allocator_api")]
+    pub fn allocator<A: Allocator + Clone + Send + Sync + 'static>(mut self, allocator: A) -> Self {
+        self.allocator = Some(Box::new(allocator));
+        self
+    }
+
+    /// Make a new, empty `SkipList` with this configuration.
+    pub fn build(&self) -> SkipList<T> {
+        let mut levels = match &self.rng {
+            Some(rng) => LevelGenerator::with_rng(self.probability, self.max_height, rng.clone()),
+            None => LevelGenerator::new(self.probability, self.max_height, self.seed),
+        };
+        if self.auto_tune && self.level_hash.is_none() {
+            levels.auto_tune();
+        }
+        let mut pool = NodePool::default();
+        if self.arena {
+            pool.set_arena();
+        }
+        #[cfg(feature = "allocator_api")]
+        if let Some(allocator) = &self.allocator {
+            pool.set_allocator(allocator.clone());
+        }
+        let mut sk = SkipList::with_pool(levels, pool);
+        sk.level_hash = self.level_hash;
+        sk
+    }
+}
+
+fn hash_element<T: Hash>(item: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    item.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SkipList;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+    use rand::{Error, RngCore};
+
+    #[test]
+    fn test_builder_max_height() {
+        let mut sk = SkipList::builder().probability(0.9).max_height(3).build();
+        for i in 0..1000 {
+            sk.insert(i);
+        }
+        // The top row is always empty, so the wall is one taller than the largest tower.
+        assert!(sk.height <= 4);
+        assert_eq!(sk.len(), 1000);
+    }
+
+    #[test]
+    fn test_config_survives_emptying() {
+        let mut sk = SkipList::builder().probability(0.9).max_height(1).build();
+        let refill = |sk: &mut SkipList<u32>| {
+            sk.extend(0..500);
+            // Only the starting levels, as no tower may be promoted.
+            assert_eq!(sk.height, 3);
+        };
+        refill(&mut sk);
+        assert_eq!(sk.clear(), 500);
+        refill(&mut sk);
+        assert_eq!(sk.pop_max(500).len(), 500);
+        refill(&mut sk);
+        assert_eq!(sk.pop_min(1000).len(), 500);
+        refill(&mut sk);
+
+        // Hashed heights still come out the same after emptying.
+        let mut hashed = SkipList::builder().hashed_levels().build();
+        let fresh = hashed.clone();
+        hashed.extend(0..100u32);
+        hashed.clear();
+        hashed.extend((0..100).rev());
+        let expected = {
+            let mut fresh = fresh;
+            fresh.extend(0..100);
+            fresh
+        };
+        assert!(hashed
+            .iter_levels()
+            .zip(expected.iter_levels())
+            .all(|(left, right)| left.eq(right)));
+    }
+
+    #[test]
+    fn test_default_max_height() {
+        use rand::rngs::mock::StepRng;
+        // This RNG always promotes, so only the cap stops a tower from growing.
+        let mut sk = SkipList::with_rng(StepRng::new(0, 0));
+        sk.extend(0..10u32);
+        assert_eq!(sk.height, super::DEFAULT_MAX_HEIGHT + 1);
+        assert!(sk.contains(&9));
+    }
+
+    #[test]
+    fn test_builder_seed() {
+        let heights = |seed| {
+            let mut sk = SkipList::builder().seed(seed).build();
+            (0..500)
+                .map(|i| {
+                    sk.insert(i);
+                    sk.height
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(heights(7), heights(7));
+    }
+
+    #[test]
+    fn test_builder_rng() {
+        let build = || {
+            let mut sk = SkipList::builder().rng(StdRng::seed_from_u64(3)).build();
+            sk.extend((0..500).rev());
+            sk
+        };
+        let (sk, other) = (build(), build());
+        assert_eq!(sk.iter_levels().count(), other.iter_levels().count());
+        assert!(sk
+            .iter_levels()
+            .zip(other.iter_levels())
+            .all(|(left, right)| left.eq(right)));
+        assert_eq!(sk.clone().len(), 500);
+    }
+
+    #[test]
+    fn test_rng_seeding() {
+        use super::{fresh_rng, LevelGenerator};
+        // Whichever source the features pick, unseeded skiplists don't share a stream.
+        let words: Vec<_> = (0..8).map(|_| fresh_rng().next_u64()).collect();
+        assert!(words
+            .iter()
+            .all(|word| words.iter().filter(|&w| w == word).count() == 1));
+        let draws =
+            |levels: &mut LevelGenerator| (0..200).map(|_| levels.next_level()).collect::<Vec<_>>();
+        // A custom RNG is cloned into forks rather than reseeded, and a seed restarts.
+        let mut custom = LevelGenerator::with_rng(0.5, 16, Box::new(StdRng::seed_from_u64(3124)));
+        let mut fork = custom.fork();
+        assert_eq!(draws(&mut custom), draws(&mut fork));
+        let mut seeded = LevelGenerator::new(0.5, 16, Some(3124));
+        draws(&mut seeded);
+        assert_eq!(
+            draws(&mut seeded.fork()),
+            draws(&mut LevelGenerator::new(0.5, 16, Some(3124)))
+        );
+    }
+
+    #[test]
+    fn test_builder_hashed_levels() {
+        let mut rng = StdRng::seed_from_u64(3121);
+        let mut items: Vec<u32> = (0..1000).collect();
+        let build = |items: &[u32]| {
+            let mut sk = SkipList::builder().hashed_levels().build();
+            for &item in items {
+                sk.insert(item);
+            }
+            sk
+        };
+        let sorted = build(&items);
+        items.shuffle(&mut rng);
+        let shuffled = build(&items);
+        assert_eq!(sorted.height, shuffled.height);
+        assert!(sorted
+            .iter_levels()
+            .zip(shuffled.iter_levels())
+            .all(|(left, right)| left.eq(right)));
+        // Clones and bulk loads agree too.
+        assert!(sorted
+            .clone()
+            .iter_levels()
+            .zip(sorted.iter_levels())
+            .all(|(left, right)| left.eq(right)));
+        // The heights still look geometric.
+        assert!(sorted.height > 5 && sorted.height < 30);
+    }
+
+    #[test]
+    fn test_level_distribution() {
+        use super::LevelGenerator;
+        // Powers of two use one word per tower; the rest draw floats.
+        for &probability in &[0.5, 0.25, 0.3] {
+            let mut levels = LevelGenerator::new(probability, 32, Some(3122));
+            let draws = 100_000;
+            let mut at_least = [0usize; 4];
+            for _ in 0..draws {
+                let height = levels.next_level();
+                for (promotions, count) in at_least.iter_mut().enumerate() {
+                    if height > promotions {
+                        *count += 1;
+                    }
+                }
+            }
+            for (promotions, &count) in at_least.iter().enumerate() {
+                let expected = probability.powi(promotions as i32);
+                let observed = count as f64 / draws as f64;
+                assert!(
+                    (observed - expected).abs() < 0.01,
+                    "{} {}",
+                    probability,
+                    promotions
+                );
+            }
+        }
+    }
+
+    /// Never promotes a tower for the first `flat` draws.
+    #[derive(Clone)]
+    struct FlatRng {
+        flat: usize,
+        rng: StdRng,
+    }
+    impl RngCore for FlatRng {
+        fn next_u32(&mut self) -> u32 {
+            self.next_u64() as u32
+        }
+        fn next_u64(&mut self) -> u64 {
+            if self.flat > 0 {
+                self.flat -= 1;
+                return u64::MAX;
+            }
+            self.rng.next_u64()
+        }
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            self.rng.fill_bytes(dest)
+        }
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+            self.rng.try_fill_bytes(dest)
+        }
+    }
+
+    #[test]
+    fn test_builder_auto_tune() {
+        let build = |auto_tune| {
+            let mut builder = SkipList::builder().rng(FlatRng {
+                flat: 1000,
+                rng: StdRng::seed_from_u64(3123),
+            });
+            if auto_tune {
+                builder = builder.auto_tune();
+            }
+            let mut sk = builder.build();
+            // The even elements go in while the RNG is flat, then the odd ones.
+            let mut rng = StdRng::seed_from_u64(0);
+            for parity in 0..2 {
+                let mut items: Vec<u32> = (0..2000).filter(|i| i % 2 == parity).collect();
+                items.shuffle(&mut rng);
+                for item in items {
+                    sk.insert(item);
+                }
+            }
+            sk
+        };
+        // Count the towers among the even elements.
+        let towers = |sk: &SkipList<u32>| {
+            sk.iter_levels()
+                .nth(sk.height - 2)
+                .unwrap()
+                .filter(|(value, _)| value.is_some_and(|v| v % 2 == 0))
+                .count()
+        };
+        // Without tuning, they stay a linked list.
+        assert_eq!(towers(&build(false)), 0);
+        // With it, the slow searches get the towers rebuilt.
+        let tuned = build(true);
+        assert!(towers(&tuned) > 300);
+        assert!(tuned.iter_all().copied().eq(0..2000));
+    }
+
+    #[test]
+    fn test_builder_auto_tune_counts_user_searches() {
+        let mut sk = SkipList::builder()
+            .rng(FlatRng {
+                flat: 2000,
+                rng: StdRng::seed_from_u64(3123),
+            })
+            .auto_tune()
+            .build();
+        let towers = |sk: &SkipList<u32>| sk.iter_levels().nth(sk.height - 2).unwrap().count();
+        // Bulk loads and pops search without being asked to, so the flat list
+        // isn't flagged by them.
+        sk.extend_from_sorted(0..2000);
+        for _ in 0..300 {
+            sk.pop_max(1);
+        }
+        sk.insert(5000);
+        assert!(towers(&sk) < 5);
+        // Removals are user searches, and leave the retune to the next insert.
+        for item in (1400..1700).rev() {
+            assert!(sk.remove(&item));
+        }
+        assert!(towers(&sk) < 5);
+        sk.extend_from_sorted(vec![6000]);
+        assert!(towers(&sk) > 300);
+        assert!(sk.iter_all().copied().eq((0..1400).chain(vec![5000, 6000])));
+    }
+
+    #[test]
+    fn test_builder_arena() {
+        let mut sk = SkipList::builder().arena().seed(3).build();
+        let mut items: Vec<String> = (0..5000).map(|i| i.to_string()).collect();
+        items.shuffle(&mut StdRng::seed_from_u64(3));
+        sk.extend(items.iter().cloned());
+        for item in items.iter().step_by(2) {
+            assert!(sk.remove(item));
+        }
+        let copy = sk.clone();
+        assert!(copy.pool.is_arena());
+        let mut expected: Vec<_> = items.into_iter().skip(1).step_by(2).collect();
+        expected.sort();
+        assert!(sk.iter_all().eq(expected.iter()));
+        assert!(copy.into_iter().eq(expected));
+    }
+
+    #[cfg(feature = "allocator_api")]
+    #[test]
+    fn test_builder_allocator() {
+        use std::alloc::{AllocError, Allocator, Global, Layout};
+        use std::ptr::NonNull;
+        use std::sync::atomic::{AtomicIsize, Ordering};
+        use std::sync::Arc;
+
+        #[derive(Clone)]
+        struct Counting(Arc<AtomicIsize>);
+
+        unsafe impl Allocator for Counting {
+            fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+                self.0.fetch_add(1, Ordering::Relaxed);
+                Global.allocate(layout)
+            }
+
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                self.0.fetch_sub(1, Ordering::Relaxed);
+                Global.deallocate(ptr, layout)
+            }
+        }
+
+        let live = Arc::new(AtomicIsize::new(0));
+        let mut sk = SkipList::builder()
+            .allocator(Counting(Arc::clone(&live)))
+            .build();
+        sk.extend((0..1000).map(|i| i.to_string()));
+        assert!(live.load(Ordering::Relaxed) > 1000);
+        for i in (0..1000).step_by(2) {
+            assert!(sk.remove(&i.to_string()));
+        }
+        let mut copy = sk.clone();
+        // The global allocator's nodes can't be adopted, so they're moved over instead.
+        copy.union_in_place((1000..1100).map(|i| i.to_string()).collect());
+        sk.union_in_place(copy.clone());
+        assert_eq!(sk.len(), 600);
+        assert!(sk.iter_all().eq(copy.iter_all()));
+        drop((sk, copy));
+        assert_eq!(live.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_builder_bad_probability() {
+        SkipList::<u32>::builder().probability(1.0);
+    }
+}
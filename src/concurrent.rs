@@ -0,0 +1,530 @@
+//! Skiplist sets for sharing between many readers and writers.
+//!
+//! Enabled with the `concurrent` feature. `SkipSet` is lock-free, and the
+//! `fine_grained_locking` feature adds `LockingSkipSet`, which locks
+//! individual nodes instead.
+
+use crate::builder::{fresh_rng, splitmix64};
+use crossbeam_epoch::{self as epoch, Atomic, Guard, Owned, Shared};
+use rand::RngCore;
+use std::borrow::Borrow;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+#[cfg(feature = "fine_grained_locking")]
+mod locking;
+
+#[cfg(feature = "fine_grained_locking")]
+pub use self::locking::LockingSkipSet;
+
+/// The tallest a tower can be. With a promotion probability of `0.5`, this
+/// comfortably covers billions of elements.
+const MAX_HEIGHT: usize = 32;
+
+/// Pick the height of a new tower, advancing the SplitMix64 stream in `seed`.
+fn random_height(seed: &AtomicU64) -> usize {
+    let mut state = seed.fetch_add(0x9e37_79b9_7f4a_7c15, Ordering::Relaxed);
+    let word = splitmix64(&mut state);
+    (word.trailing_zeros() as usize + 1).min(MAX_HEIGHT)
+}
+
+/// An element, and its tower of links to the next node on each level.
+///
+/// A link is tagged once the node is removed, which stops anything being
+/// linked after it on that level. Level 0 is tagged last, and that's what
+/// actually removes the element.
+struct Node<T> {
+    value: T,
+    tower: Box<[Atomic<Node<T>>]>,
+    /// How many levels still hold (or may yet hold) a link to this node.
+    /// The node is freed once this reaches zero.
+    refs: AtomicUsize,
+}
+
+impl<T> Node<T> {
+    fn new(value: T, height: usize) -> Self {
+        Node {
+            value,
+            tower: (0..height).map(|_| Atomic::null()).collect(),
+            refs: AtomicUsize::new(height),
+        }
+    }
+
+    #[inline]
+    fn is_removed(&self, guard: &Guard) -> bool {
+        self.tower[0].load(Ordering::Acquire, guard).tag() == 1
+    }
+}
+
+/// Where a search for a key ended up: on every level, the last link before
+/// the key and the node after it.
+struct Position<'g, T> {
+    preds: [&'g Atomic<Node<T>>; MAX_HEIGHT],
+    succs: [Shared<'g, Node<T>>; MAX_HEIGHT],
+}
+
+/// A lock-free skiplist set.
+///
+/// `insert`, `remove` and `contains` all take `&self`, so the set can be
+/// shared between threads (e.g. in an `Arc`) and updated from all of them at
+/// once with no locking. Removed elements are freed with epoch-based
+/// reclamation, once no thread can still be looking at them.
+///
+/// This makes the classic skiplist use case possible: a memtable or index
+/// under many concurrent writers. For single-threaded use, `SkipList` is
+/// quicker and has far more features.
+///
+/// # Example
+///
+/// ```rust
+/// use convenient_skiplist::concurrent::SkipSet;
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// let set = Arc::new(SkipSet::new());
+/// let writers: Vec<_> = (0..4)
+///     .map(|t| {
+///         let set = Arc::clone(&set);
+///         thread::spawn(move || {
+///             for i in 0..100 {
+///                 set.insert(i * 4 + t);
+///             }
+///         })
+///     })
+///     .collect();
+/// for writer in writers {
+///     writer.join().unwrap();
+/// }
+///
+/// assert_eq!(set.len(), 400);
+/// assert!(set.contains(&123));
+/// assert!(set.remove(&123));
+/// assert!(!set.contains(&123));
+/// ```
+pub struct SkipSet<T> {
+    head: Box<[Atomic<Node<T>>]>,
+    /// The number of levels any tower has reached, so searches can skip
+    /// the empty levels above.
+    height: AtomicUsize,
+    len: AtomicUsize,
+    /// SplitMix64 state for tower heights.
+    seed: AtomicU64,
+}
+
+impl<T: Ord + Send + 'static> SkipSet<T> {
+    /// Make an empty set.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::concurrent::SkipSet;
+    /// let set: SkipSet<u32> = SkipSet::new();
+    ///
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        SkipSet {
+            head: (0..MAX_HEIGHT).map(|_| Atomic::null()).collect(),
+            height: AtomicUsize::new(1),
+            len: AtomicUsize::new(0),
+            seed: AtomicU64::new(fresh_rng().next_u64()),
+        }
+    }
+
+    /// The number of elements in the set.
+    ///
+    /// With writers running, this is only a snapshot, and may already be out of date.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    /// Whether the set is empty. See `len` for the caveat about concurrent writers.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Insert `value`, returning `true` if it wasn't already in the set.
+    ///
+    /// Runs in expected `O(logn)` time, and is lock-free.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::concurrent::SkipSet;
+    /// let set = SkipSet::new();
+    ///
+    /// assert!(set.insert(1));
+    /// assert!(!set.insert(1));
+    /// ```
+    pub fn insert(&self, value: T) -> bool {
+        let guard = &epoch::pin();
+        let height = random_height(&self.seed);
+        self.height.fetch_max(height, Ordering::Relaxed);
+        let mut node = Owned::new(Node::new(value, height));
+        // Link in the bottom level first, which is what adds the element.
+        let node = loop {
+            let (position, found) = self.find(&node.value, guard);
+            if found {
+                return false;
+            }
+            node.tower[0].store(position.succs[0], Ordering::Relaxed);
+            // Count the element before anyone can remove it.
+            self.len.fetch_add(1, Ordering::Relaxed);
+            match position.preds[0].compare_exchange(
+                position.succs[0],
+                node,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                guard,
+            ) {
+                Ok(node) => break node,
+                Err(err) => {
+                    self.len.fetch_sub(1, Ordering::Relaxed);
+                    node = err.new;
+                }
+            }
+        };
+        // SAFETY: we're pinned, so the node can't be freed while we use it.
+        let node_ref = unsafe { node.deref() };
+        let mut level = 1;
+        'build: while level < height {
+            loop {
+                let (position, _) = self.find(&node_ref.value, guard);
+                let next = node_ref.tower[level].load(Ordering::Acquire, guard);
+                // Removed while we were building the tower, so stop here.
+                if next.tag() == 1 || node_ref.is_removed(guard) {
+                    break 'build;
+                }
+                let succ = position.succs[level];
+                if node_ref.tower[level]
+                    .compare_exchange(next, succ, Ordering::AcqRel, Ordering::Acquire, guard)
+                    .is_err()
+                {
+                    break 'build;
+                }
+                if position.preds[level]
+                    .compare_exchange(succ, node, Ordering::AcqRel, Ordering::Acquire, guard)
+                    .is_ok()
+                {
+                    break;
+                }
+            }
+            level += 1;
+        }
+        // The levels we never linked on can't unlink the node later.
+        for _ in level..height {
+            unsafe { self.release(node, guard) };
+        }
+        // If it was removed mid-build, we may have linked it after the
+        // remover cleaned up, so clean up again.
+        if node_ref.is_removed(guard) {
+            let _ = self.find(&node_ref.value, guard);
+        }
+        true
+    }
+
+    /// Remove `value`, returning `true` if it was in the set.
+    ///
+    /// Runs in expected `O(logn)` time, and is lock-free.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::concurrent::SkipSet;
+    /// let set = SkipSet::new();
+    /// set.insert("a");
+    ///
+    /// assert!(set.remove(&"a"));
+    /// assert!(!set.remove(&"a"));
+    /// ```
+    pub fn remove<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let guard = &epoch::pin();
+        let (position, found) = self.find(value, guard);
+        if !found {
+            return false;
+        }
+        // SAFETY: `value` was found, so it's the node at `succs[0]`.
+        let node = unsafe { position.succs[0].deref() };
+        // Tag the upper levels first, so nothing more is linked after the node.
+        for link in node.tower[1..].iter().rev() {
+            let mut next = link.load(Ordering::Acquire, guard);
+            while next.tag() == 0 {
+                match link.compare_exchange(
+                    next,
+                    next.with_tag(1),
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                    guard,
+                ) {
+                    Ok(_) => break,
+                    Err(err) => next = err.current,
+                }
+            }
+        }
+        // Whoever tags the bottom level removes the element.
+        let mut next = node.tower[0].load(Ordering::Acquire, guard);
+        loop {
+            if next.tag() == 1 {
+                return false;
+            }
+            match node.tower[0].compare_exchange(
+                next,
+                next.with_tag(1),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                guard,
+            ) {
+                Ok(_) => break,
+                Err(err) => next = err.current,
+            }
+        }
+        self.len.fetch_sub(1, Ordering::Relaxed);
+        // Unlink the node from every level.
+        let _ = self.find(value, guard);
+        true
+    }
+
+    /// Check if `value` is in the set.
+    ///
+    /// Runs in expected `O(logn)` time, and is lock-free.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::concurrent::SkipSet;
+    /// let set = SkipSet::new();
+    /// set.insert(String::from("a"));
+    ///
+    /// assert!(set.contains("a"));
+    /// assert!(!set.contains("b"));
+    /// ```
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let guard = &epoch::pin();
+        self.find(value, guard).1
+    }
+
+    /// Copy the elements out in ascending order.
+    ///
+    /// With writers running, this sees each element as it was when the
+    /// copy passed it, so it's not an atomic snapshot of the whole set.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::concurrent::SkipSet;
+    /// let set = SkipSet::new();
+    /// set.insert(2);
+    /// set.insert(1);
+    ///
+    /// assert_eq!(set.to_vec(), vec![1, 2]);
+    /// ```
+    pub fn to_vec(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        let mut items = Vec::with_capacity(self.len());
+        self.for_each(|item| items.push(item.clone()));
+        items
+    }
+
+    /// Call `f` on every element in ascending order, with the caveat of `to_vec`.
+    fn for_each<F: FnMut(&T)>(&self, mut f: F) {
+        let guard = &epoch::pin();
+        let mut curr = self.head[0].load(Ordering::Acquire, guard);
+        while let Some(node) = unsafe { curr.as_ref() } {
+            let next = node.tower[0].load(Ordering::Acquire, guard);
+            if next.tag() == 0 {
+                f(&node.value);
+            }
+            curr = next.with_tag(0);
+        }
+    }
+
+    /// Search for `value`, unlinking any removed nodes on the way.
+    ///
+    /// Returns the position where `value` is, or would be linked in, and
+    /// whether it's in the set, in which case it's the node in `succs[0]`.
+    fn find<'g, Q>(&'g self, value: &Q, guard: &'g Guard) -> (Position<'g, T>, bool)
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        'retry: loop {
+            let mut position = Position {
+                preds: [&self.head[0]; MAX_HEIGHT],
+                succs: [Shared::null(); MAX_HEIGHT],
+            };
+            for (level, link) in self.head.iter().enumerate() {
+                position.preds[level] = link;
+                position.succs[level] = link.load(Ordering::Acquire, guard);
+            }
+            let mut pred: &'g [Atomic<Node<T>>] = &self.head;
+            let height = self.height.load(Ordering::Relaxed);
+            for level in (0..height).rev() {
+                let mut curr = pred[level].load(Ordering::Acquire, guard);
+                // `pred` was removed, so its links can't be trusted.
+                if curr.tag() == 1 {
+                    continue 'retry;
+                }
+                while let Some(node) = unsafe { curr.as_ref() } {
+                    let succ = node.tower[level].load(Ordering::Acquire, guard);
+                    if succ.tag() == 1 {
+                        // `curr` was removed, so unlink it on this level.
+                        let succ = succ.with_tag(0);
+                        match pred[level].compare_exchange(
+                            curr,
+                            succ,
+                            Ordering::AcqRel,
+                            Ordering::Acquire,
+                            guard,
+                        ) {
+                            Ok(_) => {
+                                unsafe { self.release(curr, guard) };
+                                curr = succ;
+                                continue;
+                            }
+                            Err(_) => continue 'retry,
+                        }
+                    }
+                    if node.value.borrow() < value {
+                        pred = &node.tower;
+                        curr = succ;
+                    } else {
+                        break;
+                    }
+                }
+                position.preds[level] = &pred[level];
+                position.succs[level] = curr;
+            }
+            let found = match unsafe { position.succs[0].as_ref() } {
+                Some(node) => node.value.borrow() == value,
+                None => false,
+            };
+            return (position, found);
+        }
+    }
+
+    /// Drop one of `node`'s references, freeing it once no level can link it.
+    ///
+    /// # Safety
+    ///
+    /// The caller must own one of the node's references, i.e. have just
+    /// unlinked it on a level, or know it'll never be linked on one.
+    unsafe fn release(&self, node: Shared<'_, Node<T>>, guard: &Guard) {
+        if node.deref().refs.fetch_sub(1, Ordering::AcqRel) == 1 {
+            guard.defer_destroy(node);
+        }
+    }
+}
+
+impl<T: Ord + Send + 'static> Default for SkipSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord + Send + fmt::Debug + 'static> fmt::Debug for SkipSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut set = f.debug_set();
+        self.for_each(|item| {
+            set.entry(item);
+        });
+        set.finish()
+    }
+}
+
+impl<T> Drop for SkipSet<T> {
+    fn drop(&mut self) {
+        // Nobody else can reach the set now, and anything already handed to
+        // the collector is unlinked everywhere. So the remaining links are
+        // all there is: unlink each, freeing nodes as their last link goes.
+        unsafe {
+            let guard = epoch::unprotected();
+            for (level, link) in self.head.iter().enumerate() {
+                let mut curr = link.load(Ordering::Relaxed, guard);
+                while let Some(node) = curr.as_ref() {
+                    let next = node.tower[level].load(Ordering::Relaxed, guard);
+                    if node.refs.fetch_sub(1, Ordering::Relaxed) == 1 {
+                        drop(curr.into_owned());
+                    }
+                    curr = next.with_tag(0);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SkipSet;
+    use rand::prelude::*;
+    use std::collections::BTreeSet;
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+
+    #[test]
+    fn test_matches_btreeset() {
+        let set = SkipSet::new();
+        let mut expected = BTreeSet::new();
+        let mut rng = StdRng::seed_from_u64(0x3144);
+        for _ in 0..5000 {
+            let item = rng.gen_range(0u32, 500);
+            if rng.gen() {
+                assert_eq!(set.insert(item), expected.insert(item));
+            } else {
+                assert_eq!(set.remove(&item), expected.remove(&item));
+            }
+            assert_eq!(set.len(), expected.len());
+        }
+        assert_eq!(set.to_vec(), expected.into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_concurrent_writers() {
+        let set = Arc::new(SkipSet::new());
+        let barrier = Arc::new(Barrier::new(8));
+        let workers: Vec<_> = (0..8u64)
+            .map(|t| {
+                let set = Arc::clone(&set);
+                let barrier = Arc::clone(&barrier);
+                thread::spawn(move || {
+                    let mut rng = StdRng::seed_from_u64(t);
+                    // Everyone fights over the same 200 keys, then settles
+                    // the keys it owns.
+                    for _ in 0..2000 {
+                        let item = rng.gen_range(0u64, 200);
+                        if rng.gen() {
+                            set.insert(item);
+                        } else {
+                            set.remove(&item);
+                        }
+                    }
+                    barrier.wait();
+                    for item in (0..200).filter(|i| i % 8 == t) {
+                        set.insert(item);
+                    }
+                    for item in (0..200).filter(|i| i % 8 == t && i % 3 == 0) {
+                        assert!(set.remove(&item));
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        let expected: Vec<_> = (0..200).filter(|i| i % 3 != 0).collect();
+        assert_eq!(set.to_vec(), expected);
+        assert_eq!(set.len(), expected.len());
+        assert!(expected.iter().all(|i| set.contains(i)));
+    }
+}
@@ -0,0 +1,487 @@
+use super::{random_height, MAX_HEIGHT};
+use crate::builder::fresh_rng;
+use crossbeam_epoch::{self as epoch, Atomic, Guard, Owned, Shared};
+use rand::RngCore;
+use std::borrow::Borrow;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// The links out of a node (or the head), and the lock guarding them.
+struct Links<T> {
+    lock: Mutex<()>,
+    /// Set, under `lock`, once the node is being removed.
+    marked: AtomicBool,
+    next: Box<[Atomic<Node<T>>]>,
+}
+
+impl<T> Links<T> {
+    fn new(height: usize) -> Self {
+        Links {
+            lock: Mutex::new(()),
+            marked: AtomicBool::new(false),
+            next: (0..height).map(|_| Atomic::null()).collect(),
+        }
+    }
+
+    #[inline]
+    fn lock(&self) -> MutexGuard<'_, ()> {
+        // Nothing panics while holding a lock, so poisoning never matters.
+        self.lock.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    #[inline]
+    fn is_marked(&self) -> bool {
+        self.marked.load(Ordering::Acquire)
+    }
+}
+
+struct Node<T> {
+    links: Links<T>,
+    value: T,
+    /// Set once the node is linked on every level of its tower.
+    fully_linked: AtomicBool,
+}
+
+impl<T> Node<T> {
+    #[inline]
+    fn top_level(&self) -> usize {
+        self.links.next.len() - 1
+    }
+}
+
+/// Where a search for a key ended up: on every level, the links left of
+/// the key and the node after them.
+struct Position<'g, T> {
+    preds: [&'g Links<T>; MAX_HEIGHT],
+    succs: [Shared<'g, Node<T>>; MAX_HEIGHT],
+    /// The highest level the key was found on, if it was.
+    found: Option<usize>,
+}
+
+/// A concurrent skiplist set that locks individual nodes.
+///
+/// This is the "lazy" skiplist of Herlihy, Lev, Luchangco and Shavit: searches
+/// never lock, and `insert` and `remove` lock only the few nodes they relink,
+/// validating that nothing changed under them since the search (and retrying
+/// if it did). Removed nodes are freed with epoch-based reclamation.
+///
+/// It scales well and is much simpler than the lock-free `SkipSet`, at the
+/// cost of writers occasionally waiting on each other.
+///
+/// # Example
+///
+/// ```rust
+/// use convenient_skiplist::concurrent::LockingSkipSet;
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// let set = Arc::new(LockingSkipSet::new());
+/// let writers: Vec<_> = (0..4)
+///     .map(|t| {
+///         let set = Arc::clone(&set);
+///         thread::spawn(move || {
+///             for i in 0..100 {
+///                 set.insert(i * 4 + t);
+///             }
+///         })
+///     })
+///     .collect();
+/// for writer in writers {
+///     writer.join().unwrap();
+/// }
+///
+/// assert_eq!(set.len(), 400);
+/// assert!(set.remove(&123));
+/// assert!(!set.contains(&123));
+/// ```
+pub struct LockingSkipSet<T> {
+    head: Links<T>,
+    /// The number of levels any tower has reached, so searches can skip
+    /// the empty levels above.
+    height: AtomicUsize,
+    len: AtomicUsize,
+    /// SplitMix64 state for tower heights.
+    seed: AtomicU64,
+}
+
+impl<T: Ord + Send + 'static> LockingSkipSet<T> {
+    /// Make an empty set.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::concurrent::LockingSkipSet;
+    /// let set: LockingSkipSet<u32> = LockingSkipSet::new();
+    ///
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn new() -> Self {
+        LockingSkipSet {
+            head: Links::new(MAX_HEIGHT),
+            height: AtomicUsize::new(1),
+            len: AtomicUsize::new(0),
+            seed: AtomicU64::new(fresh_rng().next_u64()),
+        }
+    }
+
+    /// The number of elements in the set.
+    ///
+    /// With writers running, this is only a snapshot, and may already be out of date.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    /// Whether the set is empty. See `len` for the caveat about concurrent writers.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Insert `value`, returning `true` if it wasn't already in the set.
+    ///
+    /// Runs in expected `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::concurrent::LockingSkipSet;
+    /// let set = LockingSkipSet::new();
+    ///
+    /// assert!(set.insert(1));
+    /// assert!(!set.insert(1));
+    /// ```
+    pub fn insert(&self, value: T) -> bool {
+        let guard = &epoch::pin();
+        let height = random_height(&self.seed);
+        self.height.fetch_max(height, Ordering::Relaxed);
+        let node = Owned::new(Node {
+            links: Links::new(height),
+            value,
+            fully_linked: AtomicBool::new(false),
+        });
+        loop {
+            let position = self.find(&node.value, guard);
+            if let Some(level) = position.found {
+                let found = unsafe { position.succs[level].deref() };
+                if !found.links.is_marked() {
+                    // It's going in, so wait until it's all there.
+                    while !found.fully_linked.load(Ordering::Acquire) {
+                        std::hint::spin_loop();
+                    }
+                    return false;
+                }
+                // It's on its way out, so try again once it's gone.
+                continue;
+            }
+            let _locks = match Self::lock_preds(&position, height, |pred, succ, level| {
+                !pred.is_marked()
+                    && unsafe { succ.as_ref() }.is_none_or(|succ| !succ.links.is_marked())
+                    && pred.next[level].load(Ordering::Acquire, guard) == succ
+            }) {
+                Some(locks) => locks,
+                None => continue,
+            };
+            for (level, link) in node.links.next.iter().enumerate() {
+                link.store(position.succs[level], Ordering::Relaxed);
+            }
+            let node = node.into_shared(guard);
+            for level in 0..height {
+                position.preds[level].next[level].store(node, Ordering::Release);
+            }
+            self.len.fetch_add(1, Ordering::Relaxed);
+            unsafe { node.deref() }
+                .fully_linked
+                .store(true, Ordering::Release);
+            return true;
+        }
+    }
+
+    /// Remove `value`, returning `true` if it was in the set.
+    ///
+    /// Runs in expected `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::concurrent::LockingSkipSet;
+    /// let set = LockingSkipSet::new();
+    /// set.insert("a");
+    ///
+    /// assert!(set.remove(&"a"));
+    /// assert!(!set.remove(&"a"));
+    /// ```
+    pub fn remove<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let guard = &epoch::pin();
+        // Once marked, the node we're removing and the lock we hold on it.
+        let mut victim: Option<(Shared<'_, Node<T>>, MutexGuard<'_, ()>)> = None;
+        loop {
+            let position = self.find(value, guard);
+            if victim.is_none() {
+                let level = match position.found {
+                    Some(level) => level,
+                    None => return false,
+                };
+                let found = position.succs[level];
+                let node = unsafe { found.deref() };
+                // Only remove a node that's all there, found at the top of its tower.
+                if !node.fully_linked.load(Ordering::Acquire)
+                    || node.top_level() != level
+                    || node.links.is_marked()
+                {
+                    return false;
+                }
+                let lock = node.links.lock();
+                if node.links.is_marked() {
+                    return false;
+                }
+                node.links.marked.store(true, Ordering::Release);
+                self.len.fetch_sub(1, Ordering::Relaxed);
+                victim = Some((found, lock));
+            }
+            // INVARIANT: set above, or on an earlier try.
+            let found = victim.as_ref().unwrap().0;
+            let node = unsafe { found.deref() };
+            let height = node.top_level() + 1;
+            let _locks = match Self::lock_preds(&position, height, |pred, _, level| {
+                !pred.is_marked() && pred.next[level].load(Ordering::Acquire, guard) == found
+            }) {
+                Some(locks) => locks,
+                None => continue,
+            };
+            for level in (0..height).rev() {
+                let next = node.links.next[level].load(Ordering::Acquire, guard);
+                position.preds[level].next[level].store(next, Ordering::Release);
+            }
+            drop(victim);
+            unsafe { guard.defer_destroy(found) };
+            return true;
+        }
+    }
+
+    /// Check if `value` is in the set. This never locks.
+    ///
+    /// Runs in expected `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::concurrent::LockingSkipSet;
+    /// let set = LockingSkipSet::new();
+    /// set.insert(String::from("a"));
+    ///
+    /// assert!(set.contains("a"));
+    /// assert!(!set.contains("b"));
+    /// ```
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let guard = &epoch::pin();
+        let position = self.find(value, guard);
+        match position.found {
+            Some(level) => {
+                let node = unsafe { position.succs[level].deref() };
+                node.fully_linked.load(Ordering::Acquire) && !node.links.is_marked()
+            }
+            None => false,
+        }
+    }
+
+    /// Copy the elements out in ascending order.
+    ///
+    /// With writers running, this sees each element as it was when the
+    /// copy passed it, so it's not an atomic snapshot of the whole set.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::concurrent::LockingSkipSet;
+    /// let set = LockingSkipSet::new();
+    /// set.insert(2);
+    /// set.insert(1);
+    ///
+    /// assert_eq!(set.to_vec(), vec![1, 2]);
+    /// ```
+    pub fn to_vec(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        let mut items = Vec::with_capacity(self.len());
+        self.for_each(|item| items.push(item.clone()));
+        items
+    }
+
+    /// Call `f` on every element in ascending order, with the caveat of `to_vec`.
+    fn for_each<F: FnMut(&T)>(&self, mut f: F) {
+        let guard = &epoch::pin();
+        let mut curr = self.head.next[0].load(Ordering::Acquire, guard);
+        while let Some(node) = unsafe { curr.as_ref() } {
+            if node.fully_linked.load(Ordering::Acquire) && !node.links.is_marked() {
+                f(&node.value);
+            }
+            curr = node.links.next[0].load(Ordering::Acquire, guard);
+        }
+    }
+
+    /// Search for `value`, without locking anything.
+    fn find<'g, Q>(&'g self, value: &Q, guard: &'g Guard) -> Position<'g, T>
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut position = Position {
+            preds: [&self.head; MAX_HEIGHT],
+            succs: [Shared::null(); MAX_HEIGHT],
+            found: None,
+        };
+        let mut pred = &self.head;
+        for level in (0..self.height.load(Ordering::Relaxed)).rev() {
+            let mut curr = pred.next[level].load(Ordering::Acquire, guard);
+            while let Some(node) = unsafe { curr.as_ref() } {
+                if node.value.borrow() < value {
+                    pred = &node.links;
+                    curr = node.links.next[level].load(Ordering::Acquire, guard);
+                } else {
+                    if position.found.is_none() && node.value.borrow() == value {
+                        position.found = Some(level);
+                    }
+                    break;
+                }
+            }
+            position.preds[level] = pred;
+            position.succs[level] = curr;
+        }
+        position
+    }
+
+    /// Lock the predecessors on the bottom `height` levels of `position`,
+    /// checking each with `valid` once it's locked. Returns the locks if
+    /// every level was valid, or `None` (unlocking everything) otherwise.
+    fn lock_preds<'g, F>(
+        position: &Position<'g, T>,
+        height: usize,
+        mut valid: F,
+    ) -> Option<Vec<MutexGuard<'g, ()>>>
+    where
+        F: FnMut(&Links<T>, Shared<'g, Node<T>>, usize) -> bool,
+    {
+        let mut locks = Vec::with_capacity(height);
+        let mut prev: Option<&Links<T>> = None;
+        for level in 0..height {
+            let pred = position.preds[level];
+            // A node can be the predecessor on several levels in a row.
+            if !prev.is_some_and(|prev| std::ptr::eq(prev, pred)) {
+                locks.push(pred.lock());
+                prev = Some(pred);
+            }
+            if !valid(pred, position.succs[level], level) {
+                return None;
+            }
+        }
+        Some(locks)
+    }
+}
+
+impl<T: Ord + Send + 'static> Default for LockingSkipSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord + Send + fmt::Debug + 'static> fmt::Debug for LockingSkipSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut set = f.debug_set();
+        self.for_each(|item| {
+            set.entry(item);
+        });
+        set.finish()
+    }
+}
+
+impl<T> Drop for LockingSkipSet<T> {
+    fn drop(&mut self) {
+        // Nobody else can reach the set now, and every node not yet handed
+        // to the collector is still linked on the bottom level.
+        unsafe {
+            let guard = epoch::unprotected();
+            let mut curr = self.head.next[0].load(Ordering::Relaxed, guard);
+            while let Some(node) = curr.as_ref() {
+                let next = node.links.next[0].load(Ordering::Relaxed, guard);
+                drop(curr.into_owned());
+                curr = next;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LockingSkipSet;
+    use rand::prelude::*;
+    use std::collections::BTreeSet;
+    use std::sync::{Arc, Barrier};
+    use std::thread;
+
+    #[test]
+    fn test_matches_btreeset() {
+        let set = LockingSkipSet::new();
+        let mut expected = BTreeSet::new();
+        let mut rng = StdRng::seed_from_u64(0x3145);
+        for _ in 0..5000 {
+            let item = rng.gen_range(0u32, 500);
+            if rng.gen() {
+                assert_eq!(set.insert(item), expected.insert(item));
+            } else {
+                assert_eq!(set.remove(&item), expected.remove(&item));
+            }
+            assert_eq!(set.len(), expected.len());
+        }
+        assert_eq!(set.to_vec(), expected.into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_concurrent_writers() {
+        let set = Arc::new(LockingSkipSet::new());
+        let barrier = Arc::new(Barrier::new(8));
+        let workers: Vec<_> = (0..8u64)
+            .map(|t| {
+                let set = Arc::clone(&set);
+                let barrier = Arc::clone(&barrier);
+                thread::spawn(move || {
+                    let mut rng = StdRng::seed_from_u64(t);
+                    // Everyone fights over the same 200 keys, then settles
+                    // the keys it owns.
+                    for _ in 0..2000 {
+                        let item = rng.gen_range(0u64, 200);
+                        if rng.gen() {
+                            set.insert(item);
+                        } else {
+                            set.remove(&item);
+                        }
+                    }
+                    barrier.wait();
+                    for item in (0..200).filter(|i| i % 8 == t) {
+                        set.insert(item);
+                    }
+                    for item in (0..200).filter(|i| i % 8 == t && i % 3 == 0) {
+                        assert!(set.remove(&item));
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        let expected: Vec<_> = (0..200).filter(|i| i % 3 != 0).collect();
+        assert_eq!(set.to_vec(), expected);
+        assert_eq!(set.len(), expected.len());
+        assert!(expected.iter().all(|i| set.contains(i)));
+    }
+}
@@ -0,0 +1,534 @@
+use crate::iter::{LeftBiasIter, VerticalIter};
+use crate::map::KeyRef;
+use crate::{Node, SkipList};
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::{FromIterator, FusedIterator};
+use std::marker::PhantomData;
+
+/// A distinct element stored in the skiplist, with how many copies of it there are.
+#[derive(Clone)]
+struct Counted<T> {
+    value: T,
+    count: usize,
+}
+
+impl<T: PartialEq> PartialEq for Counted<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: PartialOrd> PartialOrd for Counted<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl<T: Borrow<Q>, Q: ?Sized> Borrow<KeyRef<Q>> for Counted<T> {
+    #[inline]
+    fn borrow(&self) -> &KeyRef<Q> {
+        KeyRef::new(self.value.borrow())
+    }
+}
+
+/// A multiset that stores each distinct element once, along with how many
+/// copies of it there are.
+///
+/// Memory is proportional to the number of distinct elements, which makes
+/// this a better fit than `SkipMultiSet` when there's lots of repetition.
+/// Node widths count every copy, so indexing and rank queries still treat
+/// the copies as separate elements.
+///
+/// As copies aren't stored separately, they can't be told apart: use
+/// `SkipMultiSet` if equal elements carry different data.
+///
+/// # Example
+///
+/// ```rust
+/// use convenient_skiplist::CountedMultiSet;
+/// let mut ms = CountedMultiSet::new();
+/// for word in "the cat and the hat and the bat".split(' ') {
+///     ms.insert(word);
+/// }
+///
+/// assert_eq!(ms.count("the"), 3);
+/// assert_eq!(ms.len(), 8);
+/// assert_eq!(ms.distinct_len(), 5);
+/// // "and", "and", "bat", "cat", "hat", "the", ...
+/// assert_eq!(ms.at_index(4), Some(&"hat"));
+/// assert_eq!(ms.index_of("the"), Some(5));
+/// ```
+pub struct CountedMultiSet<T> {
+    /// The skiplist's length and widths count every copy, while its
+    /// bottom row only has a node per distinct element.
+    inner: SkipList<Counted<T>>,
+    distinct: usize,
+}
+
+impl<T: PartialOrd> CountedMultiSet<T> {
+    /// Make a new, empty CountedMultiSet.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::CountedMultiSet;
+    /// let ms: CountedMultiSet<u32> = CountedMultiSet::new();
+    /// assert!(ms.is_empty());
+    /// ```
+    #[inline]
+    pub fn new() -> CountedMultiSet<T> {
+        CountedMultiSet {
+            inner: SkipList::new(),
+            distinct: 0,
+        }
+    }
+
+    /// Add a copy of `item`.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::CountedMultiSet;
+    /// let mut ms = CountedMultiSet::new();
+    /// ms.insert('a');
+    /// ms.insert('a');
+    ///
+    /// assert_eq!(ms.count(&'a'), 2);
+    /// assert_eq!(ms.distinct_len(), 1);
+    /// ```
+    pub fn insert(&mut self, item: T) {
+        if self.add_copies(&item, 1).is_none() {
+            self.inner.insert(Counted {
+                value: item,
+                count: 1,
+            });
+            self.distinct += 1;
+        }
+    }
+
+    /// Remove one copy of `item`. Returns `true` if there was one to remove.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::CountedMultiSet;
+    /// let mut ms: CountedMultiSet<_> = vec![1, 1].into_iter().collect();
+    ///
+    /// assert!(ms.remove(&1));
+    /// assert_eq!(ms.count(&1), 1);
+    /// assert!(ms.remove(&1));
+    /// assert!(!ms.remove(&1));
+    /// ```
+    pub fn remove<Q>(&mut self, item: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        match self.count(item) {
+            0 => false,
+            1 => {
+                self.inner.take(KeyRef::new(item));
+                self.distinct -= 1;
+                true
+            }
+            _ => self.add_copies(item, -1).is_some(),
+        }
+    }
+
+    /// Remove every copy of `item`, returning how many were removed.
+    ///
+    /// Runs in `O(logn)` time, however many copies there are.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::CountedMultiSet;
+    /// let mut ms: CountedMultiSet<_> = vec![1, 2, 2, 2].into_iter().collect();
+    ///
+    /// assert_eq!(ms.remove_all(&2), 3);
+    /// assert_eq!(ms.len(), 1);
+    /// ```
+    pub fn remove_all<Q>(&mut self, item: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        let count = self.count(item);
+        if count == 0 {
+            return 0;
+        }
+        // Shrink it to a single copy, so it's removed like any other element.
+        self.add_copies(item, 1 - count as isize);
+        self.inner.take(KeyRef::new(item));
+        self.distinct -= 1;
+        count
+    }
+
+    /// Count the copies of `item`.
+    ///
+    /// Runs in `O(logn)` time.
+    #[inline]
+    pub fn count<Q>(&self, item: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.inner
+            .get(KeyRef::new(item))
+            .map_or(0, |counted| counted.count)
+    }
+
+    /// Check if there's at least one copy of `item`.
+    ///
+    /// Runs in `O(logn)` time.
+    #[inline]
+    pub fn contains<Q>(&self, item: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.inner.contains(KeyRef::new(item))
+    }
+
+    /// Return the number of elements in the multiset, counting every copy.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Return the number of distinct elements in the multiset.
+    #[inline]
+    pub fn distinct_len(&self) -> usize {
+        self.distinct
+    }
+
+    /// Returns true if the multiset is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Count how many elements are strictly less than `item`, counting every copy.
+    ///
+    /// Runs in `O(logn)` time.
+    #[inline]
+    pub fn rank<Q>(&self, item: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.inner.rank(KeyRef::new(item))
+    }
+
+    /// Find the index of the first copy of `item`, counting every copy
+    /// of the elements before it.
+    ///
+    /// Runs in `O(logn)` time.
+    #[inline]
+    pub fn index_of<Q>(&self, item: &Q) -> Option<usize>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.inner.index_of(KeyRef::new(item))
+    }
+
+    /// Get the element at `index`, counting every copy.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::CountedMultiSet;
+    /// let ms: CountedMultiSet<_> = vec!['a', 'b', 'b', 'c'].into_iter().collect();
+    ///
+    /// assert_eq!(ms.at_index(2), Some(&'b'));
+    /// assert_eq!(ms.at_index(3), Some(&'c'));
+    /// assert_eq!(ms.at_index(4), None);
+    /// ```
+    pub fn at_index(&self, index: usize) -> Option<&T> {
+        if index >= self.len() {
+            return None;
+        }
+        // Find the node left of the first element whose copies reach `index`.
+        let mut distance_left = index + 1;
+        let mut curr_node = self.inner.top_left.as_ptr();
+        unsafe {
+            loop {
+                while (*curr_node).width() < distance_left {
+                    distance_left -= (*curr_node).width();
+                    // INVARIANT: `index` < len, so this never walks past PosInf.
+                    curr_node = (*curr_node).right.unwrap().as_ptr();
+                }
+                match (*curr_node).down {
+                    Some(down) => curr_node = down.as_ptr(),
+                    None => break,
+                }
+            }
+            Some(
+                &(*(*curr_node).right.unwrap().as_ptr())
+                    .value
+                    .get_value()
+                    .value,
+            )
+        }
+    }
+
+    /// Iterate over every copy of every element, in order.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::CountedMultiSet;
+    /// let ms: CountedMultiSet<_> = vec![2, 1, 2].into_iter().collect();
+    ///
+    /// assert_eq!(ms.iter().collect::<Vec<_>>(), vec![&1, &2, &2]);
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            counts: self.iter_counts(),
+            curr: None,
+            remaining: self.len(),
+        }
+    }
+
+    /// Iterate over the distinct elements in order, along with their counts.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::CountedMultiSet;
+    /// let ms: CountedMultiSet<_> = vec![2, 1, 2].into_iter().collect();
+    ///
+    /// assert_eq!(ms.iter_counts().collect::<Vec<_>>(), vec![(&1, 1), (&2, 2)]);
+    /// ```
+    #[inline]
+    pub fn iter_counts(&self) -> Counts<'_, T> {
+        Counts {
+            // INVARIANT: There's always a bottom row, starting with NegInf.
+            curr_node: VerticalIter::new(self.inner.top_left.as_ptr())
+                .last()
+                .unwrap(),
+            remaining: self.distinct,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Add `delta` copies to an element that's already in the multiset,
+    /// returning its new count, or `None` if it isn't in the multiset.
+    ///
+    /// The count must stay above zero.
+    fn add_copies<Q>(&mut self, item: &Q, delta: isize) -> Option<usize>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        let item = KeyRef::new(item);
+        let path: Vec<_> = LeftBiasIter::new(self.inner.top_left.as_ptr(), item).collect();
+        // INVARIANT: The path always ends on the bottom row, with a node right of it.
+        let left = *path.last().unwrap();
+        unsafe {
+            let right = &mut (*(*left).right.unwrap().as_ptr()).value;
+            if !right.eq_key(item) {
+                return None;
+            }
+            // Every node on the path is left of the element and spans over it,
+            // so they're exactly the widths that change.
+            for node in path {
+                (*node).set_width(((*node).width() as isize + delta) as usize);
+            }
+            let counted = right.get_value_mut();
+            counted.count = (counted.count as isize + delta) as usize;
+            debug_assert!(counted.count > 0);
+            self.inner.len = (self.inner.len as isize + delta) as usize;
+            Some(counted.count)
+        }
+    }
+}
+
+impl<T: PartialOrd> Default for CountedMultiSet<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PartialOrd + Clone> Clone for CountedMultiSet<T> {
+    fn clone(&self) -> Self {
+        let mut ms = CountedMultiSet::new();
+        for (item, count) in self.iter_counts() {
+            ms.insert(item.clone());
+            ms.add_copies(item, count as isize - 1);
+        }
+        ms
+    }
+}
+
+impl<T: PartialOrd + fmt::Debug> fmt::Debug for CountedMultiSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter_counts()).finish()
+    }
+}
+
+impl<T: PartialOrd> FromIterator<T> for CountedMultiSet<T> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut ms = CountedMultiSet::new();
+        ms.extend(iter);
+        ms
+    }
+}
+
+impl<T: PartialOrd> Extend<T> for CountedMultiSet<T> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.insert(item);
+        }
+    }
+}
+
+impl<'a, T: PartialOrd> IntoIterator for &'a CountedMultiSet<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the distinct elements of a `CountedMultiSet` and their counts,
+/// made by `CountedMultiSet::iter_counts`.
+pub struct Counts<'a, T> {
+    /// The last node yielded on the bottom row, or NegInf.
+    curr_node: *const Node<Counted<T>>,
+    remaining: usize,
+    _marker: PhantomData<&'a CountedMultiSet<T>>,
+}
+
+impl<'a, T> Iterator for Counts<'a, T> {
+    type Item = (&'a T, usize);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        unsafe {
+            // As there's elements remaining, the node to our right is never PosInf.
+            self.curr_node = (*self.curr_node).right.unwrap().as_ptr();
+            let counted = (*self.curr_node).value.get_value();
+            Some((&counted.value, counted.count))
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Counts<'a, T> {}
+
+impl<'a, T> FusedIterator for Counts<'a, T> {}
+
+/// Iterator over every copy of the elements of a `CountedMultiSet`,
+/// made by `CountedMultiSet::iter`.
+pub struct Iter<'a, T> {
+    counts: Counts<'a, T>,
+    /// The element being repeated, and how many more copies of it to yield.
+    curr: Option<(&'a T, usize)>,
+    remaining: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match &mut self.curr {
+                Some((item, copies)) if *copies > 0 => {
+                    *copies -= 1;
+                    self.remaining -= 1;
+                    return Some(*item);
+                }
+                _ => self.curr = Some(self.counts.next()?),
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> FusedIterator for Iter<'a, T> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::CountedMultiSet;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_counted_matches_sorted_vec() {
+        let mut ms = CountedMultiSet::new();
+        let mut expected: Vec<u32> = Vec::new();
+        let mut rng = StdRng::seed_from_u64(0x3114);
+        for _ in 0..5000 {
+            let item = rng.gen_range(0u32, 40);
+            match rng.gen_range(0, 20) {
+                0 => {
+                    let before = expected.len();
+                    expected.retain(|&x| x != item);
+                    assert_eq!(ms.remove_all(&item), before - expected.len());
+                }
+                1..=6 => {
+                    let position = expected.iter().position(|&x| x == item);
+                    assert_eq!(ms.remove(&item), position.is_some());
+                    if let Some(position) = position {
+                        expected.remove(position);
+                    }
+                }
+                _ => {
+                    ms.insert(item);
+                    let position = expected.partition_point(|&x| x <= item);
+                    expected.insert(position, item);
+                }
+            }
+            assert_eq!(ms.len(), expected.len());
+            assert_eq!(
+                ms.count(&item),
+                expected.iter().filter(|&&x| x == item).count()
+            );
+            assert_eq!(ms.rank(&item), expected.partition_point(|&x| x < item));
+            assert_eq!(ms.index_of(&item), expected.iter().position(|&x| x == item));
+        }
+        for (index, item) in expected.iter().enumerate() {
+            assert_eq!(ms.at_index(index), Some(item));
+        }
+        assert_eq!(ms.at_index(expected.len()), None);
+        assert!(ms.iter().eq(expected.iter()));
+        assert_eq!(ms.iter().len(), expected.len());
+        let mut distinct = expected.clone();
+        distinct.dedup();
+        assert_eq!(ms.distinct_len(), distinct.len());
+        assert!(ms.clone().iter().eq(expected.iter()));
+    }
+}
@@ -0,0 +1,462 @@
+use crate::builder::{LevelGenerator, DEFAULT_MAX_HEIGHT};
+use crate::iter::IterAll;
+use crate::{tower_levels, Node, SkipList};
+use std::borrow::Borrow;
+use std::fmt;
+use std::iter::FromIterator;
+use std::ptr::NonNull;
+
+/// A skiplist whose shape is maintained deterministically, with no RNG.
+///
+/// This is a top-down 1-2-3 skiplist: between two neighbouring nodes on a level,
+/// the level below always has one to three nodes. Inserts split full gaps on the
+/// way down, and removals fill sparse gaps on the way down, so every operation
+/// takes `O(logn)` time in the worst case rather than in expectation. That suits
+/// soft real-time uses where a probabilistic tail latency isn't acceptable.
+///
+/// Only operations that keep the gaps intact are offered directly.
+/// `as_skiplist` gives access to the read-only queries of `SkipList`.
+///
+/// # Example
+///
+/// ```rust
+/// use convenient_skiplist::DeterministicSkipList;
+/// let mut sk = DeterministicSkipList::new();
+/// for i in 0..100u32 {
+///     sk.insert(i);
+/// }
+/// assert!(sk.remove(&50));
+///
+/// assert_eq!(sk.len(), 99);
+/// assert_eq!(sk.at_index(50), Some(&51));
+/// assert_eq!(sk.as_skiplist().rank(&75), 74);
+/// ```
+pub struct DeterministicSkipList<T> {
+    inner: SkipList<T>,
+}
+
+impl<T: PartialOrd> DeterministicSkipList<T> {
+    /// Make a new, empty DeterministicSkipList.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::DeterministicSkipList;
+    /// let sk: DeterministicSkipList<u32> = DeterministicSkipList::new();
+    /// assert!(sk.is_empty());
+    /// ```
+    #[inline]
+    pub fn new() -> DeterministicSkipList<T> {
+        // Tower heights are never drawn from the generator, so a fixed seed
+        // saves seeding one from `thread_rng`.
+        DeterministicSkipList {
+            inner: SkipList::with_level_generator(LevelGenerator::new(
+                0.5,
+                DEFAULT_MAX_HEIGHT,
+                Some(0),
+            )),
+        }
+    }
+
+    /// Insert `item`, returning `true` if it wasn't already in the skiplist.
+    ///
+    /// Runs in `O(logn)` time in the worst case.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::DeterministicSkipList;
+    /// let mut sk = DeterministicSkipList::new();
+    ///
+    /// assert!(sk.insert(1));
+    /// assert!(!sk.insert(1));
+    /// ```
+    pub fn insert(&mut self, item: T) -> bool {
+        if self.inner.contains(&item) {
+            return false;
+        }
+        // The top row stays empty, so make room above a full row below it.
+        if unsafe { Self::group_len(self.inner.top_left.as_ptr()) } == 4 {
+            self.inner.add_levels(1);
+        }
+        let mut curr_node = self.inner.top_left.as_ptr();
+        unsafe {
+            while (*curr_node).down.is_some() {
+                // Split a full gap before dropping into it, so it has room for
+                // the node it may gain from the level below.
+                if Self::group_len(curr_node) == 4 {
+                    let raised = self.raise(curr_node, 2);
+                    if (*raised).value.lt_key(&item) {
+                        curr_node = raised;
+                    }
+                }
+                curr_node = Self::advance((*curr_node).down.unwrap().as_ptr(), &item);
+            }
+            let mut path = self.inner.search_path(&item, false);
+            let tower = self.inner.pool.make_tower(item, 1);
+            self.inner.stitch_tower(&mut path, 1, tower_levels(tower));
+        }
+        true
+    }
+
+    /// Remove `item`, returning `true` if it was in the skiplist.
+    ///
+    /// Runs in `O(logn)` time in the worst case.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::DeterministicSkipList;
+    /// let mut sk: DeterministicSkipList<_> = (0..10).collect();
+    ///
+    /// assert!(sk.remove(&3));
+    /// assert!(!sk.remove(&3));
+    /// ```
+    #[inline]
+    pub fn remove<Q>(&mut self, item: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.take(item).is_some()
+    }
+
+    /// Remove `item` and return it, or `None` if it's not in the skiplist.
+    ///
+    /// Runs in `O(logn)` time in the worst case.
+    pub fn take<Q>(&mut self, item: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        if !self.inner.contains(item) {
+            return None;
+        }
+        self.inner.shrink_levels();
+        let mut parent: Option<*mut Node<T>> = None;
+        let mut curr_node = self.inner.top_left.as_ptr();
+        unsafe {
+            while let Some(down) = (*curr_node).down {
+                // Fill a sparse gap before dropping into it, so it can lose a node.
+                if let Some(parent) = parent {
+                    curr_node = self.fill_group(parent, curr_node);
+                }
+                parent = Some(curr_node);
+                curr_node = Self::advance(down.as_ptr(), item);
+            }
+            // INVARIANT: The skiplist always has a row above the bottom one.
+            let above_bottom = parent.unwrap();
+            let taken = if (*above_bottom).right.unwrap().as_ref().value.eq_key(item) {
+                // `item` has a tall tower, so its gaps can't lose it. Instead, remove
+                // its predecessor -- which is only on the bottom row, as its gap
+                // has two or more nodes -- and put it in place of `item`.
+                // The whole tower shares the value, so swapping it relabels every level.
+                let bottom = (*curr_node).right.unwrap().as_ptr();
+                // `take` is done with `pred` before it moves the value out.
+                let pred = &*((*curr_node).value.get_value() as *const T);
+                let pred = self.inner.take::<T>(pred).unwrap();
+                std::mem::replace((*bottom).value.get_value_mut(), pred)
+            } else {
+                self.inner.take(item).unwrap()
+            };
+            self.inner.shrink_levels();
+            Some(taken)
+        }
+    }
+
+    /// Check if `item` is in the skiplist.
+    ///
+    /// Runs in `O(logn)` time in the worst case.
+    #[inline]
+    pub fn contains<Q>(&self, item: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.inner.contains(item)
+    }
+
+    /// Get a reference to the element equal to `item`.
+    ///
+    /// Runs in `O(logn)` time in the worst case.
+    #[inline]
+    pub fn get<Q>(&self, item: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.inner.get(item)
+    }
+
+    /// Get the element at `index`.
+    ///
+    /// Runs in `O(logn)` time in the worst case.
+    #[inline]
+    pub fn at_index(&self, index: usize) -> Option<&T> {
+        self.inner.at_index(index)
+    }
+
+    /// Find the index of `item`.
+    ///
+    /// Runs in `O(logn)` time in the worst case.
+    #[inline]
+    pub fn index_of<Q>(&self, item: &Q) -> Option<usize>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.inner.index_of(item)
+    }
+
+    /// Return the number of elements in the skiplist.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns true if the skiplist is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Iterator over all elements in the skiplist, in ascending order.
+    #[inline]
+    pub fn iter(&self) -> IterAll<'_, T> {
+        self.inner.iter_all()
+    }
+
+    /// Borrow the underlying `SkipList`, for its read-only queries.
+    #[inline]
+    pub fn as_skiplist(&self) -> &SkipList<T> {
+        &self.inner
+    }
+
+    /// Make sure the gap below `curr_node` has at least three nodes, by borrowing
+    /// from or merging with a neighbouring gap under `parent`. Returns the node
+    /// whose gap now holds what `curr_node`'s did.
+    ///
+    /// Only the node at the top of its tower is ever lowered, and the gap under
+    /// `parent` must already have three or more nodes unless it's the top one.
+    unsafe fn fill_group(&mut self, parent: *mut Node<T>, curr_node: *mut Node<T>) -> *mut Node<T> {
+        if Self::group_len(curr_node) > 2 {
+            return curr_node;
+        }
+        let siblings = Self::group(parent);
+        // INVARIANT: `curr_node` is one of the nodes `parent` spans.
+        let position = siblings.iter().position(|s| *s == curr_node).unwrap();
+        if let Some(&right) = siblings.get(position + 1) {
+            if Self::group_len(right) > 2 {
+                self.raise(right, 1);
+            }
+            self.lower(curr_node);
+            curr_node
+        } else if position > 0 {
+            let left = siblings[position - 1];
+            let left_len = Self::group_len(left);
+            let left = if left_len > 2 {
+                self.raise(left, left_len - 1)
+            } else {
+                left
+            };
+            self.lower(left);
+            left
+        } else {
+            // `curr_node` is all the top gap has, so it's the root.
+            curr_node
+        }
+    }
+
+    /// Promote the `index`th node below `left` into `left`'s row, right after `left`.
+    unsafe fn raise(&mut self, left: *mut Node<T>, index: usize) -> *mut Node<T> {
+        let mut child = (*left).down.unwrap().as_ptr();
+        let mut distance = 0;
+        for _ in 0..index {
+            distance += (*child).width();
+            child = (*child).right.unwrap().as_ptr();
+        }
+        let node = self
+            .inner
+            .pool
+            .make_node_above(NonNull::new_unchecked(child))
+            .as_ptr();
+        (*node).set_width((*left).width() - distance);
+        (*node).right = (*left).right;
+        (*left).right = NonNull::new(node);
+        (*left).set_width(distance);
+        node
+    }
+
+    /// Unlink the node right of `left`, which must be the top of its tower.
+    unsafe fn lower(&mut self, left: *mut Node<T>) {
+        let node = (*left).right.unwrap();
+        (*left).set_width((*left).width() + node.as_ref().width());
+        (*left).right = node.as_ref().right;
+        self.inner.pool.free(node);
+    }
+
+    /// The nodes in the row below `node` that it spans, starting with the one under it.
+    unsafe fn group(node: *mut Node<T>) -> Vec<*mut Node<T>> {
+        let mut group = Vec::with_capacity(4);
+        let mut spanned = 0;
+        let mut child = (*node).down.unwrap().as_ptr();
+        while spanned < (*node).width() {
+            group.push(child);
+            spanned += (*child).width();
+            child = (*child).right.unwrap().as_ptr();
+        }
+        group
+    }
+
+    /// How many nodes the row below `node` has in its span.
+    unsafe fn group_len(node: *mut Node<T>) -> usize {
+        let mut len = 0;
+        let mut spanned = 0;
+        let mut child = (*node).down.unwrap().as_ptr();
+        while spanned < (*node).width() {
+            len += 1;
+            spanned += (*child).width();
+            child = (*child).right.unwrap().as_ptr();
+        }
+        len
+    }
+
+    /// Move right from `node` along its row while the next node is less than `item`.
+    unsafe fn advance<Q>(mut node: *mut Node<T>, item: &Q) -> *mut Node<T>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        while let Some(right) = (*node).right {
+            if !right.as_ref().value.lt_key(item) {
+                break;
+            }
+            node = right.as_ptr();
+        }
+        node
+    }
+}
+
+impl<T: PartialOrd> Default for DeterministicSkipList<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PartialOrd + Clone> Clone for DeterministicSkipList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: PartialOrd + fmt::Debug> fmt::Debug for DeterministicSkipList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialOrd> FromIterator<T> for DeterministicSkipList<T> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut sk = DeterministicSkipList::new();
+        sk.extend(iter);
+        sk
+    }
+}
+
+impl<T: PartialOrd> Extend<T> for DeterministicSkipList<T> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.insert(item);
+        }
+    }
+}
+
+impl<'a, T: PartialOrd> IntoIterator for &'a DeterministicSkipList<T> {
+    type Item = &'a T;
+    type IntoIter = IterAll<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::DeterministicSkipList;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::BTreeSet;
+
+    /// Check every gap has one to three nodes, aside from the top one.
+    fn ensure_gaps(sk: &DeterministicSkipList<u32>) {
+        let levels: Vec<Vec<(usize, usize)>> = sk
+            .as_skiplist()
+            .iter_levels()
+            .map(|level| {
+                let mut column = 0;
+                level
+                    .map(|(_, width)| {
+                        let node = (column, width);
+                        column += width;
+                        node
+                    })
+                    .collect()
+            })
+            .collect();
+        for (depth, pair) in levels.windows(2).enumerate() {
+            for &(column, width) in &pair[0] {
+                let group_len = pair[1]
+                    .iter()
+                    .filter(|(below, _)| (column..column + width).contains(below))
+                    .count();
+                let is_root = depth == 0 || (depth == 1 && pair[0].len() == 1);
+                assert!(group_len <= 4);
+                assert!(is_root || group_len >= 2, "sparse gap at depth {}", depth);
+            }
+        }
+        let height_bound = ((sk.len() + 1) as f64).log2().ceil() as usize + 3;
+        assert!(levels.len() <= height_bound.max(3));
+    }
+
+    #[test]
+    fn test_deterministic_matches_btree() {
+        let mut sk = DeterministicSkipList::new();
+        let mut expected = BTreeSet::new();
+        let mut rng = StdRng::seed_from_u64(0x3120);
+        for step in 0..6000 {
+            let item = rng.gen_range(0u32, 400);
+            // Grow first, then mostly shrink.
+            if rng.gen_bool(if step < 3000 { 0.3 } else { 0.7 }) {
+                assert_eq!(sk.remove(&item), expected.remove(&item));
+            } else {
+                assert_eq!(sk.insert(item), expected.insert(item));
+            }
+            ensure_gaps(&sk);
+        }
+        assert!(sk.iter().eq(expected.iter()));
+        for (index, item) in expected.iter().enumerate() {
+            assert_eq!(sk.index_of(item), Some(index));
+        }
+        for item in expected {
+            assert_eq!(sk.take(&item), Some(item));
+            ensure_gaps(&sk);
+        }
+        assert!(sk.is_empty());
+    }
+
+    #[test]
+    fn test_deterministic_same_shape() {
+        let build = || (0..300u32).rev().collect::<DeterministicSkipList<_>>();
+        let (left, right) = (build(), build());
+        assert!(left
+            .as_skiplist()
+            .iter_levels()
+            .zip(right.as_skiplist().iter_levels())
+            .all(|(left, right)| left.eq(right)));
+        ensure_gaps(&left);
+    }
+}
@@ -0,0 +1,122 @@
+use crate::iter::{Generation, SearchPath};
+use crate::SkipList;
+use std::borrow::Borrow;
+use std::marker::PhantomData;
+
+/// A search handle that remembers where its last search ended, made by `SkipList::finger`.
+///
+/// Searching from a finger costs `O(log d)`, where d is how many elements
+/// away the new item is from the last one searched, rather than `O(logn)`.
+/// This is a big win when queries are close together, like lookups into
+/// a time series that mostly look at recent entries.
+///
+/// # Example
+///
+/// ```rust
+/// use convenient_skiplist::SkipList;
+/// let sk = SkipList::from(0..1_000_000u32);
+/// let mut finger = sk.finger();
+///
+/// assert_eq!(finger.index_of(&500_000), Some(500_000));
+/// // Nearby searches, in either direction, only look at nearby nodes.
+/// assert!(finger.contains(&500_010));
+/// assert_eq!(finger.rank(&499_990), 499_990);
+/// ```
+pub struct Finger<'a, T> {
+    path: SearchPath<T>,
+    generation: Generation<'a>,
+    _marker: PhantomData<&'a SkipList<T>>,
+}
+
+impl<'a, T: PartialOrd> Finger<'a, T> {
+    pub(crate) fn new(sk: &'a SkipList<T>) -> Self {
+        Self {
+            path: SearchPath::new(sk.top_left.as_ptr()),
+            generation: sk.generation(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Get the element equal to `item`, if it's in the skiplist.
+    ///
+    /// Runs in `O(log d)` time.
+    pub fn get<Q>(&mut self, item: &Q) -> Option<&'a T>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.generation.check();
+        let left = self.path.move_to(item);
+        // INVARIANT: The bottom of the path always has a node right of it.
+        let right = unsafe { &(*left.curr_node).right.unwrap().as_ref().value };
+        if right.eq_key(item) {
+            right.value()
+        } else {
+            None
+        }
+    }
+
+    /// Check if `item` is in the skiplist.
+    ///
+    /// Runs in `O(log d)` time.
+    #[inline]
+    pub fn contains<Q>(&mut self, item: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.get(item).is_some()
+    }
+
+    /// Find the index of `item`, if it's in the skiplist.
+    ///
+    /// Runs in `O(log d)` time.
+    pub fn index_of<Q>(&mut self, item: &Q) -> Option<usize>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.get(item)?;
+        Some(self.rank(item))
+    }
+
+    /// Count how many elements in the skiplist are strictly less than `item`.
+    ///
+    /// Runs in `O(log d)` time.
+    #[inline]
+    pub fn rank<Q>(&mut self, item: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.generation.check();
+        // NegInf is index 0, so the node left of `item` is at `rank`.
+        self.path.move_to(item).curr_width
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SkipList;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_finger() {
+        let sk: SkipList<u32> = (0..2000).map(|i| i * 2).collect();
+        let mut finger = sk.finger();
+        let mut rng = StdRng::seed_from_u64(0x3096);
+        let mut item = 2000u32;
+        for _ in 0..5000 {
+            // Mostly small steps, with the occasional big jump either way.
+            item = if rng.gen_range(0, 10) == 0 {
+                rng.gen_range(0, 4100)
+            } else {
+                (item + rng.gen_range(0, 21)).saturating_sub(10)
+            };
+            assert_eq!(finger.index_of(&item), sk.index_of(&item), "{}", item);
+            assert_eq!(finger.rank(&item), sk.rank(&item), "{}", item);
+            assert_eq!(finger.get(&item), sk.get(&item), "{}", item);
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use crate::iter::{IntoIter, IterAll};
+use crate::SkipList;
+use std::fmt;
+use std::ops::Deref;
+
+/// An immutable skiplist, made by `SkipList::freeze`.
+///
+/// Freezing packs the towers into one allocation, laid out in order, and
+/// gives them their ideal heights (see `SkipList::rebuild`), so searches and
+/// scans are as quick as they get. Every read-only method of `SkipList` is
+/// available through `Deref`, and nothing can change the skiplist afterwards.
+///
+/// A `FrozenSkipList<T>` is `Send` and `Sync` when `T` is, which suits
+/// building a skiplist once and sharing it with many threads in an `Arc`.
+///
+/// # Example
+///
+/// ```rust
+/// use convenient_skiplist::SkipList;
+/// use std::sync::Arc;
+///
+/// let frozen = Arc::new(SkipList::from(0..100).freeze());
+/// let reader = {
+///     let frozen = Arc::clone(&frozen);
+///     std::thread::spawn(move || frozen.index_of(&42))
+/// };
+///
+/// assert_eq!(reader.join().unwrap(), Some(42));
+/// assert!(frozen.contains(&99));
+/// ```
+pub struct FrozenSkipList<T: PartialOrd> {
+    inner: SkipList<T>,
+}
+
+impl<T: PartialOrd> FrozenSkipList<T> {
+    /// Wrap `inner`, which should already be rebuilt.
+    #[inline]
+    pub(crate) fn new(inner: SkipList<T>) -> Self {
+        Self { inner }
+    }
+
+    /// Turn the frozen skiplist back into a mutable `SkipList`.
+    ///
+    /// Runs in `O(1)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let frozen = SkipList::from(0..3).freeze();
+    /// let mut sk = frozen.thaw();
+    /// sk.insert(3);
+    ///
+    /// assert_eq!(sk.len(), 4);
+    /// ```
+    #[inline]
+    pub fn thaw(self) -> SkipList<T> {
+        self.inner
+    }
+}
+
+impl<T: PartialOrd> Deref for FrozenSkipList<T> {
+    type Target = SkipList<T>;
+
+    #[inline]
+    fn deref(&self) -> &SkipList<T> {
+        &self.inner
+    }
+}
+
+impl<T: PartialOrd> From<SkipList<T>> for FrozenSkipList<T> {
+    #[inline]
+    fn from(sk: SkipList<T>) -> Self {
+        sk.freeze()
+    }
+}
+
+impl<T: PartialOrd> From<FrozenSkipList<T>> for SkipList<T> {
+    #[inline]
+    fn from(frozen: FrozenSkipList<T>) -> Self {
+        frozen.thaw()
+    }
+}
+
+impl<T: PartialOrd + Clone> Clone for FrozenSkipList<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T: PartialOrd> PartialEq for FrozenSkipList<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+}
+
+impl<T: PartialOrd + fmt::Debug> fmt::Debug for FrozenSkipList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FrozenSkipList").field(&self.inner).finish()
+    }
+}
+
+impl<T: PartialOrd> IntoIterator for FrozenSkipList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.into_iter()
+    }
+}
+
+impl<'a, T: PartialOrd> IntoIterator for &'a FrozenSkipList<T> {
+    type Item = &'a T;
+    type IntoIter = IterAll<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.iter_all()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FrozenSkipList, SkipList};
+
+    #[test]
+    fn test_freeze_keeps_contents() {
+        fn assert_send_sync<S: Send + Sync>() {}
+        assert_send_sync::<FrozenSkipList<String>>();
+
+        let mut sk = SkipList::with_seed(0x3143);
+        sk.extend((0..500u32).rev());
+        sk.remove_many((0..500).step_by(3));
+        let expected: Vec<_> = sk.iter_all().copied().collect();
+        let frozen = sk.freeze();
+        assert_eq!(frozen.iter().copied().collect::<Vec<_>>(), expected);
+        assert_eq!(frozen.len(), expected.len());
+        for (i, item) in expected.iter().enumerate() {
+            assert_eq!(frozen.index_of(item), Some(i));
+            assert_eq!(frozen.at_index(i), Some(item));
+        }
+        assert_eq!(frozen.clone(), frozen);
+        let mut thawed = frozen.thaw();
+        assert!(thawed.insert(0));
+        assert_eq!(thawed.len(), expected.len() + 1);
+    }
+}
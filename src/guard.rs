@@ -0,0 +1,172 @@
+use crate::Node;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::ptr::NonNull;
+
+/// A mutable reference to a value in a `SkipList`, made by `SkipList::get_mut`,
+/// `SkipList::iter_mut` or `SkipList::range_mut`.
+///
+/// This is meant for updating the parts of a value the skiplist isn't ordered
+/// by. Changing how the value compares to its neighbours corrupts the skiplist,
+/// which is checked (in debug builds) when the guard is dropped.
+///
+/// The nodes of a tower all share one copy of the value, so a change is seen
+/// on every level straight away.
+pub struct ValueMut<'a, T: PartialOrd> {
+    /// The node on the bottom row holding the value.
+    bottom: NonNull<Node<T>>,
+    /// The node left of `bottom` on the bottom row.
+    left: NonNull<Node<T>>,
+    modified: bool,
+    _marker: PhantomData<&'a mut T>,
+}
+
+impl<'a, T: PartialOrd> ValueMut<'a, T> {
+    /// # Safety
+    ///
+    /// `left` must be immediately left of `bottom` on the bottom row, and
+    /// the skiplist must be mutably borrowed for `'a`.
+    pub(crate) unsafe fn new(bottom: NonNull<Node<T>>, left: NonNull<Node<T>>) -> Self {
+        Self {
+            bottom,
+            left,
+            modified: false,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T: PartialOrd> Deref for ValueMut<'a, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        unsafe { (*self.bottom.as_ptr()).value.get_value() }
+    }
+}
+
+impl<'a, T: PartialOrd> DerefMut for ValueMut<'a, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        self.modified = true;
+        unsafe { (*self.bottom.as_ptr()).value.get_value_mut() }
+    }
+}
+
+impl<'a, T: PartialOrd + fmt::Debug> fmt::Debug for ValueMut<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ValueMut").field(&**self).finish()
+    }
+}
+
+impl<'a, T: PartialOrd> Drop for ValueMut<'a, T> {
+    fn drop(&mut self) {
+        if !self.modified {
+            return;
+        }
+        unsafe {
+            let bottom = self.bottom.as_ptr();
+            debug_assert!(
+                (*self.left.as_ptr()).value < (*bottom).value
+                    && (*bottom).value < (*bottom).right.unwrap().as_ref().value,
+                "A value was moved out of order through a mutable reference"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SkipList;
+    use std::cmp::Ordering;
+
+    #[derive(Debug, Clone)]
+    struct Keyed {
+        key: u32,
+        payload: u32,
+    }
+
+    impl PartialEq for Keyed {
+        fn eq(&self, other: &Self) -> bool {
+            self.key == other.key
+        }
+    }
+
+    impl PartialOrd for Keyed {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            self.key.partial_cmp(&other.key)
+        }
+    }
+
+    fn keyed(key: u32) -> Keyed {
+        Keyed { key, payload: 0 }
+    }
+
+    #[test]
+    fn test_get_mut() {
+        let mut sk: SkipList<Keyed> = (0..200).map(keyed).collect();
+        for key in 0..200 {
+            sk.get_mut(&keyed(key)).unwrap().payload = key * 2;
+        }
+        assert!(sk.get_mut(&keyed(200)).is_none());
+        // Every copy in the towers was updated, whichever one a search lands on.
+        for level in sk.iter_levels() {
+            for (value, _) in level {
+                if let Some(value) = value {
+                    assert_eq!(value.payload, value.key * 2);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_iter_mut() {
+        let mut sk: SkipList<Keyed> = (0..300).map(keyed).collect();
+        assert_eq!(sk.iter_mut().len(), 300);
+        for mut value in sk.iter_mut() {
+            value.payload = value.key + 1;
+        }
+        for level in sk.iter_levels() {
+            for (value, _) in level {
+                if let Some(value) = value {
+                    assert_eq!(value.payload, value.key + 1);
+                }
+            }
+        }
+        // Guards can be held at the same time
+        let mut guards: Vec<_> = sk.iter_mut().collect();
+        guards.reverse();
+        for guard in guards.iter_mut() {
+            guard.payload = 0;
+        }
+        drop(guards);
+        assert!(sk.iter_all().all(|value| value.payload == 0));
+    }
+
+    #[test]
+    fn test_range_mut() {
+        let mut sk: SkipList<Keyed> = (0..300).map(|key| keyed(key * 2)).collect();
+        for (start, end) in [(0, 0), (1, 1), (5, 77), (100, 101), (550, 1000)].iter() {
+            for mut value in sk.range_mut(&keyed(*start), &keyed(*end)) {
+                value.payload += 1;
+            }
+        }
+        for level in sk.iter_levels() {
+            for value in level.filter_map(|(value, _)| value) {
+                let expected = [(0, 0), (5, 77), (100, 101), (550, 1000)]
+                    .iter()
+                    .any(|(start, end)| (*start..=*end).contains(&value.key));
+                assert_eq!(value.payload, expected as u32, "{:?}", value);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg(debug_assertions)]
+    fn test_get_mut_out_of_order() {
+        let mut sk: SkipList<Keyed> = (0..10).map(keyed).collect();
+        sk.get_mut(&keyed(3)).unwrap().key = 7;
+    }
+}
@@ -1,6 +1,43 @@
-use crate::{Node, NodeValue, RangeHint, SkipList};
-use core::ops::{Bound, RangeBounds};
+use crate::{Edit, Node, NodeValue, RangeHint, SkipList, ValueMut};
+use core::ops::{Bound, Range, RangeBounds};
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::collections::VecDeque;
 use std::hint::unreachable_unchecked;
+use std::iter::{FusedIterator, Peekable};
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+/// The generation of a skiplist when an iterator or cursor was made.
+///
+/// In debug builds, `check` panics if the skiplist has made or freed nodes
+/// since, so misuse through raw pointers fails loudly instead of reading
+/// freed nodes.
+#[derive(Clone, Copy)]
+pub(crate) struct Generation<'a> {
+    counter: &'a AtomicUsize,
+    expected: usize,
+}
+
+impl<'a> Generation<'a> {
+    #[inline]
+    pub(crate) fn new(counter: &'a AtomicUsize) -> Self {
+        Self {
+            counter,
+            expected: counter.load(AtomicOrdering::Relaxed),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn check(&self) {
+        debug_assert_eq!(
+            self.counter.load(AtomicOrdering::Relaxed),
+            self.expected,
+            "The skiplist was modified while it was being iterated"
+        );
+    }
+}
 
 pub(crate) struct VerticalIter<T> {
     curr_node: Option<*mut Node<T>>,
@@ -26,7 +63,9 @@ impl<T> Iterator for VerticalIter<T> {
     }
 }
 
-/// Iterator to grab all values from the right of `curr_node`
+/// Iterator to move all values out from the right of `curr_node`.
+///
+/// The nodes can only be freed afterwards.
 pub(crate) struct NodeRightIter<T> {
     curr_node: *mut Node<T>,
 }
@@ -37,7 +76,7 @@ impl<T> NodeRightIter<T> {
     }
 }
 
-impl<T: Clone> Iterator for NodeRightIter<T> {
+impl<T> Iterator for NodeRightIter<T> {
     type Item = T;
 
     #[inline]
@@ -45,40 +84,89 @@ impl<T: Clone> Iterator for NodeRightIter<T> {
         unsafe {
             let next = (*self.curr_node).right?.as_ptr();
             let ret = std::mem::replace(&mut self.curr_node, next);
-            Some((*ret).value.get_value().clone())
+            Some((*ret).value.take_value())
+        }
+    }
+}
+
+/// Iterator that takes ownership of a skiplist and unlinks one tower
+/// at a time from the front, yielding its nodes bottom first.
+///
+/// The yielded nodes are owned by the caller. Whatever is left of the
+/// skiplist is still well formed (widths aside), so it drops normally.
+pub(crate) struct DrainTowers<T> {
+    _skiplist: SkipList<T>,
+    /// The NegInf column, bottom first.
+    left_column: Vec<*mut Node<T>>,
+}
+
+impl<T> DrainTowers<T> {
+    pub(crate) fn new(skiplist: SkipList<T>) -> Self {
+        let mut left_column: Vec<_> = VerticalIter::new(skiplist.top_left.as_ptr()).collect();
+        left_column.reverse();
+        Self {
+            _skiplist: skiplist,
+            left_column,
+        }
+    }
+}
+
+impl<T> Iterator for DrainTowers<T> {
+    type Item = Vec<NonNull<Node<T>>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut tower: Vec<NonNull<Node<T>>> = Vec::new();
+        unsafe {
+            for &left in &self.left_column {
+                let first = (*left).right.unwrap();
+                let part_of_tower = match tower.last() {
+                    None => !first.as_ref().value.is_pos_inf(),
+                    Some(below) => first.as_ref().down == Some(*below),
+                };
+                if !part_of_tower {
+                    break;
+                }
+                (*left).right = first.as_ref().right;
+                tower.push(first);
+            }
+        }
+        if tower.is_empty() {
+            None
+        } else {
+            Some(tower)
         }
     }
 }
 
 /// Struct to keep track of things for IntoIterator
-/// *Warning*: As all nodes are heap allocated, we have
-/// to clone them to produce type T.
+///
+/// Values are moved out of the bottom row as they're yielded, so `T`
+/// doesn't need to be `Clone`. The rest of the skiplist is freed when
+/// the iterator is dropped.
 pub struct IntoIter<T> {
-    _skiplist: SkipList<T>,
+    skiplist: SkipList<T>,
+    /// The last node yielded, or the bottom NegInf.
     curr_node: *mut Node<T>,
-    finished: bool,
+    back: Option<BackStack<T>>,
     total_len: usize,
 }
 
-impl<T: Clone> Iterator for IntoIter<T> {
+// SAFETY: `curr_node` and `back` only point into `skiplist`, which is owned.
+unsafe impl<T: Send> Send for IntoIter<T> {}
+
+impl<T> Iterator for IntoIter<T> {
     type Item = T;
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        if self.finished {
+        if self.total_len == 0 {
             return None;
         }
+        // As there's elements remaining, the node to our right is never PosInf.
         unsafe {
-            match (*self.curr_node).right {
-                Some(right) => {
-                    self.curr_node = right.as_ptr();
-                    Some((*self.curr_node).value.get_value().clone())
-                }
-                None => {
-                    self.finished = true;
-                    Some((*self.curr_node).value.get_value().clone())
-                }
-            }
+            self.curr_node = (*self.curr_node).right.unwrap().as_ptr();
+            self.total_len -= 1;
+            Some((*self.curr_node).value.take_value())
         }
     }
 
@@ -88,20 +176,56 @@ impl<T: Clone> Iterator for IntoIter<T> {
     }
 }
 
-impl<T: PartialOrd + Clone> IntoIterator for SkipList<T> {
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.total_len == 0 {
+            return None;
+        }
+        let top_left = self.skiplist.top_left.as_ptr();
+        let back = self
+            .back
+            .get_or_insert_with(|| BackStack::spanning(top_left));
+        // INVARIANT: There's elements remaining, so we never reach NegInf.
+        let node = back.next_bottom()? as *mut Node<T>;
+        self.total_len -= 1;
+        unsafe { Some((*node).value.take_value()) }
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> FusedIterator for IntoIter<T> {}
+
+impl<T> IntoIterator for SkipList<T> {
     type Item = T;
     type IntoIter = IntoIter<T>;
 
     fn into_iter(self) -> Self::IntoIter {
+        let mut curr_node = self.top_left.as_ptr();
+        unsafe {
+            while let Some(down) = (*curr_node).down {
+                curr_node = down.as_ptr();
+            }
+        }
         IntoIter {
             total_len: self.len,
-            curr_node: self.top_left.as_ptr(),
-            _skiplist: self,
-            finished: false,
+            curr_node,
+            back: None,
+            skiplist: self,
         }
     }
 }
 
+impl<'a, T: PartialOrd> IntoIterator for &'a SkipList<T> {
+    type Item = &'a T;
+    type IntoIter = IterAll<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_all()
+    }
+}
+
 // TODO: Drain
 // pub struct Drain<T> {
 //     curr_node: *mut Node<T>,
@@ -130,34 +254,190 @@ impl<T: PartialOrd + Clone> IntoIterator for SkipList<T> {
 //     }
 // }
 
+/// The nodes whose stretches of the bottom row are yet to be walked
+/// backwards. Every node covers the stretch of the bottom row up to its right
+/// neighbour, and the top of the stack is the rightmost stretch.
+///
+/// Popping replaces a node with the nodes below it that cover the same stretch
+/// until we reach the bottom. Every node is visited at most once, so walking
+/// the whole skiplist backwards is `O(n)`.
+pub(crate) struct BackStack<T> {
+    stack: Vec<*const Node<T>>,
+}
+
+impl<T> BackStack<T> {
+    /// Walk backwards over the stretch covered by `node`.
+    #[inline]
+    pub(crate) fn spanning(node: *const Node<T>) -> Self {
+        Self { stack: vec![node] }
+    }
+
+    /// Walk backwards from where a search from `top_left` ends up, stepping
+    /// right onto a node whenever `go_right` says so. `go_right` is also given
+    /// the node's index in the bottom row (NegInf is 0).
+    pub(crate) fn ending_at<F>(top_left: *const Node<T>, mut go_right: F) -> Self
+    where
+        F: FnMut(usize, &Node<T>) -> bool,
+    {
+        let mut stack = Vec::new();
+        let mut curr_node = top_left;
+        let mut index = 0;
+        unsafe {
+            loop {
+                // Everything left of where we step down is covered in full.
+                while let Some(right) = (*curr_node).right {
+                    let right_index = index + (*curr_node).width();
+                    if !go_right(right_index, right.as_ref()) {
+                        break;
+                    }
+                    stack.push(curr_node);
+                    curr_node = right.as_ptr();
+                    index = right_index;
+                }
+                match (*curr_node).down {
+                    Some(down) => curr_node = down.as_ptr(),
+                    None => break,
+                }
+            }
+        }
+        stack.push(curr_node);
+        Self { stack }
+    }
+
+    /// The next node on the bottom row, walking backwards. The last one is NegInf.
+    #[inline]
+    pub(crate) fn next_bottom(&mut self) -> Option<*const Node<T>> {
+        unsafe {
+            loop {
+                // Stick to raw pointers, so nodes can be mutated through what we return.
+                let node = self.stack.pop()?;
+                match (*node).down {
+                    Some(down) => {
+                        let mut child = down.as_ptr();
+                        let mut covered = 0;
+                        while covered < (*node).width() {
+                            self.stack.push(child);
+                            covered += (*child).width();
+                            child = (*child).right.unwrap().as_ptr();
+                        }
+                    }
+                    None => return Some(node),
+                }
+            }
+        }
+    }
+}
+
 /// IterAll is a iterator struct to iterate over the entire
 /// linked list.
 ///
+/// It's double ended, so `iter_all().rev()` walks from the largest element down.
+///
 /// You should use the method `iter_all` on [SkipList](convenient-skiplist::SkipList)
 pub struct IterAll<'a, T> {
+    /// The last node yielded from the front, or NegInf.
     curr_node: &'a Node<T>,
     at_bottom: bool,
-    finished: bool,
-    total_len: usize,
+    back: Option<BackStack<T>>,
+    top_left: &'a Node<T>,
+    /// The index of `curr_node`, counting NegInf as 0.
+    index: usize,
+    remaining: usize,
+    generation: Generation<'a>,
 }
 
 impl<'a, T> IterAll<'a, T> {
     #[inline]
-    pub(crate) fn new(curr_node: &'a Node<T>, total_len: usize) -> Self {
+    pub(crate) fn new(
+        curr_node: &'a Node<T>,
+        total_len: usize,
+        generation: Generation<'a>,
+    ) -> Self {
         Self {
             curr_node,
             at_bottom: false,
-            finished: false,
-            total_len,
+            back: None,
+            top_left: curr_node,
+            index: 0,
+            remaining: total_len,
+            generation,
         }
     }
+
+    /// Look at the next element without advancing the iterator.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from(vec![1, 2]);
+    /// let mut iter = sk.iter_all();
+    ///
+    /// assert_eq!(iter.peek(), Some(&1));
+    /// assert_eq!(iter.next(), Some(&1));
+    /// assert_eq!(iter.peek(), Some(&2));
+    /// ```
+    #[inline]
+    pub fn peek(&self) -> Option<&'a T> {
+        self.generation.check();
+        if self.remaining == 0 {
+            return None;
+        }
+        let mut curr_node = self.curr_node;
+        // As there's elements remaining, the bottom node to our right is never PosInf.
+        unsafe {
+            while let Some(down) = curr_node.down {
+                curr_node = &*down.as_ptr();
+            }
+            Some((*curr_node.right.unwrap().as_ptr()).value.get_value())
+        }
+    }
+
+    /// Fast-forward the iterator, so the next element yielded is the first one `>= item`.
+    ///
+    /// The search goes through the upper levels of the skiplist, so this runs in
+    /// `O(logn)` no matter how far it jumps. Seeking to an element that's already
+    /// been passed does nothing.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from(0..100);
+    /// let mut iter = sk.iter_all();
+    ///
+    /// assert_eq!(iter.next(), Some(&0));
+    /// iter.seek(&50);
+    /// assert_eq!(iter.next(), Some(&50));
+    /// iter.seek(&10);
+    /// assert_eq!(iter.next(), Some(&51));
+    /// ```
+    pub fn seek<Q>(&mut self, item: &Q)
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.generation.check();
+        let left = LeftBiasIterWidth::new(self.top_left as *const _ as *mut _, item)
+            .last()
+            .expect("There's always a NegInf to the left");
+        if left.curr_width <= self.index {
+            return;
+        }
+        let end = self.index + self.remaining;
+        self.remaining = end.saturating_sub(left.curr_width);
+        self.index = left.curr_width;
+        self.curr_node = unsafe { &*left.curr_node };
+        self.at_bottom = true;
+    }
 }
 
 impl<'a, T: PartialOrd> Iterator for IterAll<'a, T> {
     type Item = &'a T;
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        if self.finished {
+        self.generation.check();
+        if self.remaining == 0 {
             return None;
         }
         // step 1: Hit the bottom
@@ -167,46 +447,561 @@ impl<'a, T: PartialOrd> Iterator for IterAll<'a, T> {
                     self.curr_node = down.as_ref();
                 }
             }
-            // step 2: Go one to the right
-            unsafe {
-                self.curr_node = self.curr_node.right.unwrap().as_ptr().as_ref().unwrap();
-            }
             self.at_bottom = true;
         }
+        // step 2: Go one to the right. As there's elements remaining, it's never PosInf.
         unsafe {
-            match self.curr_node.value {
-                NodeValue::NegInf => {
-                    self.curr_node = self.curr_node.right.unwrap().as_ptr().as_ref().unwrap();
+            self.curr_node = self.curr_node.right.unwrap().as_ptr().as_ref().unwrap();
+        }
+        self.index += 1;
+        self.remaining -= 1;
+        Some(self.curr_node.value.get_value())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+
+    /// Jump straight to the nth element using the upper levels, in `O(logn)`.
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.generation.check();
+        if n >= self.remaining {
+            self.index += self.remaining;
+            self.remaining = 0;
+            return None;
+        }
+        let target = self.index + n + 1;
+        let (node, _) = last_where(self.top_left, |index, _| index <= target);
+        self.curr_node = node;
+        self.at_bottom = true;
+        self.index = target;
+        self.remaining -= n + 1;
+        Some(node.value.get_value())
+    }
+}
+
+impl<'a, T: PartialOrd> ExactSizeIterator for IterAll<'a, T> {}
+
+impl<'a, T: PartialOrd> FusedIterator for IterAll<'a, T> {}
+
+impl<'a, T: PartialOrd> DoubleEndedIterator for IterAll<'a, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.generation.check();
+        if self.remaining == 0 {
+            return None;
+        }
+        let top_left = self.top_left;
+        let back = self
+            .back
+            .get_or_insert_with(|| BackStack::spanning(top_left));
+        self.remaining -= 1;
+        // INVARIANT: There's elements remaining, so we never reach NegInf.
+        back.next_bottom()
+            .map(|node| unsafe { (*node).value.get_value() })
+    }
+}
+
+/// Gaps is a lazy iterator over the missing stretches between consecutive
+/// elements of a skiplist, as half-open ranges `successor(a)..b`.
+///
+/// You should use the methods `gaps` or `gaps_by` on [SkipList](convenient-skiplist::SkipList)
+pub struct Gaps<'a, T, F> {
+    iter: IterAll<'a, T>,
+    prev: Option<&'a T>,
+    successor: F,
+}
+
+impl<'a, T, F> Gaps<'a, T, F> {
+    #[inline]
+    pub(crate) fn new(mut iter: IterAll<'a, T>, successor: F) -> Self
+    where
+        T: PartialOrd,
+    {
+        Self {
+            prev: iter.next(),
+            iter,
+            successor,
+        }
+    }
+}
+
+impl<'a, T, F> Iterator for Gaps<'a, T, F>
+where
+    T: PartialOrd + Clone,
+    F: FnMut(&T) -> Option<T>,
+{
+    type Item = Range<T>;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let curr = self.iter.next()?;
+            let prev = self.prev.replace(curr)?;
+            if let Some(start) = (self.successor)(prev) {
+                if &start < curr {
+                    return Some(start..curr.clone());
                 }
-                NodeValue::PosInf => return None,
-                NodeValue::Value(..) => {}
-            };
-            if self.curr_node.right.unwrap().as_ref().value == NodeValue::PosInf {
-                self.finished = true;
-                Some(self.curr_node.value.get_value())
-            } else {
-                let next = self.curr_node.right.unwrap().as_ptr().as_ref().unwrap();
-                let to_ret = std::mem::replace(&mut self.curr_node, next);
-                Some(to_ret.value.get_value())
             }
         }
     }
 
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        (self.total_len, Some(self.total_len))
+        (0, self.iter.size_hint().1)
+    }
+}
+
+/// Union is a lazy iterator over every element in either of two skiplists,
+/// in ascending order and without duplicates.
+///
+/// You should use the method `union` on [SkipList](convenient-skiplist::SkipList)
+pub struct Union<'a, T: PartialOrd> {
+    left: Peekable<IterAll<'a, T>>,
+    right: Peekable<IterAll<'a, T>>,
+}
+
+impl<'a, T: PartialOrd> Union<'a, T> {
+    #[inline]
+    pub(crate) fn new(left: IterAll<'a, T>, right: IterAll<'a, T>) -> Self {
+        Self {
+            left: left.peekable(),
+            right: right.peekable(),
+        }
+    }
+}
+
+impl<'a, T: PartialOrd> Iterator for Union<'a, T> {
+    type Item = &'a T;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        // Two-pointer walk over both bottom rows: always yield the smaller
+        // head, and advance both sides when the heads are equal.
+        match (self.left.peek(), self.right.peek()) {
+            (Some(l), Some(r)) => {
+                if l < r {
+                    self.left.next()
+                } else if r < l {
+                    self.right.next()
+                } else {
+                    self.right.next();
+                    self.left.next()
+                }
+            }
+            (Some(_), None) => self.left.next(),
+            (None, _) => self.right.next(),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (l_lower, l_upper) = self.left.size_hint();
+        let (r_lower, r_upper) = self.right.size_hint();
+        let upper = match (l_upper, r_upper) {
+            (Some(l), Some(r)) => l.checked_add(r),
+            _ => None,
+        };
+        (l_lower.max(r_lower), upper)
+    }
+}
+
+/// MergeIter is a lazy iterator over every element of two skiplists, in
+/// ascending order. Unlike `Union`, duplicates are kept.
+///
+/// You should use the method `merge_iter` on [SkipList](convenient-skiplist::SkipList)
+pub struct MergeIter<'a, T: PartialOrd> {
+    left: IterAll<'a, T>,
+    right: IterAll<'a, T>,
+}
+
+impl<'a, T: PartialOrd> MergeIter<'a, T> {
+    #[inline]
+    pub(crate) fn new(left: IterAll<'a, T>, right: IterAll<'a, T>) -> Self {
+        Self { left, right }
+    }
+}
+
+impl<'a, T: PartialOrd> Iterator for MergeIter<'a, T> {
+    type Item = &'a T;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        // Yield the smaller head, preferring the left side on ties.
+        match (self.left.peek(), self.right.peek()) {
+            (Some(l), Some(r)) => {
+                if r < l {
+                    self.right.next()
+                } else {
+                    self.left.next()
+                }
+            }
+            (Some(_), None) => self.left.next(),
+            (None, _) => self.right.next(),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.left.len() + self.right.len();
+        (len, Some(len))
+    }
+}
+
+impl<'a, T: PartialOrd> ExactSizeIterator for MergeIter<'a, T> {}
+
+/// A resumable search path into a skiplist: the node immediately left
+/// of the last searched item on every level, top to bottom, paired with
+/// its index in the bottom row (NegInf is 0).
+///
+/// Searching again for a larger item starts from this path rather than
+/// the top left, so a sequence of ascending searches costs `O(log d)`
+/// each, where d is the distance moved in the bottom row.
+pub(crate) struct SearchPath<T> {
+    path: Vec<NodeWidth<T>>,
+}
+
+impl<T: PartialOrd> SearchPath<T> {
+    pub(crate) fn new(top_left: *mut Node<T>) -> Self {
+        Self {
+            path: VerticalIter::new(top_left)
+                .map(|node| NodeWidth::new(node, 0))
+                .collect(),
+        }
+    }
+
+    /// Move the path towards `item`, returning the bottom row node
+    /// immediately left of where `item` is or should be.
+    ///
+    /// `item` must not be smaller than any previously searched item.
+    pub(crate) fn advance_to(&mut self, item: &T) -> *mut Node<T> {
+        self.advance_while(|_, right| &right.value < item)
+    }
+
+    /// Move the path to the bottom row node at `index` (NegInf is 0).
+    ///
+    /// `index` must not be smaller than any previously searched index,
+    /// nor larger than the length of the skiplist.
+    pub(crate) fn advance_to_index(&mut self, index: usize) -> *mut Node<T> {
+        self.advance_while(|left, _| unsafe {
+            left.curr_width + (*left.curr_node).width() <= index
+        })
+    }
+
+    /// Move the path right wherever `go_right` says we should step from
+    /// the left node onto the right one.
+    #[inline]
+    fn advance_while<F>(&mut self, mut go_right: F) -> *mut Node<T>
+    where
+        F: FnMut(&NodeWidth<T>, &Node<T>) -> bool,
+    {
+        let bottom = self.path.len() - 1;
+        unsafe {
+            // Step 1: Climb until we can't step right.
+            let mut level = bottom;
+            while level > 0 {
+                let left = &self.path[level];
+                if !go_right(left, (*left.curr_node).right.unwrap().as_ref()) {
+                    break;
+                }
+                level -= 1;
+            }
+            // Step 2: Descend like a normal search, but starting from the
+            // old path. If the row above moved right, its tower is further
+            // right than our old position, so we continue from there.
+            let mut moved = false;
+            for curr_level in level..=bottom {
+                let mut left = if moved {
+                    let above = &self.path[curr_level - 1];
+                    NodeWidth::new((*above.curr_node).down.unwrap().as_ptr(), above.curr_width)
+                } else {
+                    self.path[curr_level]
+                };
+                while let Some(right) = (*left.curr_node).right {
+                    if !go_right(&left, right.as_ref()) {
+                        break;
+                    }
+                    left =
+                        NodeWidth::new(right.as_ptr(), left.curr_width + (*left.curr_node).width());
+                    moved = true;
+                }
+                self.path[curr_level] = left;
+            }
+        }
+        self.path[bottom].curr_node
+    }
+
+    /// Move the path to `item`, in either direction, returning the bottom row
+    /// node immediately left of where `item` is or should be.
+    ///
+    /// Unlike `advance_to`, `item` can be smaller than previously searched items.
+    /// Either way this costs `O(log d)`, where d is the distance moved in the bottom row.
+    pub(crate) fn move_to<Q>(&mut self, item: &Q) -> NodeWidth<T>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        let bottom = self.path.len() - 1;
+        unsafe {
+            // Step 1: Climb until the path is left of `item`, and can't step right.
+            // The top row is empty, so this always holds there.
+            let mut level = bottom;
+            while level > 0 {
+                let left = &*self.path[level].curr_node;
+                if left.value.lt_key(item) && !left.right.unwrap().as_ref().value.lt_key(item) {
+                    break;
+                }
+                level -= 1;
+            }
+            // Step 2: Descend like a normal search. Rows below where we stopped
+            // are reused if they're still left of `item` and the row above
+            // didn't move, as they're then at least as far right as the tower above.
+            let mut moved = false;
+            for curr_level in level..=bottom {
+                let old = self.path[curr_level];
+                let mut left =
+                    if curr_level == level || (!moved && (*old.curr_node).value.lt_key(item)) {
+                        old
+                    } else {
+                        let above = &self.path[curr_level - 1];
+                        NodeWidth::new((*above.curr_node).down.unwrap().as_ptr(), above.curr_width)
+                    };
+                while let Some(right) = (*left.curr_node).right {
+                    if !right.as_ref().value.lt_key(item) {
+                        break;
+                    }
+                    left =
+                        NodeWidth::new(right.as_ptr(), left.curr_width + (*left.curr_node).width());
+                    moved = true;
+                }
+                if left.curr_node != old.curr_node {
+                    moved = true;
+                }
+                self.path[curr_level] = left;
+            }
+        }
+        self.path[bottom]
+    }
+
+    /// The nodes on the path, top to bottom.
+    #[inline]
+    pub(crate) fn nodes_mut(&mut self) -> &mut [NodeWidth<T>] {
+        &mut self.path
+    }
+
+    /// Account for `additional_levels` new rows added below the top row
+    /// by `SkipList::add_levels`.
+    pub(crate) fn add_levels(&mut self, top_left: *mut Node<T>, additional_levels: usize) {
+        let new_rows = VerticalIter::new(top_left)
+            .skip(1)
+            .take(additional_levels)
+            .map(|node| NodeWidth::new(node, 0));
+        self.path.splice(1..1, new_rows);
+    }
+}
+
+/// Intersection is a lazy iterator over the elements found in both of two skiplists,
+/// in ascending order.
+///
+/// You should use the method `intersection` on [SkipList](convenient-skiplist::SkipList)
+pub struct Intersection<'a, T: PartialOrd> {
+    smaller: IterAll<'a, T>,
+    larger: SearchPath<T>,
+    larger_len: usize,
+}
+
+impl<'a, T: PartialOrd> Intersection<'a, T> {
+    #[inline]
+    pub(crate) fn new(smaller: IterAll<'a, T>, larger: &'a SkipList<T>) -> Self {
+        Self {
+            smaller,
+            larger: SearchPath::new(larger.top_left.as_ptr()),
+            larger_len: larger.len,
+        }
+    }
+}
+
+impl<'a, T: PartialOrd> Iterator for Intersection<'a, T> {
+    type Item = &'a T;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        // Walk the smaller skiplist, and gallop through the larger
+        // one with its upper levels.
+        for item in &mut self.smaller {
+            let left = self.larger.advance_to(item);
+            if unsafe { &(*left).right.unwrap().as_ref().value } == item {
+                return Some(item);
+            }
+        }
+        None
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (_, upper) = self.smaller.size_hint();
+        (0, upper.map(|upper| upper.min(self.larger_len)))
+    }
+}
+
+/// Difference is a lazy iterator over the elements of one skiplist
+/// that are not in another, in ascending order.
+///
+/// You should use the method `difference` on [SkipList](convenient-skiplist::SkipList)
+pub struct Difference<'a, T: PartialOrd> {
+    left: IterAll<'a, T>,
+    right: SearchPath<T>,
+}
+
+impl<'a, T: PartialOrd> Difference<'a, T> {
+    #[inline]
+    pub(crate) fn new(left: IterAll<'a, T>, right: &'a SkipList<T>) -> Self {
+        Self {
+            left,
+            right: SearchPath::new(right.top_left.as_ptr()),
+        }
+    }
+}
+
+impl<'a, T: PartialOrd> Iterator for Difference<'a, T> {
+    type Item = &'a T;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        for item in &mut self.left {
+            let left = self.right.advance_to(item);
+            if unsafe { &(*left).right.unwrap().as_ref().value } != item {
+                return Some(item);
+            }
+        }
+        None
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.left.size_hint().1)
+    }
+}
+
+/// SymmetricDifference is a lazy iterator over the elements found in exactly one
+/// of two skiplists, in ascending order.
+///
+/// You should use the method `symmetric_difference` on [SkipList](convenient-skiplist::SkipList)
+pub struct SymmetricDifference<'a, T: PartialOrd> {
+    left: Peekable<IterAll<'a, T>>,
+    right: Peekable<IterAll<'a, T>>,
+}
+
+impl<'a, T: PartialOrd> SymmetricDifference<'a, T> {
+    #[inline]
+    pub(crate) fn new(left: IterAll<'a, T>, right: IterAll<'a, T>) -> Self {
+        Self {
+            left: left.peekable(),
+            right: right.peekable(),
+        }
+    }
+}
+
+impl<'a, T: PartialOrd> Iterator for SymmetricDifference<'a, T> {
+    type Item = &'a T;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match (self.left.peek(), self.right.peek()) {
+                (Some(l), Some(r)) => {
+                    if l < r {
+                        return self.left.next();
+                    } else if r < l {
+                        return self.right.next();
+                    } else {
+                        self.left.next();
+                        self.right.next();
+                    }
+                }
+                (Some(_), None) => return self.left.next(),
+                (None, _) => return self.right.next(),
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let upper = match (self.left.size_hint().1, self.right.size_hint().1) {
+            (Some(l), Some(r)) => l.checked_add(r),
+            _ => None,
+        };
+        (0, upper)
+    }
+}
+
+/// Diff is a lazy iterator over the edits needed to turn one skiplist
+/// into another, in ascending order.
+///
+/// You should use the method `diff` on [SkipList](convenient-skiplist::SkipList)
+pub struct Diff<'a, T: PartialOrd> {
+    old: Peekable<IterAll<'a, T>>,
+    new: Peekable<IterAll<'a, T>>,
+}
+
+impl<'a, T: PartialOrd> Diff<'a, T> {
+    #[inline]
+    pub(crate) fn new(old: IterAll<'a, T>, new: IterAll<'a, T>) -> Self {
+        Self {
+            old: old.peekable(),
+            new: new.peekable(),
+        }
+    }
+}
+
+impl<'a, T: PartialOrd> Iterator for Diff<'a, T> {
+    type Item = Edit<'a, T>;
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match (self.old.peek(), self.new.peek()) {
+                (Some(o), Some(n)) => {
+                    if o < n {
+                        return self.old.next().map(Edit::Removed);
+                    } else if n < o {
+                        return self.new.next().map(Edit::Added);
+                    } else {
+                        self.old.next();
+                        self.new.next();
+                    }
+                }
+                (Some(_), None) => return self.old.next().map(Edit::Removed),
+                (None, _) => return self.new.next().map(Edit::Added),
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let upper = match (self.old.size_hint().1, self.new.size_hint().1) {
+            (Some(o), Some(n)) => o.checked_add(n),
+            _ => None,
+        };
+        (0, upper)
     }
 }
 
 pub struct SkipListIndexRange<'a, R: RangeBounds<usize>, T> {
-    range: R,
     curr_node: *const Node<T>,
-    curr_index: usize,
-    phantom: std::marker::PhantomData<&'a T>,
+    top_left: *const Node<T>,
+    /// One past the index of the last element in the range.
+    end: usize,
+    back: Option<BackStack<T>>,
+    /// How many elements are left to yield from either end.
+    remaining: usize,
+    generation: Generation<'a>,
+    phantom: std::marker::PhantomData<(&'a T, R)>,
 }
 
 impl<'a, R: RangeBounds<usize>, T> SkipListIndexRange<'a, R, T> {
-    pub(crate) fn new(curr_node: *const Node<T>, range: R) -> Self {
+    pub(crate) fn new(
+        curr_node: *const Node<T>,
+        range: R,
+        len: usize,
+        generation: Generation<'a>,
+    ) -> Self {
+        let top_left = curr_node;
         let mut curr_node = curr_node;
         // Find closest starting node
         let mut curr_index = 0;
@@ -239,7 +1034,7 @@ impl<'a, R: RangeBounds<usize>, T> SkipListIndexRange<'a, R, T> {
                                 }
                                 _ => unreachable_unchecked(),
                             };
-                            let width = (*curr_node).width;
+                            let width = (*curr_node).width();
                             if curr_index + width <= idx {
                                 curr_node = right.as_ptr() as *const _;
                                 curr_index += width;
@@ -262,7 +1057,7 @@ impl<'a, R: RangeBounds<usize>, T> SkipListIndexRange<'a, R, T> {
                                 _ => unreachable_unchecked(),
                             };
                             curr_node = right.as_ptr();
-                            curr_index += (*curr_node).width;
+                            curr_index += (*curr_node).width();
                         }
                         (None, None) => {
                             break curr_node;
@@ -276,111 +1071,609 @@ impl<'a, R: RangeBounds<usize>, T> SkipListIndexRange<'a, R, T> {
         while let Some(down) = unsafe { (*curr_node).down } {
             curr_node = down.as_ptr();
         }
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.saturating_add(1),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => len,
+        };
+        let end = end.min(len);
+        Self {
+            curr_node,
+            top_left,
+            end,
+            back: None,
+            remaining: end.saturating_sub(start),
+            generation,
+            phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// Look at the next element without advancing the iterator.
+    #[inline]
+    pub fn peek(&self) -> Option<&'a T> {
+        self.generation.check();
+        if self.remaining == 0 {
+            return None;
+        }
+        Some(unsafe { (*self.curr_node).value.get_value() })
+    }
+}
+
+macro_rules! get_value_and_advance {
+    ($curr:expr, $right:expr) => {{
+        Some(
+            (*std::mem::replace($curr, $right.as_ptr()))
+                .value
+                .get_value(),
+        )
+    }};
+}
+
+impl<'a, T, R: RangeBounds<usize>> Iterator for SkipListIndexRange<'a, R, T> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.generation.check();
+        if self.remaining == 0 {
+            return None;
+        }
+        unsafe {
+            debug_assert!((*self.curr_node).down.is_none());
+            // As there's elements remaining, we're never at PosInf.
+            let right = (*self.curr_node).right?;
+            self.remaining -= 1;
+            get_value_and_advance!(&mut self.curr_node, right)
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T, R: RangeBounds<usize>> DoubleEndedIterator for SkipListIndexRange<'a, R, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.generation.check();
+        if self.remaining == 0 {
+            return None;
+        }
+        // The last element is at `end - 1`, which is `end` on the bottom row as NegInf is 0.
+        let end = self.end;
+        let top_left = self.top_left;
+        let back = self
+            .back
+            .get_or_insert_with(|| BackStack::ending_at(top_left, |index, _| index <= end));
+        // As there's elements remaining, this is in the range.
+        let node = unsafe { &*back.next_bottom()? };
+        self.remaining -= 1;
+        Some(node.value.get_value())
+    }
+}
+
+impl<'a, T, R: RangeBounds<usize>> ExactSizeIterator for SkipListIndexRange<'a, R, T> {}
+
+/// Iterator over the rows of a skiplist from the top down, made by `SkipList::iter_levels`.
+pub struct Levels<'a, T> {
+    curr_node: Option<&'a Node<T>>,
+    remaining: usize,
+}
+
+impl<'a, T> Levels<'a, T> {
+    pub(crate) fn new(top_left: &'a Node<T>, height: usize) -> Self {
+        Self {
+            curr_node: Some(top_left),
+            remaining: height,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Levels<'a, T> {
+    type Item = Level<'a, T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let left = self.curr_node?;
+        self.curr_node = left.down.map(|down| unsafe { &*down.as_ptr() });
+        self.remaining -= 1;
+        Some(Level {
+            curr_node: Some(left),
+        })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Levels<'a, T> {}
+
+/// Iterator over one row of a skiplist, yielding `(value, width)` pairs.
+///
+/// The first pair is the left edge of the row, with a value of `None`. Each
+/// width is how many elements on the bottom row it takes to reach the next
+/// node on this row (or the right edge).
+pub struct Level<'a, T> {
+    curr_node: Option<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Level<'a, T> {
+    type Item = (Option<&'a T>, usize);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.curr_node?;
+        // Don't yield the right edge, as its width has no meaning.
+        self.curr_node = node
+            .right
+            .map(|right| unsafe { &*right.as_ptr() })
+            .filter(|right| !right.value.is_pos_inf());
+        Some((node.value.value(), node.width()))
+    }
+}
+
+/// Iterator over mutable references to elements, made by `SkipList::iter_mut`.
+///
+/// Each element is yielded as a [ValueMut](crate::ValueMut) guard, which (in
+/// debug builds) checks that changes keep the order.
+pub struct IterMut<'a, T: PartialOrd> {
+    /// The last node visited on the bottom row.
+    curr_node: *mut Node<T>,
+    remaining: usize,
+    _marker: PhantomData<&'a mut T>,
+}
+
+impl<'a, T: PartialOrd> IterMut<'a, T> {
+    /// Start iterating right of `curr_node`, which must be on the bottom row.
+    pub(crate) fn new(curr_node: *mut Node<T>, remaining: usize) -> Self {
         Self {
-            range,
             curr_node,
-            curr_index: curr_index.saturating_sub(1),
-            phantom: std::marker::PhantomData::default(),
+            remaining,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T: PartialOrd> Iterator for IterMut<'a, T> {
+    type Item = ValueMut<'a, T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        unsafe {
+            // As there's elements remaining, the node to our right is never PosInf.
+            let left = self.curr_node;
+            self.curr_node = (*left).right.unwrap().as_ptr();
+            Some(ValueMut::new(
+                NonNull::new_unchecked(self.curr_node),
+                NonNull::new_unchecked(left),
+            ))
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T: PartialOrd> ExactSizeIterator for IterMut<'a, T> {}
+
+impl<'a, T: PartialOrd> FusedIterator for IterMut<'a, T> {}
+
+/// Iterator popping elements off the end of a skiplist, made by `SkipList::pop_max_iter`.
+///
+/// Each element is unlinked only when it's yielded, so dropping the
+/// iterator early leaves the rest in the skiplist.
+pub struct PopMax<'a, T: PartialOrd> {
+    list: &'a mut SkipList<T>,
+    remaining: usize,
+}
+
+impl<'a, T: PartialOrd> PopMax<'a, T> {
+    pub(crate) fn new(list: &'a mut SkipList<T>, count: usize) -> Self {
+        let remaining = count.min(list.len());
+        Self { list, remaining }
+    }
+}
+
+impl<'a, T: PartialOrd> Iterator for PopMax<'a, T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.list.pop_back()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T: PartialOrd> ExactSizeIterator for PopMax<'a, T> {}
+
+impl<'a, T: PartialOrd> FusedIterator for PopMax<'a, T> {}
+
+/// Iterator popping elements off the start of a skiplist, made by `SkipList::pop_min_iter`.
+///
+/// Each element is unlinked only when it's yielded, so dropping the
+/// iterator early leaves the rest in the skiplist.
+pub struct PopMin<'a, T: PartialOrd> {
+    list: &'a mut SkipList<T>,
+    remaining: usize,
+}
+
+impl<'a, T: PartialOrd> PopMin<'a, T> {
+    pub(crate) fn new(list: &'a mut SkipList<T>, count: usize) -> Self {
+        let remaining = count.min(list.len());
+        Self { list, remaining }
+    }
+}
+
+impl<'a, T: PartialOrd> Iterator for PopMin<'a, T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.list.pop_front()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T: PartialOrd> ExactSizeIterator for PopMin<'a, T> {}
+
+impl<'a, T: PartialOrd> FusedIterator for PopMin<'a, T> {}
+
+/// Iterator over overlapping windows of `size` elements, made by `SkipList::windows`.
+pub struct Windows<'a, T> {
+    iter: IterAll<'a, T>,
+    window: VecDeque<&'a T>,
+    size: usize,
+}
+
+impl<'a, T> Windows<'a, T> {
+    pub(crate) fn new(iter: IterAll<'a, T>, size: usize) -> Self {
+        Self {
+            iter,
+            window: VecDeque::with_capacity(size),
+            size,
+        }
+    }
+}
+
+impl<'a, T: PartialOrd> Iterator for Windows<'a, T> {
+    type Item = Vec<&'a T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.window.len() == self.size {
+            self.window.pop_front();
+        }
+        while self.window.len() < self.size {
+            self.window.push_back(self.iter.next()?);
+        }
+        Some(self.window.iter().copied().collect())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Every element left makes one more window, once the first is full.
+        let filled = self.window.len().max(1) - 1;
+        let len = (self.iter.len() + filled + 1).saturating_sub(self.size);
+        (len, Some(len))
+    }
+}
+
+impl<'a, T: PartialOrd> ExactSizeIterator for Windows<'a, T> {}
+
+/// Iterator over chunks of `size` elements, made by `SkipList::chunks`.
+///
+/// The last chunk is shorter if there aren't enough elements to fill it.
+pub struct Chunks<'a, T> {
+    iter: IterAll<'a, T>,
+    size: usize,
+}
+
+impl<'a, T> Chunks<'a, T> {
+    pub(crate) fn new(iter: IterAll<'a, T>, size: usize) -> Self {
+        Self { iter, size }
+    }
+}
+
+impl<'a, T: PartialOrd> Iterator for Chunks<'a, T> {
+    type Item = Vec<&'a T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk: Vec<_> = self.iter.by_ref().take(self.size).collect();
+        if chunk.is_empty() {
+            None
+        } else {
+            Some(chunk)
         }
     }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.iter.len().div_ceil(self.size);
+        (len, Some(len))
+    }
 }
 
-macro_rules! get_value_and_advance {
-    ($curr:expr, $right:expr) => {{
-        Some(
-            (*std::mem::replace($curr, $right.as_ptr()))
-                .value
-                .get_value(),
-        )
-    }};
+impl<'a, T: PartialOrd> ExactSizeIterator for Chunks<'a, T> {}
+
+/// Iterator over the elements around a value, made by `SkipList::around`.
+pub struct Around<'a, T> {
+    items: std::vec::IntoIter<&'a T>,
 }
 
-impl<'a, T, R: RangeBounds<usize>> Iterator for SkipListIndexRange<'a, R, T> {
+impl<'a, T> Around<'a, T> {
+    pub(crate) fn new(items: Vec<&'a T>) -> Self {
+        Self {
+            items: items.into_iter(),
+        }
+    }
+}
+
+impl<'a, T> Iterator for Around<'a, T> {
     type Item = &'a T;
+
+    #[inline]
     fn next(&mut self) -> Option<Self::Item> {
+        self.items.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.items.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Around<'a, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.items.next_back()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Around<'a, T> {}
+
+/// Is `value` left of where the range starting at `start` begins?
+#[inline]
+fn below_start<T, Q>(value: &NodeValue<T>, start: Bound<&Q>) -> bool
+where
+    T: Borrow<Q>,
+    Q: PartialOrd + ?Sized,
+{
+    match start {
+        Bound::Included(start) => value.lt_key(start),
+        Bound::Excluded(start) => !matches!(value.partial_cmp_key(start), Some(Ordering::Greater)),
+        Bound::Unbounded => matches!(value, NodeValue::NegInf(_)),
+    }
+}
+
+/// Is `value` left of where the range ending at `end` finishes?
+#[inline]
+fn within_end<T, Q>(value: &NodeValue<T>, end: Bound<&Q>) -> bool
+where
+    T: Borrow<Q>,
+    Q: PartialOrd + ?Sized,
+{
+    match end {
+        Bound::Included(end) => matches!(
+            value.partial_cmp_key(end),
+            Some(Ordering::Less) | Some(Ordering::Equal)
+        ),
+        Bound::Excluded(end) => value.lt_key(end),
+        Bound::Unbounded => !value.is_pos_inf(),
+    }
+}
+
+/// Find the last bottom-row node that satisfies `go_right`, along with its index
+/// (counting NegInf as 0). `go_right` is also given the node's index.
+///
+/// `go_right` is never called on NegInf, and must hold for a prefix of the skiplist.
+#[inline]
+fn last_where<T>(
+    top_left: &Node<T>,
+    mut go_right: impl FnMut(usize, &Node<T>) -> bool,
+) -> (&Node<T>, usize) {
+    let mut curr_node = top_left;
+    let mut index = 0;
+    loop {
         unsafe {
-            debug_assert!((*self.curr_node).down.is_none());
-            let right = (*self.curr_node).right?;
-            match self.range.end_bound() {
-                Bound::Unbounded => get_value_and_advance!(&mut self.curr_node, right),
-                Bound::Included(&idx) => {
-                    if self.curr_index > idx {
-                        return None;
-                    }
-                    self.curr_index += 1;
-                    get_value_and_advance!(&mut self.curr_node, right)
-                }
-                Bound::Excluded(&idx) => {
-                    if self.curr_index == idx {
-                        return None;
-                    }
-                    self.curr_index += 1;
-                    get_value_and_advance!(&mut self.curr_node, right)
+            while let Some(right) = curr_node.right {
+                if !go_right(index + curr_node.width(), right.as_ref()) {
+                    break;
                 }
+                index += curr_node.width();
+                curr_node = &*right.as_ptr();
+            }
+            match curr_node.down {
+                Some(down) => curr_node = &*down.as_ptr(),
+                None => return (curr_node, index),
             }
         }
     }
 }
 
-pub struct SkipListRange<'a, T> {
+pub struct SkipListRange<'a, T, Q: ?Sized = T, R = (Bound<&'a Q>, Bound<&'a Q>)> {
+    /// The next node to yield from the front.
     curr_node: &'a Node<T>,
-    start: &'a T,
-    end: &'a T,
-    at_bottom: bool,
+    /// The index of `curr_node`.
+    index: usize,
+    range: R,
+    top_left: &'a Node<T>,
+    back: Option<BackStack<T>>,
+    /// How many elements are left to yield from either end.
+    remaining: usize,
+    generation: Generation<'a>,
+    _key: PhantomData<fn(&Q)>,
 }
 
-impl<'a, T> SkipListRange<'a, T> {
-    pub(crate) fn new(curr_node: &'a Node<T>, start: &'a T, end: &'a T) -> Self {
+impl<'a, T, Q, R> SkipListRange<'a, T, Q, R>
+where
+    T: PartialOrd + Borrow<Q>,
+    Q: PartialOrd + ?Sized,
+    R: RangeBounds<Q>,
+{
+    pub(crate) fn new(top_left: &'a Node<T>, range: R, generation: Generation<'a>) -> Self {
+        let (before, before_index) = last_where(top_left, |_, node| {
+            below_start(&node.value, range.start_bound())
+        });
+        let (_, last_index) = last_where(top_left, |_, node| {
+            within_end(&node.value, range.end_bound())
+        });
         Self {
-            curr_node,
-            start,
-            end,
-            at_bottom: false,
+            curr_node: unsafe { &*before.right.unwrap().as_ptr() },
+            index: before_index + 1,
+            range,
+            top_left,
+            back: None,
+            remaining: last_index.saturating_sub(before_index),
+            generation,
+            _key: PhantomData,
+        }
+    }
+
+    /// Look at the next element without advancing the iterator.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from(0..10);
+    /// let mut range = sk.range(&4, &5);
+    ///
+    /// assert_eq!(range.peek(), Some(&4));
+    /// assert_eq!(range.next(), Some(&4));
+    /// assert_eq!(range.next(), Some(&5));
+    /// assert_eq!(range.peek(), None);
+    /// ```
+    #[inline]
+    pub fn peek(&self) -> Option<&'a T> {
+        self.generation.check();
+        if self.remaining == 0 {
+            return None;
+        }
+        Some(self.curr_node.value.get_value())
+    }
+
+    /// Fast-forward the iterator, so the next element yielded is the first one `>= item`.
+    ///
+    /// The search goes through the upper levels of the skiplist, so this runs in
+    /// `O(logn)` no matter how far it jumps. Seeking to an element that's already
+    /// been passed, or is before the range, does nothing.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from(0..100);
+    /// let mut range = sk.range(&10, &60);
+    ///
+    /// assert_eq!(range.next(), Some(&10));
+    /// range.seek(&55);
+    /// assert_eq!(range.collect::<Vec<_>>(), vec![&55, &56, &57, &58, &59, &60]);
+    /// ```
+    pub fn seek(&mut self, item: &Q) {
+        self.generation.check();
+        let (before, before_index) = last_where(self.top_left, |_, node| node.value.lt_key(item));
+        if before_index < self.index {
+            return;
         }
+        self.remaining = self.remaining.saturating_sub(before_index + 1 - self.index);
+        self.index = before_index + 1;
+        self.curr_node = unsafe { &*before.right.unwrap().as_ptr() };
     }
 }
 
-impl<'a, T: PartialOrd> Iterator for SkipListRange<'a, T> {
+impl<'a, T, Q, R> Iterator for SkipListRange<'a, T, Q, R>
+where
+    T: PartialOrd + Borrow<Q>,
+    Q: PartialOrd + ?Sized,
+    R: RangeBounds<Q>,
+{
     type Item = &'a T;
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        // Step 1: Find the first node >= self.start
-        while !self.at_bottom {
-            match (self.curr_node.right, self.curr_node.down) {
-                (Some(right), Some(down)) => unsafe {
-                    if &right.as_ref().value < self.start {
-                        self.curr_node = right.as_ptr().as_ref().unwrap();
-                    } else {
-                        self.curr_node = down.as_ptr().as_ref().unwrap();
-                    }
-                },
-                (Some(right), None) => unsafe {
-                    if &right.as_ref().value < self.start {
-                        self.curr_node = right.as_ptr().as_ref().unwrap();
-                    } else {
-                        self.at_bottom = true;
-                        self.curr_node = self.curr_node.right.unwrap().as_ptr().as_ref().unwrap();
-                        break;
-                    }
-                },
-                _ => unreachable!(),
-            }
+        self.generation.check();
+        if self.remaining == 0 {
+            return None;
         }
-        // Verify that we are, indeed, at the bottom
+        // As there's elements remaining, `curr_node` is in the range.
         debug_assert!(self.curr_node.down.is_none());
-        if &self.curr_node.value <= self.end {
-            unsafe {
-                let ret_val = &self.curr_node.value;
-                let next = self.curr_node.right.unwrap().as_ptr().as_ref().unwrap();
-                self.curr_node = next;
-                return Some(ret_val.get_value());
-            }
+        let ret_val = self.curr_node.value.get_value();
+        unsafe {
+            self.curr_node = &*self.curr_node.right.unwrap().as_ptr();
         }
-        None
+        self.index += 1;
+        self.remaining -= 1;
+        Some(ret_val)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T, Q, R> ExactSizeIterator for SkipListRange<'a, T, Q, R>
+where
+    T: PartialOrd + Borrow<Q>,
+    Q: PartialOrd + ?Sized,
+    R: RangeBounds<Q>,
+{
+}
+
+impl<'a, T, Q, R> DoubleEndedIterator for SkipListRange<'a, T, Q, R>
+where
+    T: PartialOrd + Borrow<Q>,
+    Q: PartialOrd + ?Sized,
+    R: RangeBounds<Q>,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.generation.check();
+        if self.remaining == 0 {
+            return None;
+        }
+        let range = &self.range;
+        let top_left = self.top_left;
+        let back = self.back.get_or_insert_with(|| {
+            BackStack::ending_at(top_left, |_, right| {
+                within_end(&right.value, range.end_bound())
+            })
+        });
+        // As there's elements remaining, this is in the range.
+        let node = unsafe { &*back.next_bottom()? };
+        self.remaining -= 1;
+        Some(node.value.get_value())
     }
 }
 
-#[derive(Clone)]
 pub(crate) struct NodeWidth<T> {
     pub curr_node: *mut Node<T>,
     /// The total width traveled so _far_ in the iterator.
@@ -389,6 +1682,15 @@ pub(crate) struct NodeWidth<T> {
     pub curr_width: usize,
 }
 
+// Manual impls, as deriving would needlessly require `T: Clone`.
+impl<T> Clone for NodeWidth<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for NodeWidth<T> {}
+
 impl<T> NodeWidth<T> {
     pub(crate) fn new(curr_node: *mut Node<T>, curr_width: usize) -> Self {
         Self {
@@ -398,25 +1700,37 @@ impl<T> NodeWidth<T> {
     }
 }
 
-pub(crate) struct LeftBiasIterWidth<'a, T> {
+pub(crate) struct LeftBiasIterWidth<'a, T, Q: ?Sized = T> {
     curr_node: *mut Node<T>,
     total_width: usize,
-    item: &'a T,
+    item: &'a Q,
     finished: bool,
+    moves_right: usize,
 }
 
-impl<'a, T> LeftBiasIterWidth<'a, T> {
-    pub(crate) fn new(curr_node: *mut Node<T>, item: &'a T) -> Self {
+impl<'a, T, Q: ?Sized> LeftBiasIterWidth<'a, T, Q> {
+    pub(crate) fn new(curr_node: *mut Node<T>, item: &'a Q) -> Self {
         Self {
             curr_node,
             item,
             finished: false,
             total_width: 0,
+            moves_right: 0,
         }
     }
+
+    /// How many times the search has moved right so far.
+    #[inline]
+    pub(crate) fn moves_right(&self) -> usize {
+        self.moves_right
+    }
 }
 
-impl<'a, T: PartialOrd> Iterator for LeftBiasIterWidth<'a, T> {
+impl<'a, T, Q> Iterator for LeftBiasIterWidth<'a, T, Q>
+where
+    T: Borrow<Q>,
+    Q: PartialOrd + ?Sized,
+{
     type Item = NodeWidth<T>;
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
@@ -428,10 +1742,15 @@ impl<'a, T: PartialOrd> Iterator for LeftBiasIterWidth<'a, T> {
                 match ((*self.curr_node).right, (*self.curr_node).down) {
                     // We're somewhere in the middle of the skiplist
                     (Some(right), Some(down)) => {
+                        // Whichever way the comparison goes, the next node we read is
+                        // either `down` or the node past `right`, so start fetching both.
+                        prefetch(down.as_ptr());
+                        prefetch_right(right);
                         // The node our right is smaller than `item`, so let's advance forward.
-                        if &right.as_ref().value < self.item {
-                            self.total_width += (*self.curr_node).width;
+                        if right.as_ref().value.lt_key(self.item) {
+                            self.total_width += (*self.curr_node).width();
                             self.curr_node = right.as_ptr();
+                            self.moves_right += 1;
                         } else {
                             // The node to our right is the first seen that's larger than `item`,
                             // So we yield it and head down.
@@ -443,13 +1762,14 @@ impl<'a, T: PartialOrd> Iterator for LeftBiasIterWidth<'a, T> {
                     (Some(right), None) => {
                         // We're at the bottom row, and the item to our right >= `self.item`.
                         // This is exactly the same as a linked list -- we don't want to continue further.
-                        if &right.as_ref().value >= self.item {
+                        if right.as_ref().value.ge_key(self.item) {
                             self.finished = true;
                             return Some(NodeWidth::new(self.curr_node, self.total_width));
                         } else {
                             // The node to our right is _smaller_ than us, so continue forward.
+                            self.total_width += (*self.curr_node).width();
                             self.curr_node = right.as_ptr();
-                            self.total_width += 1;
+                            self.moves_right += 1;
                         }
                     }
                     // If we've upheld invariants correctly, there's always a right when iterating
@@ -460,18 +1780,42 @@ impl<'a, T: PartialOrd> Iterator for LeftBiasIterWidth<'a, T> {
         }
     }
 }
+/// Hint that `node` will be read soon, so the search can overlap the
+/// cache miss with the comparison it's already waiting on.
+///
+/// Purely a hint: it never faults, even on a dangling pointer, and compiles
+/// to nothing off x86_64.
+#[inline(always)]
+pub(crate) fn prefetch<T>(node: *const Node<T>) {
+    #[cfg(target_arch = "x86_64")]
+    unsafe {
+        use std::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+        _mm_prefetch::<_MM_HINT_T0>(node as *const i8);
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    let _ = node;
+}
+
+/// Prefetch the node after `right`, the next candidate if the search moves right.
+#[inline(always)]
+unsafe fn prefetch_right<T>(right: NonNull<Node<T>>) {
+    if let Some(next) = right.as_ref().right {
+        prefetch(next.as_ptr());
+    }
+}
+
 /// Left-biased iteration towards `item`.
 ///
 /// Guaranteed to return an iterator of items directly left of `item`,
 /// or where `item` should be in the skiplist.
-pub(crate) struct LeftBiasIter<'a, T> {
+pub(crate) struct LeftBiasIter<'a, T, Q: ?Sized = T> {
     curr_node: *mut Node<T>,
-    item: &'a T,
+    item: &'a Q,
     finished: bool,
 }
 
-impl<'a, T> LeftBiasIter<'a, T> {
-    pub(crate) fn new(curr_node: *mut Node<T>, item: &'a T) -> Self {
+impl<'a, T, Q: ?Sized> LeftBiasIter<'a, T, Q> {
+    pub(crate) fn new(curr_node: *mut Node<T>, item: &'a Q) -> Self {
         Self {
             curr_node,
             item,
@@ -480,7 +1824,11 @@ impl<'a, T> LeftBiasIter<'a, T> {
     }
 }
 
-impl<'a, T: PartialOrd> Iterator for LeftBiasIter<'a, T> {
+impl<'a, T, Q> Iterator for LeftBiasIter<'a, T, Q>
+where
+    T: Borrow<Q>,
+    Q: PartialOrd + ?Sized,
+{
     type Item = *mut Node<T>;
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
@@ -492,8 +1840,10 @@ impl<'a, T: PartialOrd> Iterator for LeftBiasIter<'a, T> {
                 match ((*self.curr_node).right, (*self.curr_node).down) {
                     // We're somewhere in the middle of the skiplist, so if `self.item` is larger than our right,
                     (Some(right), Some(down)) => {
+                        prefetch(down.as_ptr());
+                        prefetch_right(right);
                         // The node our right is smaller than `item`, so let's advance forward.
-                        if &right.as_ref().value < self.item {
+                        if right.as_ref().value.lt_key(self.item) {
                             self.curr_node = right.as_ptr();
                         } else {
                             // The node to our right is the first seen that's larger than `item`,
@@ -505,7 +1855,7 @@ impl<'a, T: PartialOrd> Iterator for LeftBiasIter<'a, T> {
                     (Some(right), None) => {
                         // We're at the bottom row, and the item to our right >= `self.item`.
                         // This is exactly the same as a linked list -- we don't want to continue further.
-                        if &right.as_ref().value >= self.item {
+                        if right.as_ref().value.ge_key(self.item) {
                             self.finished = true;
                             return Some(self.curr_node);
                         } else {
@@ -525,11 +1875,14 @@ impl<'a, T: PartialOrd> Iterator for LeftBiasIter<'a, T> {
 pub struct IterRangeWith<'a, T, F>
 where
     T: PartialOrd,
-    F: Fn(&'a T) -> RangeHint,
+    F: Fn(&T) -> RangeHint,
 {
     inclusive_fn: F,
     curr_node: &'a Node<T>,
     at_bottom: bool,
+    /// How many elements are in the range and left to yield.
+    remaining: usize,
+    generation: Generation<'a>,
 }
 
 impl<'a, T, F> IterRangeWith<'a, T, F>
@@ -538,11 +1891,23 @@ where
     F: Fn(&T) -> RangeHint,
 {
     #[inline]
-    pub(crate) fn new(curr_node: &'a Node<T>, inclusive_fn: F) -> Self {
+    pub(crate) fn new(curr_node: &'a Node<T>, inclusive_fn: F, generation: Generation<'a>) -> Self {
+        let hint = |node: &Node<T>| node.value.value().map(&inclusive_fn);
+        let (_, before_index) = last_where(curr_node, |_, node| {
+            matches!(hint(node), Some(RangeHint::SmallerThanRange))
+        });
+        let (_, last_index) = last_where(curr_node, |_, node| {
+            matches!(
+                hint(node),
+                Some(RangeHint::SmallerThanRange) | Some(RangeHint::InRange)
+            )
+        });
         Self {
             inclusive_fn,
             curr_node,
             at_bottom: false,
+            remaining: last_index.saturating_sub(before_index),
+            generation,
         }
     }
 
@@ -550,14 +1915,13 @@ where
     #[inline]
     fn item_smaller_than_range(&self, item: &NodeValue<T>) -> bool {
         match item {
-            NodeValue::NegInf => true,
-            NodeValue::PosInf => false,
-            NodeValue::Value(v) => {
-                if let RangeHint::SmallerThanRange = (self.inclusive_fn)(v) {
-                    true
-                } else {
-                    false
-                }
+            NodeValue::NegInf(_) => true,
+            NodeValue::PosInf(_) => false,
+            NodeValue::Value(..) => {
+                matches!(
+                    (self.inclusive_fn)(item.get_value()),
+                    RangeHint::SmallerThanRange
+                )
             }
         }
     }
@@ -566,14 +1930,10 @@ where
     #[inline]
     fn item_in_range(&self, item: &NodeValue<T>) -> bool {
         match item {
-            NodeValue::NegInf => false,
-            NodeValue::PosInf => false,
-            NodeValue::Value(v) => {
-                if let RangeHint::InRange = (self.inclusive_fn)(v) {
-                    true
-                } else {
-                    false
-                }
+            NodeValue::NegInf(_) => false,
+            NodeValue::PosInf(_) => false,
+            NodeValue::Value(..) => {
+                matches!((self.inclusive_fn)(item.get_value()), RangeHint::InRange)
             }
         }
     }
@@ -587,6 +1947,10 @@ where
     type Item = &'a T;
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
+        self.generation.check();
+        if self.remaining == 0 {
+            return None;
+        }
         // Step 1: Find the *largest* element smaller than our range.
         // This process is _very_ similar to LeftBiasIter, where
         // we search for the element immediately left of the desired one.
@@ -628,11 +1992,18 @@ where
                 let ret_val = &self.curr_node.value;
                 let next = self.curr_node.right.unwrap().as_ptr().as_ref().unwrap();
                 self.curr_node = next;
+                self.remaining -= 1;
                 return Some(ret_val.get_value());
             }
         }
         None
     }
+
+    /// This is exact, as long as `inclusive_fn` orders elements consistently.
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
 }
 
 #[cfg(test)]
@@ -656,6 +2027,192 @@ mod tests {
         assert_eq!(foo, second)
     }
 
+    #[test]
+    fn test_exact_size() {
+        let sk = SkipList::from(0..10u32);
+        let mut iter = sk.iter_all();
+        assert_eq!(iter.len(), 10);
+        iter.next();
+        iter.next_back();
+        assert_eq!(iter.len(), 8);
+        assert_eq!(iter.by_ref().count(), 8);
+        assert_eq!(iter.len(), 0);
+        assert_eq!(iter.next(), None);
+
+        let mut into_iter = sk.clone().into_iter();
+        assert_eq!(into_iter.len(), 10);
+        assert_eq!(into_iter.next(), Some(0));
+        assert_eq!(into_iter.len(), 9);
+        assert_eq!(into_iter.collect::<Vec<_>>(), (1..10).collect::<Vec<_>>());
+        assert_eq!(SkipList::<u32>::new().into_iter().next(), None);
+    }
+
+    #[test]
+    fn test_range_size_hints() {
+        let sk: SkipList<i32> = (0..100).map(|i| i * 2).collect();
+        for start in -2..202 {
+            for end in [-1, 0, 1, 50, 101, 198, 250].iter() {
+                let mut range = sk.range(&start, end);
+                let mut index_range = sk.index_range(start.max(0) as usize..*end.max(&0) as usize);
+                let mut range_with = sk.range_with(|&v| {
+                    if v < start {
+                        RangeHint::SmallerThanRange
+                    } else if v > *end {
+                        RangeHint::LargerThanRange
+                    } else {
+                        RangeHint::InRange
+                    }
+                });
+                loop {
+                    let hints = (range.len(), index_range.len(), range_with.size_hint().0);
+                    let items = (range.next(), index_range.next(), range_with.next());
+                    assert_eq!(hints.0, items.0.is_some() as usize + range.len());
+                    assert_eq!(hints.1, items.1.is_some() as usize + index_range.len());
+                    assert_eq!(
+                        hints.2,
+                        items.2.is_some() as usize + range_with.size_hint().0
+                    );
+                    if items == (None, None, None) {
+                        break;
+                    }
+                }
+                let mut range = sk.range(&start, end);
+                range.next_back();
+                assert_eq!(range.len(), range.count());
+            }
+        }
+    }
+
+    #[test]
+    fn test_iterall_nth() {
+        let sk = SkipList::from(0..500u32);
+        for start in 0..5 {
+            for n in (0..600).step_by(7) {
+                let mut iter = sk.iter_all();
+                iter.nth(start);
+                let expected = start as u32 + n as u32 + 1;
+                assert_eq!(iter.nth(n), (0..500).find(|&i| i == expected).as_ref());
+                assert_eq!(iter.len(), 499usize.saturating_sub(start + n + 1));
+                assert_eq!(iter.next(), (0..500).find(|&i| i == expected + 1).as_ref());
+            }
+        }
+        let mut iter = sk.iter_all().skip(250);
+        assert_eq!(iter.next(), Some(&250));
+        let mut iter = sk.iter_all();
+        iter.next_back();
+        assert_eq!(iter.nth(497), Some(&497));
+        assert_eq!(iter.nth(1), None);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn test_peek() {
+        let sk = SkipList::from(0..20u32);
+        let mut iter = sk.iter_all();
+        let mut range = sk.range(&5, &15);
+        let mut index_range = sk.index_range(5..16);
+        loop {
+            let peeked = (iter.peek(), range.peek(), index_range.peek());
+            assert_eq!(peeked, (iter.next(), range.next(), index_range.next()));
+            if peeked.0.is_none() {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn test_into_iter_ref() {
+        let sk = SkipList::from(vec![3, 1, 2]);
+        let mut seen = Vec::new();
+        for item in &sk {
+            seen.push(*item);
+        }
+        assert_eq!(seen, vec![1, 2, 3]);
+        assert!(sk.iter().eq(sk.iter_all()));
+    }
+
+    #[test]
+    fn test_into_iter_moves() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+        #[derive(Debug, PartialEq, PartialOrd)]
+        struct Counted(u32, Rc<Cell<usize>>);
+        impl Clone for Counted {
+            fn clone(&self) -> Self {
+                self.1.set(self.1.get() + 1);
+                Counted(self.0, Rc::clone(&self.1))
+            }
+        }
+        let clones = Rc::new(Cell::new(0));
+        let sk: SkipList<_> = (0..100).map(|i| Counted(i, Rc::clone(&clones))).collect();
+        let before = clones.get();
+        let mut iter = sk.into_iter();
+        assert_eq!(iter.next().map(|c| c.0), Some(0));
+        let rest: Vec<_> = iter.map(|c| c.0).collect();
+        assert_eq!(rest, (1..100).collect::<Vec<_>>());
+        assert_eq!(clones.get(), before);
+        // Everything was dropped along the way
+        assert_eq!(Rc::strong_count(&clones), 1);
+    }
+
+    #[test]
+    fn test_into_iter_rev() {
+        let sk = SkipList::from(0..100u32);
+        let stack: Vec<_> = sk.clone().into_iter().rev().collect();
+        assert_eq!(stack, (0..100).rev().collect::<Vec<_>>());
+
+        let mut iter = sk.into_iter();
+        let mut front = Vec::new();
+        let mut back = Vec::new();
+        for i in 0.. {
+            let item = if i % 3 == 0 {
+                iter.next_back().map(|x| back.push(x))
+            } else {
+                iter.next().map(|x| front.push(x))
+            };
+            if item.is_none() {
+                break;
+            }
+        }
+        assert_eq!(iter.len(), 0);
+        front.extend(back.into_iter().rev());
+        assert_eq!(front, (0..100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_index_range_rev() {
+        let sk = SkipList::from(0..300u32);
+        let rev: Vec<_> = sk.index_range(100..200).rev().cloned().collect();
+        assert_eq!(rev, (100..200).rev().collect::<Vec<_>>());
+        for (start, end) in [(0, 0), (0, 1), (5, 17), (290, 400), (299, 300), (310, 320)].iter() {
+            let mut range = sk.index_range(*start..=*end);
+            let mut front = Vec::new();
+            let mut back = Vec::new();
+            while let Some(x) = range.next_back() {
+                back.push(*x);
+                if let Some(x) = range.next() {
+                    front.push(*x);
+                }
+            }
+            front.extend(back.into_iter().rev());
+            let expected: Vec<_> = (*start as u32..(*end as u32 + 1).min(300)).collect();
+            assert_eq!(front, expected);
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "modified while it was being iterated")]
+    fn test_generation_mismatch() {
+        let mut sk = SkipList::from(0..10u32);
+        let sk_ptr: *mut SkipList<u32> = &mut sk;
+        let mut iter = sk.iter_all();
+        iter.next();
+        // Misuse that the borrow checker can't see
+        unsafe { (*sk_ptr).insert(20) };
+        iter.next();
+    }
+
     #[test]
     fn test_empty() {
         let sk = SkipList::<usize>::new();
@@ -698,37 +2255,44 @@ mod tests {
 
     #[test]
     fn test_inclusion_fn_range_with() {
-        use crate::iter::IterRangeWith;
-        use crate::{Node, NodeValue};
-        let n = Node {
-            right: None,
-            down: None,
-            value: NodeValue::Value(3),
-            width: 1,
-        };
-        let srw = IterRangeWith::new(&n, |&i| {
-            if i < 2 {
-                RangeHint::SmallerThanRange
-            } else if i > 4 {
-                RangeHint::LargerThanRange
-            } else {
-                RangeHint::InRange
-            }
-        });
-        assert!(srw.item_smaller_than_range(&NodeValue::Value(1)) == true);
-        assert!(srw.item_smaller_than_range(&NodeValue::Value(2)) == false);
-        assert!(srw.item_smaller_than_range(&NodeValue::Value(4)) == false);
-        assert!(srw.item_smaller_than_range(&NodeValue::Value(5)) == false);
-        assert!(srw.item_smaller_than_range(&NodeValue::NegInf) == true);
-        assert!(srw.item_smaller_than_range(&NodeValue::PosInf) == false);
-
-        assert!(srw.item_in_range(&NodeValue::Value(1)) == false);
-        assert!(srw.item_in_range(&NodeValue::Value(2)) == true);
-        assert!(srw.item_in_range(&NodeValue::Value(3)) == true);
-        assert!(srw.item_in_range(&NodeValue::Value(4)) == true);
-        assert!(srw.item_in_range(&NodeValue::Value(5)) == false);
-        assert!(srw.item_in_range(&NodeValue::PosInf) == false);
-        assert!(srw.item_in_range(&NodeValue::NegInf) == false);
+        use crate::iter::{Generation, IterRangeWith};
+        use crate::pool::NodePool;
+        use crate::NodeValue;
+        use std::sync::atomic::AtomicUsize;
+        let mut pool = NodePool::default();
+        let nodes: Vec<_> = (0..6).map(|i| pool.make_tower(i, 1)).collect();
+        let value = |i: usize| unsafe { &nodes[i].as_ref().value };
+        let counter = AtomicUsize::new(0);
+        let srw = IterRangeWith::new(
+            unsafe { nodes[3].as_ref() },
+            |&i| {
+                if i < 2 {
+                    RangeHint::SmallerThanRange
+                } else if i > 4 {
+                    RangeHint::LargerThanRange
+                } else {
+                    RangeHint::InRange
+                }
+            },
+            Generation::new(&counter),
+        );
+        assert!(srw.item_smaller_than_range(value(1)));
+        assert!(!srw.item_smaller_than_range(value(2)));
+        assert!(!srw.item_smaller_than_range(value(4)));
+        assert!(!srw.item_smaller_than_range(value(5)));
+        assert!(srw.item_smaller_than_range(&NodeValue::NegInf(0)));
+        assert!(!srw.item_smaller_than_range(&NodeValue::PosInf(0)));
+
+        assert!(!srw.item_in_range(value(1)));
+        assert!(srw.item_in_range(value(2)));
+        assert!(srw.item_in_range(value(3)));
+        assert!(srw.item_in_range(value(4)));
+        assert!(!srw.item_in_range(value(5)));
+        assert!(!srw.item_in_range(&NodeValue::PosInf(0)));
+        assert!(!srw.item_in_range(&NodeValue::NegInf(0)));
+        for node in nodes {
+            unsafe { pool.free(node) };
+        }
     }
 
     #[test]
@@ -843,6 +2407,99 @@ mod tests {
         assert_eq!(f, expected);
     }
 
+    #[test]
+    fn test_union() {
+        let left = SkipList::from(vec![1, 3, 5, 7]);
+        let right = SkipList::from(vec![2, 3, 4, 7, 8]);
+        let got: Vec<i32> = left.union(&right).cloned().collect();
+        assert_eq!(got, vec![1, 2, 3, 4, 5, 7, 8]);
+        let got: Vec<i32> = right.union(&left).cloned().collect();
+        assert_eq!(got, vec![1, 2, 3, 4, 5, 7, 8]);
+
+        let empty = SkipList::new();
+        let got: Vec<i32> = left.union(&empty).cloned().collect();
+        assert_eq!(got, vec![1, 3, 5, 7]);
+        let got: Vec<i32> = empty.union(&left).cloned().collect();
+        assert_eq!(got, vec![1, 3, 5, 7]);
+        assert_eq!(empty.union(&empty).count(), 0);
+    }
+
+    #[test]
+    fn test_intersection() {
+        let left = SkipList::from(vec![1, 3, 5, 7]);
+        let right = SkipList::from(vec![2, 3, 4, 7, 8]);
+        let got: Vec<i32> = left.intersection(&right).cloned().collect();
+        assert_eq!(got, vec![3, 7]);
+        let got: Vec<i32> = right.intersection(&left).cloned().collect();
+        assert_eq!(got, vec![3, 7]);
+
+        let empty = SkipList::new();
+        assert_eq!(left.intersection(&empty).count(), 0);
+        assert_eq!(empty.intersection(&left).count(), 0);
+
+        let small = SkipList::from(vec![0, 250, 999, 1000, 5000]);
+        let large = SkipList::from(0..1000);
+        let got: Vec<i32> = small.intersection(&large).cloned().collect();
+        assert_eq!(got, vec![0, 250, 999]);
+        let got: Vec<i32> = large.intersection(&small).cloned().collect();
+        assert_eq!(got, vec![0, 250, 999]);
+    }
+
+    #[test]
+    fn test_difference() {
+        let left = SkipList::from(vec![1, 3, 5, 7]);
+        let right = SkipList::from(vec![2, 3, 4, 7, 8]);
+        let got: Vec<i32> = left.difference(&right).cloned().collect();
+        assert_eq!(got, vec![1, 5]);
+        let got: Vec<i32> = right.difference(&left).cloned().collect();
+        assert_eq!(got, vec![2, 4, 8]);
+
+        let empty = SkipList::new();
+        let got: Vec<i32> = left.difference(&empty).cloned().collect();
+        assert_eq!(got, vec![1, 3, 5, 7]);
+        assert_eq!(empty.difference(&left).count(), 0);
+    }
+
+    #[test]
+    fn test_symmetric_difference() {
+        let left = SkipList::from(vec![1, 3, 5, 7]);
+        let right = SkipList::from(vec![2, 3, 4, 7, 8]);
+        let got: Vec<i32> = left.symmetric_difference(&right).cloned().collect();
+        assert_eq!(got, vec![1, 2, 4, 5, 8]);
+        let got: Vec<i32> = right.symmetric_difference(&left).cloned().collect();
+        assert_eq!(got, vec![1, 2, 4, 5, 8]);
+        assert_eq!(left.symmetric_difference(&left).count(), 0);
+    }
+
+    #[test]
+    fn test_diff() {
+        use crate::Edit;
+        let old = SkipList::from(vec![1, 3, 5, 7]);
+        let new = SkipList::from(vec![2, 3, 4, 7, 8]);
+        let got: Vec<_> = old.diff(&new).collect();
+        assert_eq!(
+            got,
+            vec![
+                Edit::Removed(&1),
+                Edit::Added(&2),
+                Edit::Added(&4),
+                Edit::Removed(&5),
+                Edit::Added(&8)
+            ]
+        );
+        assert_eq!(old.diff(&old).count(), 0);
+
+        // Applying the edits to `old` should produce `new`.
+        let mut patched = old.clone();
+        for edit in old.diff(&new) {
+            match edit {
+                Edit::Added(item) => assert!(patched.insert(*item)),
+                Edit::Removed(item) => assert!(patched.remove(item)),
+            }
+        }
+        assert_eq!(patched, new);
+    }
+
     // You should run this test with miri
     #[test]
     fn test_range_pathological_no_panic() {
@@ -869,4 +2526,35 @@ mod tests {
             .cloned()
             .collect();
     }
+
+    #[test]
+    fn test_seek() {
+        let sk: SkipList<i32> = (0..200).map(|i| i * 3).collect();
+        for first in 0..20 {
+            for target in -1..100 {
+                let mut iter = sk.iter_all();
+                let mut range = sk.range(&6, &450);
+                iter.nth(first);
+                range.nth(first);
+                iter.seek(&target);
+                range.seek(&target);
+                let lower = std::cmp::max(target, first as i32 * 3 + 1);
+                let expected: Vec<_> = sk.iter_all().filter(|&&v| v >= lower).collect();
+                assert_eq!(iter.size_hint().0, expected.len());
+                assert_eq!(iter.collect::<Vec<_>>(), expected);
+                let lower = std::cmp::max(target, first as i32 * 3 + 7);
+                let expected: Vec<_> = sk.range(&6, &450).filter(|&&v| v >= lower).collect();
+                assert_eq!(range.collect::<Vec<_>>(), expected);
+            }
+        }
+        // Seeking past the back end of the iterator doesn't yield those elements again
+        let mut iter = sk.iter_all();
+        assert_eq!(iter.next_back(), Some(&597));
+        iter.seek(&597);
+        assert_eq!(iter.next(), None);
+        let mut range = sk.range(&0, &30);
+        assert_eq!(range.next_back(), Some(&30));
+        range.seek(&30);
+        assert_eq!(range.next(), None);
+    }
 }
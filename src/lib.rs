@@ -1,38 +1,169 @@
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
+
+use crate::builder::LevelGenerator;
+pub use crate::builder::SkipListBuilder;
+pub use crate::counted::CountedMultiSet;
+pub use crate::deterministic::DeterministicSkipList;
+pub use crate::finger::Finger;
+pub use crate::frozen::FrozenSkipList;
+pub use crate::guard::ValueMut;
 use crate::iter::{
-    IterAll, IterRangeWith, LeftBiasIter, LeftBiasIterWidth, NodeRightIter, NodeWidth,
-    SkipListIndexRange, SkipListRange, VerticalIter,
+    prefetch, Around, Chunks, Diff, Difference, DrainTowers, Gaps, Generation, Intersection,
+    IterAll, IterMut, IterRangeWith, LeftBiasIter, LeftBiasIterWidth, Levels, MergeIter,
+    NodeRightIter, NodeWidth, PopMax, PopMin, SearchPath, SkipListIndexRange, SkipListRange,
+    SymmetricDifference, Union, VerticalIter, Windows,
 };
-use core::ops::RangeBounds;
-use rand::prelude::*;
+pub use crate::map::SkipMap;
+pub use crate::multimap::OrderedMultiMap;
+pub use crate::multiset::SkipMultiSet;
+use crate::pool::{NodePool, TowerHeader};
+pub use crate::unrolled::UnrolledSkipList;
+use core::ops::{Bound, RangeBounds};
+use rand::{Rng, RngCore};
+use std::borrow::Borrow;
 use std::cmp::{Ordering, PartialOrd};
 use std::fmt;
-use std::iter::FromIterator;
-use std::ops::Index;
+use std::iter::{Enumerate, FromIterator, Rev};
+use std::ops::{BitAnd, BitOr, BitXor, Index, Range, RangeInclusive, Sub};
 use std::ptr::NonNull;
+use std::sync::atomic::Ordering as AtomicOrdering;
+pub mod builder;
+#[cfg(feature = "concurrent")]
+pub mod concurrent;
+pub mod counted;
+pub mod deterministic;
+pub mod finger;
+pub mod frozen;
+pub mod guard;
 pub mod iter;
+pub mod map;
+pub mod multimap;
+pub mod multiset;
+mod pool;
+#[cfg(feature = "rayon")]
+pub mod rayon;
+pub mod unrolled;
 
 #[cfg(feature = "serde_support")]
 mod serde;
 
-#[derive(PartialEq, Debug)]
+/// Create a `SkipList` containing the given elements, analogous to `vec!`.
+///
+/// Duplicate elements are only inserted once. Sorted input is built in O(n).
+///
+/// ```rust
+/// use convenient_skiplist::{skiplist, SkipList};
+/// let sk = skiplist![3, 1, 2, 2];
+/// assert_eq!(vec![1, 2, 3], Vec::from(sk));
+/// let empty: SkipList<u32> = skiplist![];
+/// assert!(empty.is_empty());
+/// ```
+#[macro_export]
+macro_rules! skiplist {
+    () => {
+        $crate::SkipList::new()
+    };
+    ($($item:expr),+ $(,)?) => {
+        $crate::SkipList::from(::std::vec![$($item),+])
+    };
+}
+
+/// A node's width: how many elements on the bottom row it takes to reach
+/// the node to its right.
+#[cfg(not(feature = "compact_widths"))]
+type Width = usize;
+#[cfg(feature = "compact_widths")]
+type Width = u32;
+
+/// What a node holds: one of the sentinels at either end of a row, or an
+/// element, whose value lives in the allocation of its tower.
+///
+/// The node's width is kept here too, next to the tag, so that a `u32`
+/// width fits alongside it rather than being padded out to its own word.
 enum NodeValue<T> {
-    NegInf,
-    Value(T),
-    PosInf,
+    NegInf(Width),
+    Value(NonNull<TowerHeader<T>>, Width),
+    PosInf(Width),
 }
 
 impl<T> NodeValue<T> {
     #[inline]
     fn get_value(&self) -> &T {
-        match &self {
-            NodeValue::Value(v) => v,
+        match self {
+            NodeValue::Value(tower, _) => unsafe { (*tower.as_ptr()).value() },
+            _ => unreachable!("Failed to get value! This shouldn't happen."),
+        }
+    }
+    #[inline]
+    fn get_value_mut(&mut self) -> &mut T {
+        match self {
+            NodeValue::Value(tower, _) => unsafe { (*tower.as_ptr()).value_mut() },
             _ => unreachable!("Failed to get value! This shouldn't happen."),
         }
     }
     #[inline]
+    fn value(&self) -> Option<&T> {
+        match self {
+            NodeValue::Value(tower, _) => unsafe { Some((*tower.as_ptr()).value()) },
+            _ => None,
+        }
+    }
+    /// Move the value out of the tower. Its nodes can only be freed afterwards.
+    #[inline]
+    fn take_value(&mut self) -> T {
+        match self {
+            NodeValue::Value(tower, _) => unsafe { (*tower.as_ptr()).take() },
+            _ => unreachable!("Failed to take value! This shouldn't happen."),
+        }
+    }
+    #[inline]
     fn is_pos_inf(&self) -> bool {
-        match &self {
-            NodeValue::PosInf => true,
+        matches!(self, NodeValue::PosInf(_))
+    }
+}
+
+impl<T> NodeValue<T> {
+    /// Compare against a borrowed form of `T`. NegInf and PosInf are
+    /// smaller and larger than everything respectively.
+    #[inline]
+    fn partial_cmp_key<Q>(&self, key: &Q) -> Option<Ordering>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        match self {
+            NodeValue::NegInf(_) => Some(Ordering::Less),
+            NodeValue::PosInf(_) => Some(Ordering::Greater),
+            NodeValue::Value(..) => self.get_value().borrow().partial_cmp(key),
+        }
+    }
+    #[inline]
+    fn lt_key<Q>(&self, key: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        matches!(self.partial_cmp_key(key), Some(Ordering::Less))
+    }
+    #[inline]
+    fn ge_key<Q>(&self, key: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        matches!(
+            self.partial_cmp_key(key),
+            Some(Ordering::Greater) | Some(Ordering::Equal)
+        )
+    }
+    #[inline]
+    fn eq_key<Q>(&self, key: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: PartialEq + ?Sized,
+    {
+        match self {
+            NodeValue::Value(..) => self.get_value().borrow() == key,
             _ => false,
         }
     }
@@ -41,20 +172,41 @@ impl<T> NodeValue<T> {
 impl<T: PartialEq> PartialEq<T> for NodeValue<T> {
     #[inline]
     fn eq(&self, other: &T) -> bool {
-        match self {
-            NodeValue::Value(v) => v == other,
+        self.value() == Some(other)
+    }
+}
+
+impl<T: PartialEq> PartialEq for NodeValue<T> {
+    #[inline]
+    fn eq(&self, other: &NodeValue<T>) -> bool {
+        match (self, other) {
+            (NodeValue::NegInf(_), NodeValue::NegInf(_))
+            | (NodeValue::PosInf(_), NodeValue::PosInf(_)) => true,
+            (NodeValue::Value(..), NodeValue::Value(..)) => self.get_value() == other.get_value(),
             _ => false,
         }
     }
 }
 
+impl<T: fmt::Debug> fmt::Debug for NodeValue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NodeValue::NegInf(_) => f.write_str("NegInf"),
+            NodeValue::Value(..) => f.debug_tuple("Value").field(self.get_value()).finish(),
+            NodeValue::PosInf(_) => f.write_str("PosInf"),
+        }
+    }
+}
+
 impl<T: PartialOrd> PartialOrd<NodeValue<T>> for NodeValue<T> {
     #[inline]
     fn partial_cmp(&self, other: &NodeValue<T>) -> Option<Ordering> {
         match (self, other) {
-            (NodeValue::NegInf, _) => Some(Ordering::Less),
-            (_, NodeValue::PosInf) => Some(Ordering::Less),
-            (NodeValue::Value(l), NodeValue::Value(r)) => l.partial_cmp(r),
+            (NodeValue::NegInf(_), _) => Some(Ordering::Less),
+            (_, NodeValue::PosInf(_)) => Some(Ordering::Less),
+            (NodeValue::Value(..), NodeValue::Value(..)) => {
+                self.get_value().partial_cmp(other.get_value())
+            }
             _ => unreachable!(),
         }
     }
@@ -64,9 +216,9 @@ impl<T: PartialOrd> PartialOrd<T> for NodeValue<T> {
     #[inline]
     fn partial_cmp(&self, other: &T) -> Option<Ordering> {
         match self {
-            NodeValue::NegInf => Some(Ordering::Less),
-            NodeValue::PosInf => Some(Ordering::Greater),
-            NodeValue::Value(v) => v.partial_cmp(other),
+            NodeValue::NegInf(_) => Some(Ordering::Less),
+            NodeValue::PosInf(_) => Some(Ordering::Greater),
+            NodeValue::Value(..) => self.get_value().partial_cmp(other),
         }
     }
 }
@@ -74,31 +226,70 @@ impl<T: PartialOrd> PartialOrd<T> for NodeValue<T> {
 struct Node<T> {
     right: Option<NonNull<Node<T>>>,
     down: Option<NonNull<Node<T>>>,
+    /// Also holds the node's width.
     value: NodeValue<T>,
-    width: usize,
 }
 
 impl<T> Node<T> {
+    /// How many elements on the bottom row it takes to reach the node to our right.
+    #[inline]
+    fn width(&self) -> usize {
+        let width: Width = match self.value {
+            NodeValue::NegInf(width) | NodeValue::Value(_, width) | NodeValue::PosInf(width) => {
+                width
+            }
+        };
+        width as usize
+    }
+
+    #[inline]
+    fn set_width(&mut self, width: usize) {
+        #[cfg(feature = "compact_widths")]
+        assert!(
+            width <= Width::MAX as usize,
+            "too many elements for the `compact_widths` feature"
+        );
+        match &mut self.value {
+            NodeValue::NegInf(curr) | NodeValue::Value(_, curr) | NodeValue::PosInf(curr) => {
+                *curr = width as Width
+            }
+        }
+    }
+
     #[inline]
     fn nodes_skipped_over(&self) -> usize {
-        self.width - 1
+        self.width() - 1
     }
 
     #[inline]
-    fn clear_right(&mut self) {
-        self.width = 1;
+    fn clear_right(&mut self, pool: &mut NodePool<T>) {
+        self.set_width(1);
         unsafe {
             while let Some(right) = self.right {
                 if right.as_ref().value.is_pos_inf() {
                     break;
                 }
                 let garbage = std::mem::replace(&mut self.right, (*right.as_ptr()).right);
-                drop(Box::from_raw(garbage.unwrap().as_ptr()));
+                pool.free(garbage.unwrap());
             }
         }
     }
 }
 
+/// Hand out the nodes of the tower starting at `bottom` to `stitch_tower`, bottom first.
+#[inline]
+fn tower_levels<T>(
+    bottom: NonNull<Node<T>>,
+) -> impl FnMut(&mut NodePool<T>, usize) -> NonNull<Node<T>> {
+    let mut level = 0;
+    move |_, width| unsafe {
+        let node = bottom.as_ptr().add(level);
+        level += 1;
+        (*node).set_width(width);
+        NonNull::new_unchecked(node)
+    }
+}
+
 impl<T: fmt::Debug> fmt::Debug for Node<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "Node(")?;
@@ -115,7 +306,7 @@ impl<T: fmt::Debug> fmt::Debug for Node<T> {
                 .map(|some| format!("{:?}", unsafe { &some.as_ref().value }))
         )?;
         writeln!(f, "  value: {:?}", self.value)?;
-        writeln!(f, "  width: {:?}", self.width)?;
+        writeln!(f, "  width: {:?}", self.width())?;
         write!(f, ")")
     }
 }
@@ -134,6 +325,47 @@ pub enum RangeHint {
     LargerThanRange,
 }
 
+/// A single step of the edit script produced by `SkipList::diff`:
+///
+/// - Added: the element is only in the other skiplist, so it needs to be added.
+/// - Removed: the element is only in this skiplist, so it needs to be removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edit<'a, T> {
+    Added(&'a T),
+    Removed(&'a T),
+}
+
+/// The median of a skiplist, as returned by `SkipList::median`:
+///
+/// - Single: the skiplist has an odd length, so there's one middle element.
+/// - Pair: the skiplist has an even length, so the two middle elements are returned, smallest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Median<'a, T> {
+    Single(&'a T),
+    Pair(&'a T, &'a T),
+}
+
+/// Types where every value has a next value, used by `SkipList::gaps`.
+pub trait Successor: Sized {
+    /// The smallest value larger than `self`, or `None` if `self` is the largest value.
+    fn successor(&self) -> Option<Self>;
+}
+
+macro_rules! impl_successor {
+    ($($t:ty),*) => {
+        $(
+            impl Successor for $t {
+                #[inline]
+                fn successor(&self) -> Option<Self> {
+                    self.checked_add(1)
+                }
+            }
+        )*
+    };
+}
+
+impl_successor!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
 /// `SkipLists` are fast probabilistic data-structures that feature logarithmic time complexity for inserting elements,
 /// testing element association, removing elements, and finding ranges of elements.
 ///
@@ -153,16 +385,32 @@ pub enum RangeHint {
 /// assert!(!sk.contains(&10));
 /// assert!(sk.remove(&0)); // remove is also O(log(n))
 /// assert!(sk == sk); // equality checking is O(n)
-/// let from_vec = SkipList::from(vec![1usize, 2, 3].into_iter()); // From<Vec<T>> is O(nlogn)
+/// let from_vec = SkipList::from(vec![1usize, 2, 3]); // From<Vec<T>> is O(n) for sorted input
 /// assert_eq!(vec![1, 2, 3], from_vec.iter_all().cloned().collect::<Vec<usize>>());
 /// ```
 pub struct SkipList<T> {
     top_left: NonNull<Node<T>>,
     height: usize,
     len: usize,
-    _prevent_sync_send: std::marker::PhantomData<*const ()>,
+    levels: LevelGenerator,
+    /// When set, tower heights are derived from a hash of each element.
+    level_hash: Option<fn(&T) -> u64>,
+    pool: NodePool<T>,
 }
 
+// SAFETY: every node and tower is reachable only through the skiplist that
+// owns it; no pointer into them is shared with another skiplist or kept in
+// a thread-local. So moving a skiplist moves sole ownership of its elements,
+// which is fine as long as they can be sent. The level generator and custom
+// allocators are `Send` by construction.
+unsafe impl<T: Send> Send for SkipList<T> {}
+
+// SAFETY: methods taking `&self` only ever read nodes and towers, and the
+// generation counter is atomic. The one shared-reference path that touches
+// the RNG or a custom allocator is `clone`, which clones them through `&self`,
+// so those are required to be `Sync` too.
+unsafe impl<T: Sync> Sync for SkipList<T> {}
+
 impl<T> Drop for SkipList<T> {
     fn drop(&mut self) {
         // Main idea: Start in top left and iterate row by row.
@@ -178,9 +426,9 @@ impl<T> Drop for SkipList<T> {
                 }
                 while let Some(right) = (*curr_node).right {
                     let garbage = std::mem::replace(&mut curr_node, right.as_ptr());
-                    drop(Box::from_raw(garbage));
+                    self.pool.free(NonNull::new_unchecked(garbage));
                 }
-                drop(Box::from_raw(curr_node));
+                self.pool.free(NonNull::new_unchecked(curr_node));
                 if let Some(next_down) = next_down {
                     curr_left_node = next_down;
                     curr_node = curr_left_node;
@@ -192,40 +440,229 @@ impl<T> Drop for SkipList<T> {
     }
 }
 
-impl<T: Clone + PartialOrd> From<SkipList<T>> for Vec<T> {
+impl<T> From<SkipList<T>> for Vec<T> {
     fn from(sk: SkipList<T>) -> Vec<T> {
-        sk.iter_all().cloned().collect()
+        sk.into_iter().collect()
     }
 }
 
 impl<T: Clone + PartialOrd> Clone for SkipList<T> {
     fn clone(&self) -> Self {
-        SkipList::from(self.iter_all().cloned())
+        let mut sk = SkipList::with_pool(self.levels.fork(), self.pool.fresh());
+        sk.level_hash = self.level_hash;
+        sk.reserve_nodes(self.len());
+        sk.extend(self.iter_all().cloned());
+        sk
     }
 }
 
-impl<T: PartialOrd + Clone> FromIterator<T> for SkipList<T> {
+impl<T: PartialOrd> FromIterator<T> for SkipList<T> {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> SkipList<T> {
-        let mut sk = SkipList::new();
+        let iter = iter.into_iter();
+        let mut sk = SkipList::with_capacity(iter.size_hint().0);
+        sk.extend(iter);
+        sk
+    }
+}
+
+impl<T: PartialOrd> Extend<T> for SkipList<T> {
+    /// Insert every item from `iter` into the skiplist.
+    ///
+    /// Items larger than everything in the skiplist are appended directly
+    /// onto the end of every level without searching, so extending with
+    /// already-sorted input costs `O(1)` expected time per item instead of `O(logn)`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let mut sk = SkipList::from(0..5);
+    /// sk.extend(5..10); // Sorted, so appended in O(1) per item
+    /// sk.extend(vec![20, 15, 1]); // Unsorted, so inserted as usual
+    ///
+    /// assert_eq!(vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 15, 20], Vec::from(sk));
+    /// ```
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        // The right edge stays valid for as long as we only append.
+        let mut edge = None;
         for item in iter {
-            sk.insert(item);
+            let edge_ref = edge.get_or_insert_with(|| self.right_edge());
+            let appendable = unsafe {
+                match (*edge_ref[0].curr_node).value.value() {
+                    Some(last) => last < &item,
+                    None => true,
+                }
+            };
+            if appendable {
+                self.push_back_with_edge(edge_ref, item);
+            } else {
+                self.insert(item);
+                edge = None;
+            }
         }
-        sk
+        #[cfg(debug_assertions)]
+        {
+            self.ensure_invariants()
+        }
+    }
+}
+
+impl<'a, T: PartialOrd + Clone + 'a> Extend<&'a T> for SkipList<T> {
+    /// Clone every item from `iter` into the skiplist. See `Extend<T>`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let mut sk: SkipList<i32> = SkipList::new();
+    /// sk.extend(&[3, 1, 2]);
+    ///
+    /// assert_eq!(vec![1, 2, 3], Vec::from(sk));
+    /// ```
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().cloned());
+    }
+}
+
+/// Is `items` sorted in ascending order? Duplicates are allowed.
+fn is_sorted<T: PartialOrd>(items: &[T]) -> bool {
+    items.windows(2).all(|pair| pair[0] <= pair[1])
+}
+
+impl<T: PartialOrd> From<Vec<T>> for SkipList<T> {
+    /// Build a skiplist from a `Vec<T>`.
+    ///
+    /// Runs in `O(n)` time if `vec` is sorted, and `O(nlogn)` otherwise.
+    fn from(vec: Vec<T>) -> Self {
+        if is_sorted(&vec) {
+            SkipList::from_sorted_iter(vec)
+        } else {
+            vec.into_iter().collect()
+        }
+    }
+}
+
+impl<T: PartialOrd + Clone> From<&[T]> for SkipList<T> {
+    /// Build a skiplist by cloning the items of a slice.
+    ///
+    /// Runs in `O(n)` time if `slice` is sorted, and `O(nlogn)` otherwise.
+    fn from(slice: &[T]) -> Self {
+        if is_sorted(slice) {
+            SkipList::from_sorted_iter(slice.iter().cloned())
+        } else {
+            slice.iter().cloned().collect()
+        }
+    }
+}
+
+impl<T: PartialOrd, const N: usize> From<[T; N]> for SkipList<T> {
+    fn from(array: [T; N]) -> Self {
+        SkipList::from(Vec::from(array))
+    }
+}
+
+impl<T: PartialOrd> From<Range<T>> for SkipList<T>
+where
+    Range<T>: Iterator<Item = T>,
+{
+    /// Ranges are always sorted, so this runs in `O(n)` time.
+    fn from(range: Range<T>) -> Self {
+        SkipList::from_sorted_iter(range)
     }
 }
 
-impl<T: PartialOrd + Clone, I: Iterator<Item = T>> From<I> for SkipList<T> {
-    fn from(iter: I) -> Self {
-        iter.collect()
+impl<T: PartialOrd> From<RangeInclusive<T>> for SkipList<T>
+where
+    RangeInclusive<T>: Iterator<Item = T>,
+{
+    /// Ranges are always sorted, so this runs in `O(n)` time.
+    fn from(range: RangeInclusive<T>) -> Self {
+        SkipList::from_sorted_iter(range)
     }
 }
 
-impl<T: PartialOrd + Clone> PartialEq for SkipList<T> {
+impl<T: PartialOrd> PartialEq for SkipList<T> {
     fn eq(&self, other: &Self) -> bool {
         self.len() == other.len() && self.iter_all().zip(other.iter_all()).all(|(l, r)| l == r)
     }
 }
 
+impl<T: PartialOrd + Clone> BitOr<&SkipList<T>> for &SkipList<T> {
+    type Output = SkipList<T>;
+
+    /// Returns the union of `self` and `rhs` as a new `SkipList<T>`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let a = SkipList::from(vec![1, 2, 3]);
+    /// let b = SkipList::from(vec![3, 4, 5]);
+    ///
+    /// assert_eq!(vec![1, 2, 3, 4, 5], Vec::from(&a | &b));
+    /// ```
+    fn bitor(self, rhs: &SkipList<T>) -> SkipList<T> {
+        SkipList::from_sorted_iter(self.union(rhs).cloned())
+    }
+}
+
+impl<T: PartialOrd + Clone> BitAnd<&SkipList<T>> for &SkipList<T> {
+    type Output = SkipList<T>;
+
+    /// Returns the intersection of `self` and `rhs` as a new `SkipList<T>`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let a = SkipList::from(vec![1, 2, 3]);
+    /// let b = SkipList::from(vec![2, 3, 4]);
+    ///
+    /// assert_eq!(vec![2, 3], Vec::from(&a & &b));
+    /// ```
+    fn bitand(self, rhs: &SkipList<T>) -> SkipList<T> {
+        SkipList::from_sorted_iter(self.intersection(rhs).cloned())
+    }
+}
+
+impl<T: PartialOrd + Clone> Sub<&SkipList<T>> for &SkipList<T> {
+    type Output = SkipList<T>;
+
+    /// Returns the difference of `self` and `rhs` as a new `SkipList<T>`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let a = SkipList::from(vec![1, 2, 3]);
+    /// let b = SkipList::from(vec![2, 3, 4]);
+    ///
+    /// assert_eq!(vec![1], Vec::from(&a - &b));
+    /// ```
+    fn sub(self, rhs: &SkipList<T>) -> SkipList<T> {
+        SkipList::from_sorted_iter(self.difference(rhs).cloned())
+    }
+}
+
+impl<T: PartialOrd + Clone> BitXor<&SkipList<T>> for &SkipList<T> {
+    type Output = SkipList<T>;
+
+    /// Returns the symmetric difference of `self` and `rhs` as a new `SkipList<T>`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let a = SkipList::from(vec![1, 2, 3]);
+    /// let b = SkipList::from(vec![2, 3, 4]);
+    ///
+    /// assert_eq!(vec![1, 4], Vec::from(&a ^ &b));
+    /// ```
+    fn bitxor(self, rhs: &SkipList<T>) -> SkipList<T> {
+        SkipList::from_sorted_iter(self.symmetric_difference(rhs).cloned())
+    }
+}
+
 macro_rules! fmt_node {
     ($f:expr, $node:expr) => {
         write!(
@@ -262,32 +699,117 @@ impl<T: fmt::Debug> fmt::Debug for SkipList<T> {
     }
 }
 
-impl<T: PartialOrd + Clone> Default for SkipList<T> {
+impl<T: PartialOrd> Default for SkipList<T> {
     #[inline]
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl<T: PartialOrd + Clone> Index<usize> for SkipList<T> {
+impl<T: PartialOrd> Index<usize> for SkipList<T> {
     type Output = T;
     fn index(&self, index: usize) -> &Self::Output {
         self.at_index(index).expect("index out of bounds!")
     }
 }
 
-/// Get the level of an item in the skiplist
-#[inline]
-fn get_level() -> usize {
-    let mut height = 1;
-    let mut rng = rand::thread_rng();
-    while rng.gen::<f32>() >= 0.5 {
-        height += 1;
+/// A pass of `SkipList::retain_nodes`, unlinking towers left to right.
+///
+/// The pass finishes when dropped, keeping any towers not yet visited, so
+/// the skiplist is consistent again even if the `keep` closure panicked.
+struct RetainPass<'a, T: PartialOrd> {
+    sk: &'a mut SkipList<T>,
+    // IDEA: Keep the last *kept* node on every level (bottom first),
+    // along with its index in the bottom row. The next node on a level
+    // is always `left[level].right`, and a tower is the run of those
+    // next nodes stacked on top of the next bottom node.
+    // Widths are recomputed from indices as we go.
+    left: Vec<*mut Node<T>>,
+    left_index: Vec<usize>,
+    kept: usize,
+    removed: usize,
+}
+
+impl<'a, T: PartialOrd> RetainPass<'a, T> {
+    fn new(sk: &'a mut SkipList<T>) -> Self {
+        let mut left: Vec<*mut Node<T>> = sk.iter_vertical().collect();
+        left.reverse();
+        let left_index = vec![0; left.len()];
+        Self {
+            sk,
+            left,
+            left_index,
+            kept: 0,
+            removed: 0,
+        }
+    }
+
+    /// The bottom node of the next tower to visit, if there is one.
+    #[inline]
+    fn next_tower(&self) -> Option<NonNull<Node<T>>> {
+        let bottom = unsafe { (*self.left[0]).right.unwrap() };
+        if unsafe { bottom.as_ref() }.value.is_pos_inf() {
+            None
+        } else {
+            Some(bottom)
+        }
+    }
+
+    /// Keep or unlink (and free) the next tower.
+    ///
+    /// # Safety
+    ///
+    /// There must be a next tower.
+    unsafe fn advance(&mut self, keep_tower: bool) {
+        if keep_tower {
+            self.kept += 1;
+        } else {
+            self.removed += 1;
+        }
+        let mut below: Option<NonNull<Node<T>>> = None;
+        for level in 0..self.left.len() {
+            let left = self.left[level];
+            let node = (*left).right.unwrap();
+            if let Some(below) = below {
+                if node.as_ref().down != Some(below) {
+                    break;
+                }
+            }
+            if keep_tower {
+                (*left).set_width(self.kept - self.left_index[level]);
+                self.left[level] = node.as_ptr();
+                self.left_index[level] = self.kept;
+            } else {
+                (*left).right = node.as_ref().right;
+            }
+            below = Some(node);
+        }
+        if !keep_tower {
+            // Free the tower from the top down.
+            while let Some(node) = below {
+                below = node.as_ref().down;
+                self.sk.pool.free(node);
+            }
+        }
+    }
+}
+
+impl<'a, T: PartialOrd> Drop for RetainPass<'a, T> {
+    fn drop(&mut self) {
+        unsafe {
+            while self.next_tower().is_some() {
+                self.advance(true);
+            }
+            for (&node, &index) in self.left.iter().zip(&self.left_index) {
+                (*node).set_width(self.kept + 1 - index);
+            }
+        }
+        self.sk.len = self.kept;
+        self.sk.shrink_levels();
     }
-    height
 }
 
-impl<T: PartialOrd + Clone> SkipList<T> {
+impl<T: PartialOrd> SkipList<T> {
     /// Make a new, empty SkipList. By default there is three levels.
     ///
     /// # Example
@@ -301,51 +823,224 @@ impl<T: PartialOrd + Clone> SkipList<T> {
     /// ```
     #[inline]
     pub fn new() -> SkipList<T> {
-        let mut sk = SkipList {
-            top_left: SkipList::pos_neg_pair(1),
-            height: 1,
-            len: 0,
-            _prevent_sync_send: std::marker::PhantomData,
-        };
-        sk.add_levels(2);
-        sk
+        SkipList::with_level_generator(LevelGenerator::default())
     }
 
-    /// add `additional_levels` to the _top_ of the SkipList
-    #[inline]
-    fn add_levels(&mut self, additional_levels: usize) {
-        let mut curr_level = self.top_left;
-        for _ in 0..additional_levels {
-            let mut new_level = SkipList::pos_neg_pair(self.len() + 1);
-            // We're going to insert this `new_level` between curr_level and the row below it.
-            // So it will look like:
-            // | top_left -> top_right
-            // | *new row here*
-            // | *existing row*
-            unsafe {
-                new_level.as_mut().down = curr_level.as_ref().down;
-                curr_level.as_mut().down = Some(new_level);
-                curr_level = new_level;
-            }
-        }
-        self.height += additional_levels as usize;
-    }
-    /// Insert `item` into the `SkipList`.
-    ///
-    /// Returns `true` if the item was actually inserted (i.e. wasn't already in the skiplist)
-    /// and `false` otherwise.
-    ///
-    /// Runs in `O(logn)` time.
-    ///
-    /// # Arguments
-    ///
-    /// * `item` - the item to insert.
+    /// Configure the promotion probability, maximum tower height, or RNG seed
+    /// of a new skiplist.
     ///
     /// # Example
     ///
     /// ```rust
     /// use convenient_skiplist::SkipList;
-    /// let mut sk = SkipList::new();
+    /// let mut sk = SkipList::builder().probability(0.25).max_height(24).seed(42).build();
+    /// sk.insert(0usize);
+    ///
+    /// assert!(sk.contains(&0));
+    /// ```
+    #[inline]
+    pub fn builder() -> SkipListBuilder<T> {
+        SkipListBuilder::new()
+    }
+
+    /// Make a new, empty SkipList whose tower heights are seeded by `seed`.
+    ///
+    /// The same sequence of operations on skiplists with the same seed builds
+    /// identical structures, which makes tests and debugging reproducible.
+    /// Clones restart from the same seed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let mut left = SkipList::with_seed(42);
+    /// let mut right = SkipList::with_seed(42);
+    /// for i in (0..100u32).rev() {
+    ///     left.insert(i);
+    ///     right.insert(i);
+    /// }
+    ///
+    /// assert!(left.iter_levels().zip(right.iter_levels()).all(|(l, r)| l.eq(r)));
+    /// ```
+    #[inline]
+    pub fn with_seed(seed: u64) -> SkipList<T> {
+        SkipList::builder().seed(seed).build()
+    }
+
+    /// Make a new, empty SkipList that picks tower heights with `rng`.
+    ///
+    /// Unlike `SkipList::new`, this never touches `thread_rng`, and `rng` makes
+    /// the shape of the skiplist controllable. See `SkipListBuilder::rng`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// use rand::rngs::StdRng;
+    /// use rand::SeedableRng;
+    /// let mut sk = SkipList::with_rng(StdRng::seed_from_u64(0));
+    /// sk.insert(0usize);
+    ///
+    /// assert!(sk.contains(&0));
+    /// ```
+    #[inline]
+    pub fn with_rng<R: RngCore + Clone + Send + Sync + 'static>(rng: R) -> SkipList<T> {
+        SkipList::builder().rng(rng).build()
+    }
+
+    pub(crate) fn with_level_generator(levels: LevelGenerator) -> SkipList<T> {
+        SkipList::with_pool(levels, NodePool::default())
+    }
+
+    pub(crate) fn with_pool(levels: LevelGenerator, mut pool: NodePool<T>) -> SkipList<T> {
+        let mut sk = SkipList {
+            top_left: pool.make_sentinels(1),
+            height: 1,
+            len: 0,
+            levels,
+            level_hash: None,
+            pool,
+        };
+        sk.add_levels(2);
+        sk
+    }
+
+    /// Make a new, empty SkipList with node storage pre-allocated for
+    /// `capacity` elements, so the first `capacity` inserts don't each
+    /// allocate their towers individually.
+    ///
+    /// Storage is sized for the expected tower heights, so an unlucky
+    /// skiplist may still allocate a few nodes on its own.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let mut sk = SkipList::with_capacity(100);
+    /// for i in 0..100usize {
+    ///     sk.insert(i);
+    /// }
+    ///
+    /// assert_eq!(sk.len(), 100);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> SkipList<T> {
+        let mut sk = SkipList::new();
+        sk.reserve_nodes(capacity);
+        sk
+    }
+
+    /// Pre-allocate node storage for `additional` more elements.
+    #[inline]
+    fn reserve_nodes(&mut self, additional: usize) {
+        let nodes = self.levels.expected_nodes(additional);
+        self.pool.reserve(additional, nodes);
+    }
+
+    /// Build a skiplist from items that are already sorted in ascending order.
+    ///
+    /// The skiplist is built bottom-up: each item is appended to the end of the bottom
+    /// row and its tower erected directly, so no searching is done at all.
+    ///
+    /// Runs in `O(n)` expected time.
+    ///
+    /// Consecutive duplicate items are skipped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an item is smaller than the item before it.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from_sorted_iter(0..1000);
+    ///
+    /// assert_eq!(sk.len(), 1000);
+    /// assert_eq!(sk.index_of(&500), Some(500));
+    /// ```
+    pub fn from_sorted_iter<I: IntoIterator<Item = T>>(iter: I) -> SkipList<T> {
+        let iter = iter.into_iter();
+        let mut sk = SkipList::with_capacity(iter.size_hint().0);
+        let mut edge = sk.right_edge();
+        for item in iter {
+            if let Some(last) = unsafe { (*edge[0].curr_node).value.value() } {
+                if last == &item {
+                    continue;
+                }
+                assert!(
+                    last < &item,
+                    "from_sorted_iter: items must be in ascending order"
+                );
+            }
+            sk.push_back_with_edge(&mut edge, item);
+        }
+        #[cfg(debug_assertions)]
+        {
+            sk.ensure_invariants()
+        }
+        sk
+    }
+
+    /// add `additional_levels` to the _top_ of the SkipList
+    #[inline]
+    fn add_levels(&mut self, additional_levels: usize) {
+        let mut curr_level = self.top_left;
+        for _ in 0..additional_levels {
+            let mut new_level = self.pool.make_sentinels(self.len() + 1);
+            // We're going to insert this `new_level` between curr_level and the row below it.
+            // So it will look like:
+            // | top_left -> top_right
+            // | *new row here*
+            // | *existing row*
+            unsafe {
+                new_level.as_mut().down = curr_level.as_ref().down;
+                curr_level.as_mut().down = Some(new_level);
+                curr_level = new_level;
+            }
+        }
+        self.height += additional_levels;
+        // Besides the levels we start with, the wall is only ever one taller
+        // than the tallest tower, which `levels` caps.
+        debug_assert!(self.height <= self.levels.max_height().max(2) + 1);
+    }
+
+    /// Drop empty rows below the top one, down to the three a skiplist starts with.
+    ///
+    /// Removing the tallest towers leaves rows holding only their sentinels,
+    /// which every search would otherwise keep walking down through.
+    pub(crate) fn shrink_levels(&mut self) {
+        let top_left = self.top_left.as_ptr();
+        unsafe {
+            while self.height > 3 {
+                // INVARIANT: There's more rows below the top one.
+                let below = (*top_left).down.unwrap();
+                let right = below.as_ref().right.unwrap();
+                if !right.as_ref().value.is_pos_inf() {
+                    break;
+                }
+                (*top_left).down = below.as_ref().down;
+                self.pool.free(right);
+                self.pool.free(below);
+                self.height -= 1;
+            }
+        }
+    }
+    /// Insert `item` into the `SkipList`.
+    ///
+    /// Returns `true` if the item was actually inserted (i.e. wasn't already in the skiplist)
+    /// and `false` otherwise.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Arguments
+    ///
+    /// * `item` - the item to insert.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let mut sk = SkipList::new();
     /// sk.insert(0usize);
     ///
     /// assert!(sk.contains(&0));
@@ -360,7 +1055,71 @@ impl<T: PartialOrd + Clone> SkipList<T> {
         if self.contains(&item) {
             return false;
         }
-        let height = get_level();
+        let height = self.tower_height(&item);
+        let tower = self.pool.make_tower(item, height);
+        unsafe {
+            let item = &*(tower.as_ref().value.get_value() as *const T);
+            self.link_tower(item, height, true, tower_levels(tower));
+        }
+        self.retune_if_drifted();
+        true
+    }
+
+    /// Rebuild the towers if auto-tuning found searches running long.
+    #[inline]
+    fn retune_if_drifted(&mut self) {
+        if self.levels.take_drifted() {
+            self.rebuild_towers();
+        }
+    }
+
+    /// Rebuild every tower with fresh heights, in `O(n)` time.
+    fn rebuild_towers(&mut self) {
+        let old = self.take_all();
+        self.reserve_nodes(old.len());
+        self.extend(old);
+    }
+
+    /// Empty the skiplist, keeping its configuration, and return what it held.
+    fn take_all(&mut self) -> SkipList<T> {
+        // The generator moves over, so its RNG carries on where it was.
+        let levels = std::mem::replace(&mut self.levels, LevelGenerator::new(0.5, 1, Some(0)));
+        let mut empty = SkipList::with_pool(levels, self.pool.fresh());
+        empty.level_hash = self.level_hash;
+        std::mem::replace(self, empty)
+    }
+
+    /// Insert every item from `iter`, returning how many were actually
+    /// added (i.e. weren't already in the skiplist).
+    ///
+    /// This uses `extend`, so sorted input is appended quickly.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let mut sk = SkipList::from(0..5);
+    ///
+    /// assert_eq!(sk.insert_many(vec![3, 4, 5, 6]), 2);
+    /// assert_eq!(sk.len(), 7);
+    /// ```
+    pub fn insert_many<I: IntoIterator<Item = T>>(&mut self, iter: I) -> usize {
+        let len_before = self.len();
+        self.extend(iter);
+        self.len() - len_before
+    }
+
+    /// Stitch a tower of `height` nodes for `item` into the skiplist.
+    ///
+    /// `next_node` is called once per level, bottom first, with the width
+    /// the new node needs, and must return the node to link in.
+    ///
+    /// `item` must not already be in the skiplist. With `observe`, the search
+    /// for its spot counts toward auto-tuning.
+    fn link_tower<F>(&mut self, item: &T, height: usize, observe: bool, next_node: F)
+    where
+        F: FnMut(&mut NodePool<T>, usize) -> NonNull<Node<T>>,
+    {
         let additional_height_req: i32 = (height as i32 - self.height as i32) + 1;
         if additional_height_req > 0 {
             self.add_levels(additional_height_req as usize);
@@ -371,16 +1130,31 @@ impl<T: PartialOrd + Clone> SkipList<T> {
             self.ensure_invariants()
         }
 
-        // Now the skiplist has enough height to actually insert this element.
+        let mut path = self.search_path(item, observe);
+        self.stitch_tower(&mut path, height, next_node);
+    }
+
+    /// Stitch a tower of `height` nodes in right after the nodes of `path`.
+    ///
+    /// `path` holds the nodes immediately *left* of where the tower goes
+    /// on every level, top to bottom, along with their index. Afterwards it
+    /// holds the new tower's nodes instead, wherever the tower reaches.
+    ///
+    /// The skiplist must already be taller than `height`.
+    fn stitch_tower<F>(&mut self, path: &mut [NodeWidth<T>], height: usize, mut next_node: F)
+    where
+        F: FnMut(&mut NodePool<T>, usize) -> NonNull<Node<T>>,
+    {
+        // The skiplist has enough height to actually insert this element.
         // We'll need to reverse iterate to stitch the required items between.
-        // As self.path_to returns all nodes immediately *left* of where we've inserted,
+        // As `path` holds all nodes immediately *left* of where we're inserting,
         // we just need to insert the nodes after.
         let mut node_below_me = None;
         let mut added = 0;
         let mut total_width = None;
-        for node in self.insert_path(&item).into_iter().rev() {
+        for node in path.iter_mut().rev() {
             unsafe {
-                (*node.curr_node).width += 1;
+                (*node.curr_node).set_width((*node.curr_node).width() + 1);
             }
             // Set total_width from the bottom node.
             if total_width.is_none() {
@@ -427,19 +1201,20 @@ impl<T: PartialOrd + Clone> SkipList<T> {
                     // truncation above to the new element.
 
                     let left_node_width = total_width - node.curr_width + 1;
-                    let new_node_width = (*node.curr_node).width - left_node_width;
+                    let new_node_width = (*node.curr_node).width() - left_node_width;
 
-                    (*node.curr_node).width = left_node_width;
+                    (*node.curr_node).set_width(left_node_width);
 
                     debug_assert!(total_width + 1 == node.curr_width + left_node_width);
 
-                    let mut new_node = SkipList::make_node(item.clone(), new_node_width);
+                    let new_node = next_node(&mut self.pool, new_node_width);
 
-                    let node: *mut Node<T> = node.curr_node;
-                    new_node.as_mut().down = node_below_me;
-                    new_node.as_mut().right = (*node).right;
-                    (*node).right = Some(new_node);
+                    let left: *mut Node<T> = node.curr_node;
+                    (*new_node.as_ptr()).down = node_below_me;
+                    (*new_node.as_ptr()).right = (*left).right;
+                    (*left).right = Some(new_node);
                     node_below_me = Some(new_node);
+                    *node = NodeWidth::new(new_node.as_ptr(), total_width + 1);
                 }
                 added += 1;
             }
@@ -449,354 +1224,448 @@ impl<T: PartialOrd + Clone> SkipList<T> {
         {
             self.ensure_invariants()
         }
-        true
     }
-    /// Test if `item` is in the skiplist. Returns `true` if it's in the skiplist,
-    /// `false` otherwise.
-    ///
-    /// Runs in `O(logn)` time
+
+    /// Iterator over the edits needed to turn `self` into `other`, in ascending order.
     ///
-    /// # Arguments
+    /// Elements only in `other` are yielded as `Edit::Added`, and elements
+    /// only in `self` as `Edit::Removed`. Shared elements are skipped.
     ///
-    /// * `item` - the item we're testing.
+    /// This runs in `O(n + m)` time, and is lazy.
     ///
     /// # Example
     ///
     /// ```rust
-    /// use convenient_skiplist::SkipList;
-    /// let mut sk = SkipList::new();
-    /// sk.insert(0usize);
+    /// use convenient_skiplist::{Edit, SkipList};
+    /// let old = SkipList::from(vec![1, 2, 3]);
+    /// let new = SkipList::from(vec![2, 3, 4]);
     ///
-    /// assert!(sk.contains(&0));
+    /// let edits: Vec<_> = old.diff(&new).collect();
+    /// assert_eq!(edits, vec![Edit::Removed(&1), Edit::Added(&4)]);
     /// ```
     #[inline]
-    pub fn contains(&self, item: &T) -> bool {
-        self.iter_left(item).any(|node| unsafe {
-            if let Some(right) = &(*node).right {
-                &right.as_ref().value == item
-            } else {
-                false
-            }
-        })
+    pub fn diff<'a>(&'a self, other: &'a SkipList<T>) -> Diff<'a, T> {
+        Diff::new(self.iter_all(), other.iter_all())
     }
 
-    /// Remove `item` from the SkipList.
-    ///
-    /// Returns `true` if the item was in the collection to be removed,
-    /// and `false` otherwise.
-    ///
-    /// Runs in `O(logn)` time.
+    /// Insert every item from a sorted batch into the skiplist.
     ///
-    /// # Arguments
+    /// Unlike `extend`, the batch may interleave with the existing elements.
+    /// The search for each item resumes from where the previous item went, so
+    /// a run of items landing between the same two elements costs one descent
+    /// for the whole run rather than a fresh top-down search per item.
     ///
-    /// * `item` - the item to remove.
+    /// Items that are out of order are still inserted correctly, they just
+    /// restart their search from the top.
     ///
     /// # Example
     ///
     /// ```rust
     /// use convenient_skiplist::SkipList;
-    /// let mut sk = SkipList::new();
-    /// sk.insert(0usize);
+    /// let mut sk = SkipList::from(vec![0, 10, 20]);
+    /// sk.extend_from_sorted(vec![1, 2, 3, 10, 15, 25]);
     ///
-    /// let removed = sk.remove(&0);
-    /// assert!(removed);
+    /// assert_eq!(vec![0, 1, 2, 3, 10, 15, 20, 25], Vec::from(sk));
     /// ```
-    pub fn remove(&mut self, item: &T) -> bool {
-        if !self.contains(item) {
-            return false;
-        }
-        for node in self.iter_left(item) {
-            unsafe {
-                (*node).width -= 1;
-                // Invariant: `node` can never be PosInf
-                let right = (*node).right.unwrap();
-                if &right.as_ref().value != item {
+    pub fn extend_from_sorted<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let mut path = SearchPath::new(self.top_left.as_ptr());
+        for item in iter {
+            let last = path.nodes_mut().last().unwrap().curr_node;
+            if let Some(last) = unsafe { (*last).value.value() } {
+                if last == &item {
                     continue;
                 }
-                // So the node right of us needs to be removed.
-                (*node).width += right.as_ref().width;
-                let garbage = std::mem::replace(&mut (*node).right, right.as_ref().right);
-                drop(Box::from_raw(garbage.unwrap().as_ptr()));
+                if &item < last {
+                    path = SearchPath::new(self.top_left.as_ptr());
+                }
+            }
+            let left = path.advance_to(&item);
+            if unsafe { &(*left).right.unwrap().as_ref().value } == &item {
+                continue;
+            }
+            let height = self.tower_height(&item);
+            if height + 1 > self.height {
+                let additional_levels = height + 1 - self.height;
+                self.add_levels(additional_levels);
+                path.add_levels(self.top_left.as_ptr(), additional_levels);
             }
+            let tower = self.pool.make_tower(item, height);
+            self.stitch_tower(path.nodes_mut(), height, tower_levels(tower));
         }
-        self.len -= 1;
-        true
+        self.retune_if_drifted();
     }
 
-    /// Remove and return the item at `index`.
+    /// Move every element of `other` into `self`, leaving `self` as the union
+    /// of both skiplists.
     ///
-    /// Runs in O(log n) time.
+    /// The nodes of `other` are unlinked and spliced into `self` directly,
+    /// so nothing is cloned. Elements already in `self` are dropped.
+    ///
+    /// Runs in `O(m log(n + m))` time, where `m` is the length of `other`.
     ///
     /// # Example
     ///
     /// ```rust
     /// use convenient_skiplist::SkipList;
-    /// let mut sk = SkipList::from(0..5);
+    /// let mut sk = SkipList::from(vec![1, 3, 5]);
+    /// sk.union_in_place(SkipList::from(vec![2, 3, 4]));
     ///
-    /// assert_eq!(sk.len(), 5);
-    /// assert_eq!(sk.remove_at(1), Some(1));
-    /// assert_eq!(sk.len(), 4);
+    /// assert_eq!(vec![1, 2, 3, 4, 5], Vec::from(sk));
     /// ```
-    pub fn remove_at(&mut self, index: usize) -> Option<T> {
-        let item = self.at_index(index).cloned();
-        if let Some(item) = &item {
-            self.remove(item);
+    pub fn union_in_place(&mut self, mut other: SkipList<T>) {
+        if !self.pool.can_adopt(&other.pool) {
+            self.extend(other);
+            return;
         }
-        item
+        // We take over `other`'s nodes, so we need to own their storage too.
+        self.pool.append(&mut other.pool);
+        for mut tower in DrainTowers::new(other) {
+            unsafe {
+                let item = &*(tower[0].as_ref().value.get_value() as *const T);
+                if self.contains(item) {
+                    for node in tower {
+                        self.pool.free(node);
+                    }
+                    continue;
+                }
+                // Pick the height as if `item` were inserted, so our own cap
+                // and hashed heights apply, then trim or grow the tower to fit.
+                let height = self.tower_height(item);
+                for node in tower.drain(height.min(tower.len())..).rev() {
+                    self.pool.free(node);
+                }
+                while tower.len() < height {
+                    let top = *tower.last().unwrap();
+                    tower.push(self.pool.make_node_above(top));
+                }
+                let mut levels = tower.into_iter();
+                self.link_tower(item, height, false, |_, width| {
+                    let node = levels.next().unwrap();
+                    (*node.as_ptr()).set_width(width);
+                    node
+                });
+            }
+        }
+        self.retune_if_drifted();
     }
 
-    /// Return the number of elements in the skiplist.
+    /// Retain only the elements of `self` that are also in `other`.
+    ///
+    /// Both bottom rows are walked side by side, and nodes not in `other`
+    /// are unlinked from `self` during that single pass.
+    ///
+    /// Runs in `O(n + m)` time.
     ///
     /// # Example
+    ///
     /// ```rust
     /// use convenient_skiplist::SkipList;
-    /// let mut sk = SkipList::new();
-    ///
-    /// sk.insert(0);
-    /// assert_eq!(sk.len(), 1);
+    /// let mut sk = SkipList::from(0..10);
+    /// sk.intersect_in_place(&SkipList::from(vec![1, 4, 9, 12]));
     ///
-    /// sk.insert(1);
-    /// assert_eq!(sk.len(), 2);
+    /// assert_eq!(vec![1, 4, 9], Vec::from(sk));
     /// ```
-
-    #[inline]
-    pub fn len(&self) -> usize {
-        self.len
-    }
-
-    /// Returns true if the skiplist is empty
-    #[inline]
-    pub fn is_empty(&self) -> bool {
-        self.len == 0
+    pub fn intersect_in_place(&mut self, other: &SkipList<T>) {
+        let mut other_iter = other.iter_all().peekable();
+        self.retain_nodes(|item| {
+            let item = &*item;
+            while other_iter.next_if(|&o| o < item).is_some() {}
+            matches!(other_iter.peek(), Some(&o) if o == item)
+        });
     }
 
-    // TODO
-    // fn remove_range<'a>(&'a mut self, _start: &'a T, _end: &'a T) -> usize {
-    //     // Idea: Use iter_left twice to determine the chunk in the middle to remove.
-    //     // Hardest part will be cleaning up garbage. :thinking:
-    //     todo!()
-    // }
-
-    /// Find the index of `item` in the `SkipList`.
-    ///
-    /// Runs in `O(logn)` time.
+    /// Remove every element of `other` from `self`.
     ///
-    /// # Arguments
+    /// Rather than `m` independent calls to `remove`, both bottom rows are
+    /// walked side by side and matching nodes are unlinked in a single pass.
     ///
-    /// * `item`: the item to find the position of.
+    /// Runs in `O(n + m)` time.
     ///
     /// # Example
+    ///
     /// ```rust
     /// use convenient_skiplist::SkipList;
-    /// let mut sk = SkipList::new();
-    /// sk.insert(1);
-    /// sk.insert(2);
-    /// sk.insert(3);
+    /// let mut sk = SkipList::from(0..10);
+    /// sk.difference_in_place(&SkipList::from(vec![1, 4, 9, 12]));
     ///
-    /// assert_eq!(sk.index_of(&1), Some(0));
-    /// assert_eq!(sk.index_of(&2), Some(1));
-    /// assert_eq!(sk.index_of(&3), Some(2));
-    /// assert_eq!(sk.index_of(&999), None);
+    /// assert_eq!(vec![0, 2, 3, 5, 6, 7, 8], Vec::from(sk));
     /// ```
-    #[inline]
-    pub fn index_of(&self, item: &T) -> Option<usize> {
-        // INVARIANT: path_to is a LeftBiasIterWidth, so there's always a
-        // node right of us.
-        self.path_to(item).last().and_then(|node| {
-            if unsafe { &(*node.curr_node).right.unwrap().as_ref().value } == item {
-                Some(node.curr_width)
-            } else {
-                None
+    pub fn difference_in_place(&mut self, other: &SkipList<T>) {
+        let mut other_iter = other.iter_all().peekable();
+        self.retain_nodes(|item| {
+            let item = &*item;
+            while other_iter.next_if(|&o| o < item).is_some() {}
+            !matches!(other_iter.peek(), Some(&o) if o == item)
+        });
+    }
+
+    /// Remove the elements at indices `start..end` by unlinking the whole run
+    /// once per level, rather than searching for each element separately.
+    ///
+    /// Runs in `O(logn + k)` time, where k is the number of nodes removed.
+    /// Returns the number of elements removed.
+    fn splice_out(&mut self, start: usize, end: usize) -> usize {
+        let end = end.min(self.len);
+        if start >= end {
+            return 0;
+        }
+        let removed = end - start;
+        // Node indices count NegInf as 0, so the run is `start + 1..=end`.
+        let mut curr_node = self.top_left.as_ptr();
+        let mut curr_index = 0;
+        unsafe {
+            loop {
+                while curr_index + (*curr_node).width() <= start {
+                    curr_index += (*curr_node).width();
+                    curr_node = (*curr_node).right.unwrap().as_ptr();
+                }
+                // `curr_node` is left of the run on this level. Free everything
+                // up to the first node past it, which is at worst PosInf.
+                let mut right = (*curr_node).right.unwrap();
+                let mut right_index = curr_index + (*curr_node).width();
+                while right_index <= end {
+                    let garbage = right;
+                    right_index += right.as_ref().width();
+                    right = right.as_ref().right.unwrap();
+                    self.pool.free(garbage);
+                }
+                (*curr_node).right = Some(right);
+                (*curr_node).set_width(right_index - curr_index - removed);
+                match (*curr_node).down {
+                    Some(down) => curr_node = down.as_ptr(),
+                    None => break,
+                }
             }
-        })
+        }
+        self.len -= removed;
+        self.shrink_levels();
+        #[cfg(debug_assertions)]
+        {
+            self.ensure_invariants()
+        }
+        removed
     }
 
-    /// Get the item at the index `index `in the `SkipList`.
+    /// Remove every element for which `keep` returns `false`, in a single
+    /// left-to-right pass over every level at once.
     ///
-    /// Runs in `O(logn)` time.
+    /// `keep` is called exactly once per element, in ascending order.
+    /// It may change each element, but only in ways that keep its order, like
+    /// a `SkipMap`'s values.
+    /// If `keep` panics, that element and every one after it are kept, and
+    /// the skiplist is left consistent.
+    /// Returns the number of elements removed.
+    fn retain_nodes<F>(&mut self, mut keep: F) -> usize
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        let mut pass = RetainPass::new(self);
+        unsafe {
+            while let Some(bottom) = pass.next_tower() {
+                let keep_tower = keep((*bottom.as_ptr()).value.get_value_mut());
+                pass.advance(keep_tower);
+            }
+        }
+        let removed = pass.removed;
+        // Dropping the pass fixes up the widths and length.
+        drop(pass);
+        #[cfg(debug_assertions)]
+        {
+            self.ensure_invariants()
+        }
+        removed
+    }
+
+    /// Test if `item` is in the skiplist. Returns `true` if it's in the skiplist,
+    /// `false` otherwise.
+    ///
+    /// Runs in `O(logn)` time
     ///
     /// # Arguments
     ///
-    /// * `index`: the index to get the item at
+    /// * `item` - the item we're testing.
     ///
     /// # Example
+    ///
     /// ```rust
     /// use convenient_skiplist::SkipList;
-    /// let sk = SkipList::from(0..10);
-    /// for i in 0..10 {
-    ///     assert_eq!(Some(&i), sk.at_index(i));
-    /// }
-    /// assert_eq!(None, sk.at_index(11));
-    ///
     /// let mut sk = SkipList::new();
-    /// sk.insert('a');
-    /// sk.insert('b');
-    /// sk.insert('c');
-    /// assert_eq!(Some(&'a'), sk.at_index(0));
-    /// assert_eq!(Some(&'b'), sk.at_index(1));
-    /// assert_eq!(Some(&'c'), sk.at_index(2));
-    /// assert_eq!(None, sk.at_index(3));
+    /// sk.insert(0usize);
+    ///
+    /// assert!(sk.contains(&0));
     /// ```
     #[inline]
-    pub fn at_index(&self, index: usize) -> Option<&T> {
-        if index >= self.len() {
-            return None;
-        }
-        unsafe {
-            let mut curr_node = self.top_left.as_ref();
-            let mut distance_left = index + 1;
-            loop {
-                if distance_left == 0 {
-                    return Some(curr_node.value.get_value());
-                }
-                if curr_node.width <= distance_left {
-                    distance_left -= curr_node.width;
-                    // INVARIANT: We've checked if `index` < self.len(),
-                    // so there's always a `right`
-                    curr_node = curr_node.right.unwrap().as_ptr().as_ref().unwrap();
-                    continue;
-                } else if let Some(down) = curr_node.down {
-                    curr_node = down.as_ptr().as_ref().unwrap();
-                } else {
-                    unreachable!()
-                }
+    pub fn contains<Q>(&self, item: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.iter_left(item).any(|node| unsafe {
+            if let Some(right) = &(*node).right {
+                right.as_ref().value.eq_key(item)
+            } else {
+                false
             }
-        }
+        })
     }
 
-    /// Peek at the first item in the skiplist.
+    /// Test if every item in `iter` is in the skiplist. Returns `true` if `iter` is empty.
     ///
-    /// Runs in constant time.
+    /// If the items are sorted, each search resumes from where the previous
+    /// one ended, so this takes `O(min(n + m, m logn))` time rather than `m`
+    /// top-down searches. Items that are out of order restart their search from the top.
     ///
     /// # Example
     ///
     /// ```rust
     /// use convenient_skiplist::SkipList;
-    /// let mut sk = SkipList::from(0..10);
+    /// let sk = SkipList::from(0..10);
     ///
-    /// assert_eq!(Some(&0), sk.peek_first());
+    /// assert!(sk.contains_all(&[1, 3, 5]));
+    /// assert!(!sk.contains_all(vec![1, 3, 50]));
     /// ```
-    #[inline]
-    pub fn peek_first(&self) -> Option<&T> {
-        self.at_index(0)
+    pub fn contains_all<I>(&self, iter: I) -> bool
+    where
+        I: IntoIterator,
+        I::Item: Borrow<T>,
+    {
+        self.contains_each(iter).all(|found| found)
     }
 
-    /// Peek at the last item in the skiplist.
+    /// Test if any item in `iter` is in the skiplist. Returns `false` if `iter` is empty.
     ///
-    /// Runs in O(log n) time.
+    /// Like `contains_all`, sorted items share a single left-to-right pass over the skiplist.
     ///
     /// # Example
     ///
     /// ```rust
     /// use convenient_skiplist::SkipList;
-    /// let mut sk = SkipList::from(0..10);
+    /// let sk = SkipList::from(0..10);
     ///
-    /// assert_eq!(Some(&9), sk.peek_last());
+    /// assert!(sk.contains_any(&[20, 5, 30]));
+    /// assert!(!sk.contains_any(vec![-1, 10]));
     /// ```
-    #[inline]
-    pub fn peek_last(&self) -> Option<&T> {
-        if self.is_empty() {
-            None
-        } else {
-            self.at_index(self.len() - 1)
-        }
+    pub fn contains_any<I>(&self, iter: I) -> bool
+    where
+        I: IntoIterator,
+        I::Item: Borrow<T>,
+    {
+        self.contains_each(iter).any(|found| found)
     }
 
-    /// Pop `count` elements off of the end of the Skiplist.
+    /// Lazily test whether each item in `iter` is in the skiplist,
+    /// resuming each search from the last one where possible.
+    fn contains_each<'a, I>(&'a self, iter: I) -> impl Iterator<Item = bool> + 'a
+    where
+        I: IntoIterator,
+        I::IntoIter: 'a,
+        I::Item: Borrow<T>,
+    {
+        let mut path = SearchPath::new(self.top_left.as_ptr());
+        iter.into_iter().map(move |item| {
+            let item = item.borrow();
+            let last = path.nodes_mut().last().unwrap().curr_node;
+            if let Some(last) = unsafe { (*last).value.value() } {
+                if item <= last {
+                    path = SearchPath::new(self.top_left.as_ptr());
+                }
+            }
+            let left = path.advance_to(item);
+            unsafe { &(*left).right.unwrap().as_ref().value == item }
+        })
+    }
+
+    /// Remove `item` from the SkipList.
     ///
-    /// Runs in O(logn * count) time, O(logn + count) space.
+    /// Returns `true` if the item was in the collection to be removed,
+    /// and `false` otherwise.
     ///
-    /// Memory pressure: This is implemented such that the entire
-    /// region of the skiplist is cleaved off at once. So you'll
-    /// see in the worse case (i.e. all towers have maximum height ~ logn)
-    /// count * logn memory deallocations.
+    /// Runs in `O(logn)` time.
     ///
-    /// Returns an empty `vec` if count == 0.
+    /// # Arguments
     ///
-    /// Will dealloc the whole skiplist if count >= len and start fresh.
+    /// * `item` - the item to remove.
     ///
     /// # Example
     ///
     /// ```rust
     /// use convenient_skiplist::SkipList;
-    /// let mut sk = SkipList::from(0..10);
-    ///
-    /// assert_eq!(Some(&7), sk.at_index(7));
-    /// assert_eq!(vec![7, 8, 9], sk.pop_max(3));
-    /// assert_eq!(vec![6], sk.pop_max(1));
-    /// assert_eq!(vec![4, 5], sk.pop_max(2));
-    /// assert_eq!(vec![0, 1, 2, 3], sk.pop_max(5));
+    /// let mut sk = SkipList::new();
+    /// sk.insert(0usize);
     ///
-    /// let v: Vec<u32> = Vec::new();
-    /// assert_eq!(v, sk.pop_max(1000)); // empty
+    /// let removed = sk.remove(&0);
+    /// assert!(removed);
     /// ```
     #[inline]
-    pub fn pop_max(&mut self, count: usize) -> Vec<T> {
-        if self.is_empty() || count == 0 {
-            return vec![];
-        }
-        if count >= self.len() {
-            // let new = SkipList::new();
-            // let garbage = std::mem::replace(&mut self, &mut new);
-            // drop(garbage);
-            let ret = self.iter_all().cloned().collect();
-            *self = SkipList::new(); // TODO: Does this drop me?
-            return ret;
-        }
-        let ele_at = self.at_index(self.len() - count).unwrap().clone();
-        self.len -= count;
-        // IDEA: Calculate widths by adding _backwards_ through the
-        // insert path.
-        let mut frontier = self.insert_path(&ele_at);
-        let last_value = frontier.last_mut().cloned().unwrap();
-        let mut last_width = last_value.curr_width;
-        let mut ret: Vec<_> = Vec::with_capacity(count);
-        let mut jumped_left = 1;
-        unsafe {
-            ret.extend(NodeRightIter::new(
-                (*last_value.curr_node).right.unwrap().as_ptr(),
-            ));
-            (*last_value.curr_node).clear_right();
-        }
-        for mut nw in frontier.into_iter().rev().skip(1) {
-            unsafe {
-                // We've jumped right, and now need to update our width field.
-                // Do we need this if-gate?
-                if (*nw.curr_node).value != (*last_value.curr_node).value {
-                    jumped_left += last_width - nw.curr_width;
-                    last_width = nw.curr_width;
-                }
-                (*nw.curr_node).clear_right();
-                (*nw.curr_node).width = jumped_left;
-            }
-        }
-        ret
+    pub fn remove<Q>(&mut self, item: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.take(item).is_some()
     }
 
-    /// Pop the last element off of the skiplist.
+    /// Remove the element equal to `item` from the skiplist and return it,
+    /// if it was there.
     ///
-    /// Runs in O(logn) time, O(1) space.
+    /// Runs in `O(logn)` time.
     ///
     /// # Example
     ///
     /// ```rust
     /// use convenient_skiplist::SkipList;
-    /// let mut sk = SkipList::from(0..10);
+    /// let mut sk = SkipList::from(vec!["a".to_string(), "b".to_string()]);
     ///
-    /// assert_eq!(Some(9), sk.pop_back());
+    /// assert_eq!(sk.take("a"), Some("a".to_string()));
+    /// assert_eq!(sk.take("a"), None);
+    /// assert_eq!(sk.len(), 1);
     /// ```
-    #[inline]
-    pub fn pop_back(&mut self) -> Option<T> {
-        if self.is_empty() {
-            None
-        } else {
-            self.pop_max(1).pop()
+    pub fn take<Q>(&mut self, item: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        if !self.contains(item) {
+            return None;
+        }
+        // Unlink the first element equal to `item` from the bottom up. A right
+        // node further up is only part of its tower if it sits directly above the
+        // node unlinked below it: other elements can compare equal to `item`
+        // through `Borrow` while being different elements.
+        let path = self.search_path(item, true);
+        let mut tower: Vec<NonNull<Node<T>>> = Vec::new();
+        for node in path.into_iter().rev().map(|node| node.curr_node) {
+            unsafe {
+                (*node).set_width((*node).width() - 1);
+                // Invariant: `node` can never be PosInf
+                let right = (*node).right.unwrap();
+                match tower.last() {
+                    Some(below) if right.as_ref().down != Some(*below) => continue,
+                    _ => {}
+                }
+                // So the node right of us needs to be removed.
+                (*node).set_width((*node).width() + right.as_ref().width());
+                (*node).right = right.as_ref().right;
+                tower.push(right);
+            }
         }
+        // INVARIANT: `contains` found an equal element, so the tower has a bottom.
+        let taken = unsafe { (*tower[0].as_ptr()).value.take_value() };
+        for node in tower {
+            unsafe { self.pool.free(node) };
+        }
+        self.len -= 1;
+        self.shrink_levels();
+        Some(taken)
     }
 
-    /// Pop the first element off of the skiplist.
+    /// Remove every item in `iter` from the skiplist, returning how many
+    /// were actually removed.
     ///
-    /// Runs in O(logn) time, O(1) space.
+    /// If the items are sorted, each search resumes from where the previous
+    /// one ended, so the whole batch shares a single left-to-right pass over
+    /// the skiplist: `O(min(n + m, m logn))` time rather than `m` top-down searches.
+    /// Items that are out of order restart their search from the top.
     ///
     /// # Example
     ///
@@ -804,293 +1673,1997 @@ impl<T: PartialOrd + Clone> SkipList<T> {
     /// use convenient_skiplist::SkipList;
     /// let mut sk = SkipList::from(0..10);
     ///
-    /// assert_eq!(Some(0), sk.pop_front());
+    /// assert_eq!(sk.remove_many(&[2, 3, 4, 20]), 3);
+    /// assert_eq!(sk.remove_many(vec![9, 0]), 2);
+    /// assert_eq!(vec![1, 5, 6, 7, 8], Vec::from(sk));
     /// ```
-    #[inline]
-    pub fn pop_front(&mut self) -> Option<T> {
-        if self.is_empty() {
-            None
-        } else {
-            self.pop_min(1).pop()
+    pub fn remove_many<I>(&mut self, iter: I) -> usize
+    where
+        I: IntoIterator,
+        I::Item: Borrow<T>,
+    {
+        let mut path = SearchPath::new(self.top_left.as_ptr());
+        let mut removed = 0;
+        for item in iter {
+            let item = item.borrow();
+            let last = path.nodes_mut().last().unwrap().curr_node;
+            if let Some(last) = unsafe { (*last).value.value() } {
+                if item <= last {
+                    path = SearchPath::new(self.top_left.as_ptr());
+                }
+            }
+            let left = path.advance_to(item);
+            if unsafe { &(*left).right.unwrap().as_ref().value } != item {
+                continue;
+            }
+            // Every node on the path is immediately left of `item`, so
+            // unlink its tower bottom up and shrink the widths above it.
+            let mut tower_top: Option<NonNull<Node<T>>> = None;
+            for node in path.nodes_mut().iter().rev() {
+                unsafe {
+                    let left = node.curr_node;
+                    let right = (*left).right.unwrap();
+                    let in_tower = match tower_top {
+                        None => true,
+                        Some(below) => right.as_ref().down == Some(below),
+                    };
+                    if in_tower {
+                        (*left).set_width((*left).width() + right.as_ref().width() - 1);
+                        (*left).right = right.as_ref().right;
+                        tower_top = Some(right);
+                    } else {
+                        (*left).set_width((*left).width() - 1);
+                    }
+                }
+            }
+            while let Some(node) = tower_top {
+                unsafe {
+                    tower_top = node.as_ref().down;
+                    self.pool.free(node);
+                }
+            }
+            self.len -= 1;
+            removed += 1;
         }
+        self.shrink_levels();
+        #[cfg(debug_assertions)]
+        {
+            self.ensure_invariants()
+        }
+        removed
     }
 
-    fn iter_vertical(&self) -> impl Iterator<Item = *mut Node<T>> {
-        VerticalIter::new(self.top_left.as_ptr())
-    }
-
-    /// Pop `count` elements off of the start of the Skiplist.
-    ///
-    /// Runs in O(logn * count) time, O(count) space.
-    ///
-    /// Memory pressure: This is implemented such that the entire
-    /// region of the skiplist is cleaved off at once. So you'll
-    /// see in the worse case (i.e. all towers have maximum height ~ logn)
-    /// count * logn memory deallocations.
-    ///
-    /// Returns an empty `vec` if count == 0.
+    /// Remove and return the item at `index`.
     ///
-    /// Will dealloc the whole skiplist if count >= len and start fresh.
+    /// Runs in O(log n) time.
     ///
     /// # Example
     ///
     /// ```rust
     /// use convenient_skiplist::SkipList;
-    /// let mut sk = SkipList::from(0..10);
+    /// let mut sk = SkipList::from(0..5);
     ///
-    /// assert_eq!(vec![0, 1, 2], sk.pop_min(3));
-    /// assert_eq!(vec![3], sk.pop_min(1));
-    /// assert_eq!(vec![4, 5], sk.pop_min(2));
-    /// assert_eq!(vec![6, 7, 8, 9], sk.pop_max(5));
+    /// assert_eq!(sk.len(), 5);
+    /// assert_eq!(sk.remove_at(1), Some(1));
+    /// assert_eq!(sk.len(), 4);
+    /// ```
+    pub fn remove_at(&mut self, index: usize) -> Option<T> {
+        // `take` is done with `item` before it moves the value out.
+        let item = unsafe { &*(self.at_index(index)? as *const T) };
+        self.take(item)
+    }
+
+    /// Return the number of elements in the skiplist.
     ///
-    /// let v: Vec<u32> = Vec::new();
-    /// assert_eq!(v, sk.pop_min(1000)); // empty
+    /// # Example
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let mut sk = SkipList::new();
+    ///
+    /// sk.insert(0);
+    /// assert_eq!(sk.len(), 1);
+    ///
+    /// sk.insert(1);
+    /// assert_eq!(sk.len(), 2);
     /// ```
     #[inline]
-    pub fn pop_min(&mut self, count: usize) -> Vec<T> {
-        if count == 0 || self.is_empty() {
-            return Vec::with_capacity(0);
-        }
-        if count >= self.len() {
-            let ret = self.iter_all().cloned().collect();
-            // Tested in valgrind -- this drops old me.
-            *self = SkipList::new();
-            return ret;
-        }
-        let ele_at = self.at_index(count).unwrap();
-        // dbg!(ele_at);
-        let mut ret = Vec::with_capacity(count);
-        for (left, row_end) in self.iter_vertical().zip(self.path_to(ele_at)) {
-            // Our path can have the same elements left and right of the
-            // frontier.
-            if std::ptr::eq(left, row_end.curr_node) {
-                unsafe { (*left).width -= count };
-                continue;
-            }
-            debug_assert!(count >= row_end.curr_width);
-            // Next, we need to unlink the first node after `left`,
-            // and calculate width.
-            // Idea: count is how many elements popped over, curr_width
-            // is how far we've traveled so far.
-            //         _
-            // -inf ->                ...
-            // -inf -> 1 ->           ...
-            // -inf -> 1 -> 2 -> 3 -> ...
-            //         ~    ~    ~
-            // width_over_removed = count(_) - count(~) = 2
-            // new_width = Node<1>.width - width_over_removed
-            let width_over_removed = count - row_end.curr_width;
-            let new_width = unsafe { (*row_end.curr_node).width - width_over_removed };
-            // Now, surgically remove this stretch of nodes.
-            unsafe {
-                let mut start_garbage = (*left).right.unwrap();
-                (*left).right = (*row_end.curr_node).right;
-                (*left).width = new_width;
-                (*row_end.curr_node).right = None;
-                // We're at the bottom, so lets grab our return values.
-                if start_garbage.as_ref().down.is_none() {
-                    let mut curr_node = start_garbage.as_ptr();
-                    loop {
-                        ret.push((*curr_node).value.get_value().clone());
-                        curr_node = match (*curr_node).right {
-                            Some(right) => right.as_ptr(),
-                            None => break,
-                        };
-                    }
-                }
-                start_garbage.as_mut().clear_right();
-                drop(Box::from_raw(start_garbage.as_ptr()));
-            }
-        }
-        self.len -= count;
-        ret
+    pub fn len(&self) -> usize {
+        self.len
     }
 
-    /// Left-Biased iterator towards `item`.
-    ///
-    /// Returns all possible positions *left* where `item`
-    /// is or should be in the skiplist.
+    /// Returns true if the skiplist is empty
     #[inline]
-    fn iter_left<'a>(&'a self, item: &'a T) -> impl Iterator<Item = *mut Node<T>> + 'a {
-        LeftBiasIter::new(self.top_left.as_ptr(), item)
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
     }
 
-    /// Iterator over all elements in the Skiplist.
+    // TODO
+    // fn remove_range<'a>(&'a mut self, _start: &'a T, _end: &'a T) -> usize {
+    //     // Idea: Use iter_left twice to determine the chunk in the middle to remove.
+    //     // Hardest part will be cleaning up garbage. :thinking:
+    //     todo!()
+    // }
+
+    /// Find the index of `item` in the `SkipList`.
     ///
-    /// This runs in `O(n)` time.
+    /// Runs in `O(logn)` time.
     ///
-    /// # Example
+    /// # Arguments
+    ///
+    /// * `item`: the item to find the position of.
     ///
+    /// # Example
     /// ```rust
     /// use convenient_skiplist::SkipList;
     /// let mut sk = SkipList::new();
-    /// sk.insert(0usize);
-    /// sk.insert(1usize);
-    /// sk.insert(2usize);
-    /// for item in sk.iter_all() {
-    ///     println!("{:?}", item);
-    /// }
+    /// sk.insert(1);
+    /// sk.insert(2);
+    /// sk.insert(3);
+    ///
+    /// assert_eq!(sk.index_of(&1), Some(0));
+    /// assert_eq!(sk.index_of(&2), Some(1));
+    /// assert_eq!(sk.index_of(&3), Some(2));
+    /// assert_eq!(sk.index_of(&999), None);
     /// ```
     #[inline]
-    pub fn iter_all(&self) -> IterAll<T> {
-        unsafe { IterAll::new(self.top_left.as_ref(), self.len) }
+    pub fn index_of<Q>(&self, item: &Q) -> Option<usize>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        // INVARIANT: path_to is a LeftBiasIterWidth, so there's always a
+        // node right of us.
+        self.path_to(item).last().and_then(|node| {
+            if unsafe { (*node.curr_node).right.unwrap().as_ref().value.eq_key(item) } {
+                Some(node.curr_width)
+            } else {
+                None
+            }
+        })
     }
 
-    /// Iterator over an inclusive range of elements in the SkipList.
+    /// Get a reference to the element in the skiplist equal to `item`.
     ///
-    /// This runs in `O(logn + k)`, where k is the width of range.
+    /// Runs in `O(logn)` time.
     ///
     /// # Example
     ///
     /// ```rust
     /// use convenient_skiplist::SkipList;
-    /// let mut sk = SkipList::new();
-    /// for item in 0..100 {
-    ///     sk.insert(item);
-    /// }
+    /// let sk = SkipList::from(vec!["a".to_string(), "b".to_string()]);
     ///
-    /// for item in sk.range(&20, &40) {
-    ///     println!("{}", item); // First prints 20, then 21, ... and finally 40.
-    /// }
+    /// assert_eq!(sk.get("a"), Some(&"a".to_string()));
+    /// assert_eq!(sk.get("c"), None);
     /// ```
     #[inline]
-    pub fn range<'a>(&'a self, start: &'a T, end: &'a T) -> SkipListRange<'a, T> {
-        SkipListRange::new(unsafe { self.top_left.as_ref() }, start, end)
+    pub fn get<Q>(&self, item: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        // INVARIANT: path_to always ends on the bottom row, with a node right of it.
+        let left = self.path_to(item).last().unwrap();
+        let right = unsafe { &(*left.curr_node).right.unwrap().as_ref().value };
+        if right.eq_key(item) {
+            right.value()
+        } else {
+            None
+        }
     }
 
-    /// Iterate over a range of indices.
+    /// Get mutable access to the element equal to `item`, if it's in the skiplist.
     ///
-    /// This runs in `O(logn + k)`, where k is the width of range.
+    /// This is for updating parts of the element that don't affect its ordering,
+    /// like a payload carried alongside a key. The returned guard derefs to `&mut T`,
+    /// and when it's dropped (in debug builds) checks the element is still ordered
+    /// between its neighbours.
     ///
-    /// This is different than `SkipList::range` as this operates on indices and not values.
+    /// Runs in `O(logn)` time.
     ///
     /// # Example
     ///
     /// ```rust
     /// use convenient_skiplist::SkipList;
-    /// let mut sk = SkipList::new();
-    /// for c in 'a'..'z' {
-    ///     sk.insert(c);
+    /// use std::cmp::Ordering;
+    ///
+    /// #[derive(Clone, Debug)]
+    /// struct Session {
+    ///     id: u32,
+    ///     hits: u32,
     /// }
     ///
-    /// for item in sk.index_range(0..5) {
-    ///     println!("{}", item); // Prints a, b, c, d, e
+    /// impl PartialEq for Session {
+    ///     fn eq(&self, other: &Self) -> bool {
+    ///         self.id == other.id
+    ///     }
+    /// }
+    ///
+    /// impl PartialOrd for Session {
+    ///     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    ///         self.id.partial_cmp(&other.id)
+    ///     }
     /// }
+    ///
+    /// let mut sk = SkipList::new();
+    /// sk.insert(Session { id: 1, hits: 0 });
+    ///
+    /// sk.get_mut(&Session { id: 1, hits: 0 }).unwrap().hits += 1;
+    /// assert_eq!(sk.at_index(0).unwrap().hits, 1);
     /// ```
-    pub fn index_range<R: RangeBounds<usize>>(&self, range: R) -> SkipListIndexRange<'_, R, T> {
-        SkipListIndexRange::new(unsafe { self.top_left.as_ref() }, range)
+    pub fn get_mut<Q>(&mut self, item: &Q) -> Option<ValueMut<'_, T>>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        // INVARIANT: The search path always reaches the bottom row.
+        let left = self.iter_left(item).last().unwrap();
+        unsafe {
+            let bottom = (*left).right.unwrap();
+            if !bottom.as_ref().value.eq_key(item) {
+                return None;
+            }
+            Some(ValueMut::new(bottom, NonNull::new_unchecked(left)))
+        }
     }
 
-    /// Iterator over an inclusive range of elements in the SkipList,
-    /// as defined by the `inclusive_fn`.
+    /// Make a [Finger](crate::Finger) for searches that are close to each other.
     ///
-    /// This runs in `O(logn + k)`, where k is the width of range.
+    /// A finger remembers where its last search ended, so searching for a nearby
+    /// item costs `O(log d)`, where d is the distance in elements, instead of `O(logn)`.
     ///
-    /// As the skiplist is ordered in an ascending way, `inclusive_fn` should be
-    /// structured with the idea in mind that you're going to see the smallest elements
-    /// first. `inclusive_fn` should be designed to extract a *single contiguous
-    /// stretch of elements*.
+    /// # Example
     ///
-    /// This iterator will find the smallest element in the range,
-    /// and then return elements until it finds the first element
-    /// larger than the range.
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from(0..1000);
+    /// let mut finger = sk.finger();
     ///
-    /// If multiple ranges are desired, you can use `range_with` multiple times,
-    /// and simply use the last element of the previous run as the start of
-    /// the next run.
+    /// for item in 100..200 {
+    ///     assert_eq!(finger.index_of(&item), Some(item as usize));
+    /// }
+    /// ```
+    #[inline]
+    pub fn finger(&self) -> Finger<'_, T> {
+        Finger::new(self)
+    }
+
+    /// Count how many elements in the skiplist are strictly less than `item`.
+    ///
+    /// Unlike `index_of`, `item` doesn't need to be in the skiplist. If it is,
+    /// this is the same as its index.
+    ///
+    /// Runs in `O(logn)` time.
     ///
     /// # Example
     ///
     /// ```rust
-    /// use convenient_skiplist::{RangeHint, SkipList};
-    /// let mut sk = SkipList::new();
-    /// for item in 0..100 {
-    ///     sk.insert(item);
-    /// }
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from(vec![10, 20, 30]);
     ///
-    /// let desired_range = sk.range_with(|&ele| {
-    ///     if ele <= 5 {
-    ///         RangeHint::SmallerThanRange
-    ///     } else if ele <= 30 {
-    ///         RangeHint::InRange
-    ///     } else {
-    ///         RangeHint::LargerThanRange
-    ///     }
-    /// });
-    /// for item in desired_range {
-    ///     println!("{}", item); // First prints 6, then 7, ... and finally 30.
-    /// }
+    /// assert_eq!(sk.rank(&5), 0);
+    /// assert_eq!(sk.rank(&20), 1);
+    /// assert_eq!(sk.rank(&25), 2);
+    /// assert_eq!(sk.rank(&99), 3);
     /// ```
     #[inline]
-    pub fn range_with<F>(&self, inclusive_fn: F) -> IterRangeWith<T, F>
+    pub fn rank<Q>(&self, item: &Q) -> usize
     where
-        F: Fn(&T) -> RangeHint,
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
     {
-        IterRangeWith::new(unsafe { self.top_left.as_ref() }, inclusive_fn)
+        // INVARIANT: path_to always ends on the bottom row.
+        self.path_to(item).last().unwrap().curr_width
     }
 
-    /// Clear (deallocate all entries in) the skiplist.
+    /// The fraction of elements in the skiplist strictly less than `item`,
+    /// between `0.0` and `1.0`. An empty skiplist always returns `0.0`.
     ///
-    /// Returns the number of elements removed (length of bottom row).
+    /// Runs in `O(logn)` time.
     ///
     /// # Example
     ///
     /// ```rust
-    /// use convenient_skiplist::{RangeHint, SkipList};
-    /// let mut sk = SkipList::from(0..10);
-    /// assert_eq!(sk.clear(), 10);
-    /// assert_eq!(sk, SkipList::new());
+    /// use convenient_skiplist::SkipList;
+    /// let latencies = SkipList::from((0..1000).step_by(10).collect::<Vec<u32>>());
     ///
+    /// assert_eq!(latencies.percentile_rank(&250), 0.25);
+    /// assert_eq!(latencies.percentile_rank(&0), 0.0);
+    /// assert_eq!(latencies.percentile_rank(&5000), 1.0);
     /// ```
-    pub fn clear(&mut self) -> usize {
-        let removed = self.len();
-        *self = SkipList::new();
-        removed
-    }
-
     #[inline]
-    fn path_to<'a>(&self, item: &'a T) -> LeftBiasIterWidth<'a, T> {
-        LeftBiasIterWidth::new(self.top_left.as_ptr(), item)
+    pub fn percentile_rank<Q>(&self, item: &Q) -> f64
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        if self.is_empty() {
+            return 0.0;
+        }
+        self.rank(item) as f64 / self.len() as f64
     }
 
+    /// Count the elements in the inclusive range `[start, end]`, the same
+    /// elements `range(start, end)` would visit.
+    ///
+    /// Runs in `O(logn)` time, regardless of how many elements are in the range.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from(0..100);
+    ///
+    /// assert_eq!(sk.count_range(&20, &40), 21);
+    /// assert_eq!(sk.count_range(&-10, &4), 5);
+    /// assert_eq!(sk.count_range(&40, &20), 0);
+    /// ```
     #[inline]
-    fn insert_path(&mut self, item: &T) -> Vec<NodeWidth<T>> {
-        self.path_to(item).collect()
+    pub fn count_range<Q>(&self, start: &Q, end: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        // INVARIANT: path_to always ends on the bottom row, with a node right of it.
+        let end = self.path_to(end).last().map_or(0, |left| {
+            let right = unsafe { &(*left.curr_node).right.unwrap().as_ref().value };
+            left.curr_width + right.eq_key(end) as usize
+        });
+        end.saturating_sub(self.rank(start))
     }
 
-    fn pos_neg_pair(width: usize) -> NonNull<Node<T>> {
-        let right = Box::new(Node {
-            right: None,
-            down: None,
-            value: NodeValue::PosInf,
-            width: 1,
-        });
+    /// Find an element using a comparator instead of a full element, like
+    /// `slice::binary_search_by`.
+    ///
+    /// `f` is given elements of the skiplist, and should return whether they're
+    /// `Less`, `Equal` or `Greater` than what you're looking for. It needs to
+    /// agree with the order of the skiplist.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from(vec![(1, "one"), (2, "two"), (3, "three")]);
+    ///
+    /// assert_eq!(sk.find_by(|(key, _)| key.cmp(&2)), Some(&(2, "two")));
+    /// assert_eq!(sk.find_by(|(key, _)| key.cmp(&4)), None);
+    /// ```
+    #[inline]
+    pub fn find_by<F: FnMut(&T) -> Ordering>(&self, mut f: F) -> Option<&T> {
+        let left = self.path_to_by(&mut f);
         unsafe {
-            let left = Box::new(Node {
-                right: Some(NonNull::new_unchecked(Box::into_raw(right))),
-                down: None,
-                value: NodeValue::NegInf,
-                width,
-            });
-            NonNull::new_unchecked(Box::into_raw(left))
+            (*left.curr_node)
+                .right
+                .unwrap()
+                .as_ref()
+                .value
+                .value()
+                .filter(|&v| f(v) == Ordering::Equal)
         }
     }
 
-    fn make_node(value: T, width: usize) -> NonNull<Node<T>> {
-        unsafe {
-            let node = Box::new(Node {
-                right: None,
-                down: None,
-                value: NodeValue::Value(value),
-                width,
-            });
-            NonNull::new_unchecked(Box::into_raw(node))
+    /// Find the index of an element using a comparator instead of a full element.
+    ///
+    /// See `find_by` for how `f` should behave.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from(vec![(1, "one"), (2, "two"), (3, "three")]);
+    ///
+    /// assert_eq!(sk.index_of_by(|(key, _)| key.cmp(&3)), Some(2));
+    /// assert_eq!(sk.index_of_by(|(key, _)| key.cmp(&0)), None);
+    /// ```
+    #[inline]
+    pub fn index_of_by<F: FnMut(&T) -> Ordering>(&self, mut f: F) -> Option<usize> {
+        let left = self.path_to_by(&mut f);
+        let right = unsafe { (*left.curr_node).right.unwrap().as_ref() };
+        match right.value.value() {
+            Some(v) if f(v) == Ordering::Equal => Some(left.curr_width),
+            _ => None,
         }
     }
 
-    #[cfg(debug_assertions)]
-    fn ensure_columns_same_value(&self) {
-        let mut left_row = self.top_left;
+    /// Get the item at the index `index `in the `SkipList`.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Arguments
+    ///
+    /// * `index`: the index to get the item at
+    ///
+    /// # Example
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from(0..10);
+    /// for i in 0..10 {
+    ///     assert_eq!(Some(&i), sk.at_index(i));
+    /// }
+    /// assert_eq!(None, sk.at_index(11));
+    ///
+    /// let mut sk = SkipList::new();
+    /// sk.insert('a');
+    /// sk.insert('b');
+    /// sk.insert('c');
+    /// assert_eq!(Some(&'a'), sk.at_index(0));
+    /// assert_eq!(Some(&'b'), sk.at_index(1));
+    /// assert_eq!(Some(&'c'), sk.at_index(2));
+    /// assert_eq!(None, sk.at_index(3));
+    /// ```
+    #[inline]
+    pub fn at_index(&self, index: usize) -> Option<&T> {
+        if index >= self.len() {
+            return None;
+        }
+        unsafe {
+            let mut curr_node = self.top_left.as_ref();
+            let mut distance_left = index + 1;
+            loop {
+                if distance_left == 0 {
+                    return Some(curr_node.value.get_value());
+                }
+                if curr_node.width() <= distance_left {
+                    distance_left -= curr_node.width();
+                    // INVARIANT: We've checked if `index` < self.len(),
+                    // so there's always a `right`
+                    curr_node = curr_node.right.unwrap().as_ptr().as_ref().unwrap();
+                    continue;
+                } else if let Some(down) = curr_node.down {
+                    curr_node = down.as_ptr().as_ref().unwrap();
+                } else {
+                    unreachable!()
+                }
+            }
+        }
+    }
+
+    /// Get the items at several indices in a single left-to-right walk.
+    ///
+    /// This is much faster than calling `at_index` for every index, as each lookup
+    /// continues from the last one rather than starting over from the top.
+    ///
+    /// Runs in `O(k * log(n / k))` time, where k is the number of indices.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `indices` isn't sorted in ascending order, or if an index is out of bounds.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from((0..100).map(|i| i * 2).collect::<Vec<_>>());
+    ///
+    /// assert_eq!(sk.select_many(&[0, 10, 10, 99]), vec![&0, &20, &20, &198]);
+    /// ```
+    pub fn select_many(&self, indices: &[usize]) -> Vec<&T> {
+        let mut path = SearchPath::new(self.top_left.as_ptr());
+        let mut last = 0;
+        indices
+            .iter()
+            .map(|&index| {
+                assert!(index < self.len(), "index out of bounds!");
+                assert!(
+                    last <= index,
+                    "select_many: indices must be in ascending order"
+                );
+                last = index;
+                // NegInf is at index 0, so the element at `index` is one further.
+                let node = path.advance_to_index(index + 1);
+                unsafe { (*node).value.get_value() }
+            })
+            .collect()
+    }
+
+    /// Pick an element uniformly at random, or `None` if the skiplist is empty.
+    ///
+    /// Runs in `O(logn)` time, by picking a random index and descending to it.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from(0..10);
+    ///
+    /// let chosen = sk.choose(&mut rand::thread_rng()).unwrap();
+    /// assert!(sk.contains(chosen));
+    /// ```
+    #[inline]
+    pub fn choose<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<&T> {
+        if self.is_empty() {
+            return None;
+        }
+        self.at_index(rng.gen_range(0, self.len()))
+    }
+
+    /// Pick `k` distinct elements uniformly at random, returned in ascending order.
+    ///
+    /// If `k` is at least the length of the skiplist, every element is returned.
+    ///
+    /// The random indices are resolved with `select_many`, so this runs in
+    /// `O(k * log(n / k))` time rather than `k` separate descents.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from(0..100);
+    ///
+    /// let sample = sk.sample(&mut rand::thread_rng(), 5);
+    /// assert_eq!(sample.len(), 5);
+    /// assert!(sample.windows(2).all(|pair| pair[0] < pair[1]));
+    /// ```
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R, k: usize) -> Vec<&T> {
+        let mut indices = rand::seq::index::sample(rng, self.len(), k.min(self.len())).into_vec();
+        indices.sort_unstable();
+        self.select_many(&indices)
+    }
+
+    /// Get the item `index` positions from the end of the `SkipList`,
+    /// so `0` is the last item.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from(0..10);
+    ///
+    /// assert_eq!(Some(&9), sk.at_index_from_end(0));
+    /// assert_eq!(Some(&0), sk.at_index_from_end(9));
+    /// assert_eq!(None, sk.at_index_from_end(10));
+    /// ```
+    #[inline]
+    pub fn at_index_from_end(&self, index: usize) -> Option<&T> {
+        self.len()
+            .checked_sub(index + 1)
+            .and_then(|index| self.at_index(index))
+    }
+
+    /// Find the smallest element in the skiplist that's greater than or equal to `item`.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from(vec![10, 20, 30]);
+    ///
+    /// assert_eq!(sk.ceiling(&20), Some(&20));
+    /// assert_eq!(sk.ceiling(&21), Some(&30));
+    /// assert_eq!(sk.ceiling(&5), Some(&10));
+    /// assert_eq!(sk.ceiling(&31), None);
+    /// ```
+    #[inline]
+    pub fn ceiling<Q>(&self, item: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        // INVARIANT: iter_left always ends at the bottom row,
+        // and there's always a node right of it.
+        let left = self.iter_left(item).last().unwrap();
+        unsafe { (*left).right.unwrap().as_ref().value.value() }
+    }
+
+    /// Find the largest element in the skiplist that's less than or equal to `item`.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from(vec![10, 20, 30]);
+    ///
+    /// assert_eq!(sk.floor(&20), Some(&20));
+    /// assert_eq!(sk.floor(&21), Some(&20));
+    /// assert_eq!(sk.floor(&35), Some(&30));
+    /// assert_eq!(sk.floor(&9), None);
+    /// ```
+    #[inline]
+    pub fn floor<Q>(&self, item: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        let left = self.iter_left(item).last().unwrap();
+        unsafe {
+            let right = &(*left).right.unwrap().as_ref().value;
+            if right.eq_key(item) {
+                right.value()
+            } else {
+                (*left).value.value()
+            }
+        }
+    }
+
+    /// Check if there's at least one element in the inclusive range `[start, end]`.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from(vec![10, 20, 30]);
+    ///
+    /// assert!(sk.contains_any_in(&15, &25));
+    /// assert!(sk.contains_any_in(&30, &30));
+    /// assert!(!sk.contains_any_in(&21, &29));
+    /// assert!(!sk.contains_any_in(&25, &15));
+    /// ```
+    #[inline]
+    pub fn contains_any_in<Q>(&self, start: &Q, end: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        matches!(self.ceiling(start), Some(first) if first.borrow() <= end)
+    }
+
+    /// Get the median of the skiplist, or `None` if it's empty.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::{Median, SkipList};
+    /// let sk = SkipList::from(vec![1, 2, 3]);
+    /// assert_eq!(sk.median(), Some(Median::Single(&2)));
+    ///
+    /// let sk = SkipList::from(vec![1, 2, 3, 4]);
+    /// assert_eq!(sk.median(), Some(Median::Pair(&2, &3)));
+    /// ```
+    pub fn median(&self) -> Option<Median<'_, T>> {
+        let mid = self.len() / 2;
+        if self.len() % 2 == 1 {
+            return self.at_index(mid).map(Median::Single);
+        }
+        let mut middle = self.index_range(mid.checked_sub(1)?..=mid);
+        // INVARIANT: mid - 1 and mid are both valid indices.
+        Some(Median::Pair(middle.next().unwrap(), middle.next().unwrap()))
+    }
+
+    /// Peek at the first item in the skiplist.
+    ///
+    /// Runs in constant time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let mut sk = SkipList::from(0..10);
+    ///
+    /// assert_eq!(Some(&0), sk.peek_first());
+    /// ```
+    #[inline]
+    pub fn peek_first(&self) -> Option<&T> {
+        self.at_index(0)
+    }
+
+    /// Peek at the last item in the skiplist.
+    ///
+    /// Runs in O(log n) time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let mut sk = SkipList::from(0..10);
+    ///
+    /// assert_eq!(Some(&9), sk.peek_last());
+    /// ```
+    #[inline]
+    pub fn peek_last(&self) -> Option<&T> {
+        if self.is_empty() {
+            None
+        } else {
+            self.at_index(self.len() - 1)
+        }
+    }
+
+    /// Iterate over the `k` largest elements without removing them,
+    /// in ascending order like `pop_max`.
+    ///
+    /// Runs in `O(logn + k)` time, jumping straight to the element at `len - k`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from(0..10);
+    ///
+    /// assert_eq!(vec![&7, &8, &9], sk.iter_max_k(3).collect::<Vec<_>>());
+    /// assert_eq!(10, sk.iter_max_k(100).count());
+    /// assert_eq!(sk.len(), 10);
+    /// ```
+    #[inline]
+    pub fn iter_max_k(&self, k: usize) -> SkipListIndexRange<'_, Range<usize>, T> {
+        self.index_range(self.len().saturating_sub(k)..self.len())
+    }
+
+    /// Iterate over the `k` smallest elements without removing them,
+    /// in ascending order like `pop_min`.
+    ///
+    /// Runs in `O(k)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from(0..10);
+    ///
+    /// assert_eq!(vec![&0, &1, &2], sk.iter_min_k(3).collect::<Vec<_>>());
+    /// assert_eq!(10, sk.iter_min_k(100).count());
+    /// assert_eq!(sk.len(), 10);
+    /// ```
+    #[inline]
+    pub fn iter_min_k(&self, k: usize) -> SkipListIndexRange<'_, Range<usize>, T> {
+        self.index_range(0..k.min(self.len()))
+    }
+
+    /// Pop `count` elements off of the end of the Skiplist.
+    ///
+    /// Runs in O(logn * count) time, O(logn + count) space.
+    ///
+    /// Memory pressure: This is implemented such that the entire
+    /// region of the skiplist is cleaved off at once. So you'll
+    /// see in the worse case (i.e. all towers have maximum height ~ logn)
+    /// count * logn memory deallocations.
+    ///
+    /// Returns an empty `vec` if count == 0.
+    ///
+    /// Will dealloc the whole skiplist if count >= len and start fresh.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let mut sk = SkipList::from(0..10);
+    ///
+    /// assert_eq!(Some(&7), sk.at_index(7));
+    /// assert_eq!(vec![7, 8, 9], sk.pop_max(3));
+    /// assert_eq!(vec![6], sk.pop_max(1));
+    /// assert_eq!(vec![4, 5], sk.pop_max(2));
+    /// assert_eq!(vec![0, 1, 2, 3], sk.pop_max(5));
+    ///
+    /// let v: Vec<u32> = Vec::new();
+    /// assert_eq!(v, sk.pop_max(1000)); // empty
+    /// ```
+    #[inline]
+    pub fn pop_max(&mut self, count: usize) -> Vec<T> {
+        if self.is_empty() || count == 0 {
+            return vec![];
+        }
+        if count >= self.len() {
+            return self.take_all().into_iter().collect();
+        }
+        // `ele_at` is the first element we pop, which is moved out only after
+        // we're done searching for it.
+        let ele_at = unsafe { &*(self.at_index(self.len() - count).unwrap() as *const T) };
+        self.len -= count;
+        // IDEA: Calculate widths by adding _backwards_ through the
+        // insert path.
+        let mut frontier = self.search_path(ele_at, false);
+        let last_value = frontier.last_mut().cloned().unwrap();
+        let mut last_width = last_value.curr_width;
+        let mut ret: Vec<_> = Vec::with_capacity(count);
+        let mut jumped_left = 1;
+        unsafe {
+            ret.extend(NodeRightIter::new(
+                (*last_value.curr_node).right.unwrap().as_ptr(),
+            ));
+            (*last_value.curr_node).clear_right(&mut self.pool);
+        }
+        for nw in frontier.into_iter().rev().skip(1) {
+            unsafe {
+                // We've jumped right, and now need to update our width field.
+                // Do we need this if-gate?
+                if (*nw.curr_node).value != (*last_value.curr_node).value {
+                    jumped_left += last_width - nw.curr_width;
+                    last_width = nw.curr_width;
+                }
+                (*nw.curr_node).clear_right(&mut self.pool);
+                (*nw.curr_node).set_width(jumped_left);
+            }
+        }
+        self.shrink_levels();
+        ret
+    }
+
+    /// Lazily pop up to `count` elements off of the end of the Skiplist,
+    /// largest first.
+    ///
+    /// Unlike `pop_max`, elements are unlinked one at a time as the
+    /// iterator is consumed, so stopping early leaves the rest in place.
+    ///
+    /// Each element popped takes O(logn) time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let mut sk = SkipList::from(0..10);
+    ///
+    /// let mut popped = sk.pop_max_iter(5);
+    /// assert_eq!(Some(9), popped.next());
+    /// assert_eq!(Some(8), popped.next());
+    /// drop(popped);
+    /// assert_eq!(8, sk.len());
+    /// assert_eq!(vec![7, 6], sk.pop_max_iter(2).collect::<Vec<_>>());
+    /// ```
+    #[inline]
+    pub fn pop_max_iter(&mut self, count: usize) -> PopMax<'_, T> {
+        PopMax::new(self, count)
+    }
+
+    /// Lazily pop up to `count` elements off of the start of the Skiplist,
+    /// smallest first.
+    ///
+    /// Unlike `pop_min`, elements are unlinked one at a time as the
+    /// iterator is consumed, so stopping early leaves the rest in place.
+    ///
+    /// Each element popped takes O(logn) time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let mut sk = SkipList::from(0..10);
+    ///
+    /// let small: Vec<_> = sk.pop_min_iter(100).take_while(|&x| x < 3).collect();
+    /// assert_eq!(vec![0, 1, 2], small);
+    /// // `3` was popped to end the `take_while`, but nothing after it.
+    /// assert_eq!(Some(&4), sk.peek_first());
+    /// ```
+    #[inline]
+    pub fn pop_min_iter(&mut self, count: usize) -> PopMin<'_, T> {
+        PopMin::new(self, count)
+    }
+
+    /// Pop the last element off of the skiplist.
+    ///
+    /// Runs in O(logn) time, O(1) space.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let mut sk = SkipList::from(0..10);
+    ///
+    /// assert_eq!(Some(9), sk.pop_back());
+    /// ```
+    #[inline]
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.is_empty() {
+            None
+        } else {
+            self.pop_max(1).pop()
+        }
+    }
+
+    /// Pop the first element off of the skiplist.
+    ///
+    /// Runs in O(logn) time, O(1) space.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let mut sk = SkipList::from(0..10);
+    ///
+    /// assert_eq!(Some(0), sk.pop_front());
+    /// ```
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.is_empty() {
+            None
+        } else {
+            self.pop_min(1).pop()
+        }
+    }
+
+    fn iter_vertical(&self) -> impl Iterator<Item = *mut Node<T>> {
+        VerticalIter::new(self.top_left.as_ptr())
+    }
+
+    /// Pop `count` elements off of the start of the Skiplist.
+    ///
+    /// Runs in O(logn * count) time, O(count) space.
+    ///
+    /// Memory pressure: This is implemented such that the entire
+    /// region of the skiplist is cleaved off at once. So you'll
+    /// see in the worse case (i.e. all towers have maximum height ~ logn)
+    /// count * logn memory deallocations.
+    ///
+    /// Returns an empty `vec` if count == 0.
+    ///
+    /// Will dealloc the whole skiplist if count >= len and start fresh.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let mut sk = SkipList::from(0..10);
+    ///
+    /// assert_eq!(vec![0, 1, 2], sk.pop_min(3));
+    /// assert_eq!(vec![3], sk.pop_min(1));
+    /// assert_eq!(vec![4, 5], sk.pop_min(2));
+    /// assert_eq!(vec![6, 7, 8, 9], sk.pop_max(5));
+    ///
+    /// let v: Vec<u32> = Vec::new();
+    /// assert_eq!(v, sk.pop_min(1000)); // empty
+    /// ```
+    #[inline]
+    pub fn pop_min(&mut self, count: usize) -> Vec<T> {
+        if count == 0 || self.is_empty() {
+            return Vec::with_capacity(0);
+        }
+        if count >= self.len() {
+            return self.take_all().into_iter().collect();
+        }
+        // `ele_at` is the first element we keep, so it outlives the nodes we free.
+        let ele_at = unsafe { &*(self.at_index(count).unwrap() as *const T) };
+        // dbg!(ele_at);
+        let mut ret = Vec::with_capacity(count);
+        for (left, row_end) in self.iter_vertical().zip(self.path_to(ele_at)) {
+            // Our path can have the same elements left and right of the
+            // frontier.
+            if std::ptr::eq(left, row_end.curr_node) {
+                unsafe { (*left).set_width((*left).width() - count) };
+                continue;
+            }
+            debug_assert!(count >= row_end.curr_width);
+            // Next, we need to unlink the first node after `left`,
+            // and calculate width.
+            // Idea: count is how many elements popped over, curr_width
+            // is how far we've traveled so far.
+            //         _
+            // -inf ->                ...
+            // -inf -> 1 ->           ...
+            // -inf -> 1 -> 2 -> 3 -> ...
+            //         ~    ~    ~
+            // width_over_removed = count(_) - count(~) = 2
+            // new_width = Node<1>.width - width_over_removed
+            let width_over_removed = count - row_end.curr_width;
+            let new_width = unsafe { (*row_end.curr_node).width() - width_over_removed };
+            // Now, surgically remove this stretch of nodes.
+            unsafe {
+                let mut start_garbage = (*left).right.unwrap();
+                (*left).right = (*row_end.curr_node).right;
+                (*left).set_width(new_width);
+                (*row_end.curr_node).right = None;
+                // We're at the bottom, so lets grab our return values.
+                if start_garbage.as_ref().down.is_none() {
+                    let mut curr_node = start_garbage.as_ptr();
+                    loop {
+                        ret.push((*curr_node).value.take_value());
+                        curr_node = match (*curr_node).right {
+                            Some(right) => right.as_ptr(),
+                            None => break,
+                        };
+                    }
+                }
+                start_garbage.as_mut().clear_right(&mut self.pool);
+                self.pool.free(start_garbage);
+            }
+        }
+        self.len -= count;
+        self.shrink_levels();
+        ret
+    }
+
+    /// Left-Biased iterator towards `item`.
+    ///
+    /// Returns all possible positions *left* where `item`
+    /// is or should be in the skiplist.
+    #[inline]
+    fn iter_left<'a, Q>(&'a self, item: &'a Q) -> impl Iterator<Item = *mut Node<T>> + 'a
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        LeftBiasIter::new(self.top_left.as_ptr(), item)
+    }
+
+    /// Iterator over all elements in the Skiplist, in ascending order.
+    ///
+    /// This is the same as `iter_all`, named to match the std collections.
+    /// `for item in &sk` also iterates this way.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from(vec![2, 1]);
+    ///
+    /// assert_eq!(sk.iter().collect::<Vec<_>>(), vec![&1, &2]);
+    /// for item in &sk {
+    ///     println!("{}", item);
+    /// }
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> IterAll<'_, T> {
+        self.iter_all()
+    }
+
+    /// Iterator over all elements in the Skiplist.
+    ///
+    /// This runs in `O(n)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let mut sk = SkipList::new();
+    /// sk.insert(0usize);
+    /// sk.insert(1usize);
+    /// sk.insert(2usize);
+    /// for item in sk.iter_all() {
+    ///     println!("{:?}", item);
+    /// }
+    /// ```
+    #[inline]
+    pub fn iter_all(&self) -> IterAll<'_, T> {
+        unsafe { IterAll::new(self.top_left.as_ref(), self.len, self.generation()) }
+    }
+
+    /// Iterator over all elements in the Skiplist, from the largest down.
+    ///
+    /// This runs in `O(n)` time, and is the same as `iter_all().rev()`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from(0..5);
+    ///
+    /// assert_eq!(vec![4, 3, 2, 1, 0], sk.iter_desc().cloned().collect::<Vec<_>>());
+    /// ```
+    #[inline]
+    pub fn iter_desc(&self) -> Rev<IterAll<'_, T>> {
+        self.iter_all().rev()
+    }
+
+    /// Iterator over the rows of the skiplist, from the top down, so the
+    /// structure can be inspected without parsing the `Debug` output.
+    ///
+    /// Each row yields `(value, width)` pairs, starting with the left edge
+    /// (`None`). A width is how many elements on the bottom row it takes to
+    /// reach the next node on that row. The top row is always empty.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from(0..10u32);
+    ///
+    /// let bottom: Vec<_> = sk.iter_levels().last().unwrap().collect();
+    /// assert_eq!(bottom[0], (None, 1));
+    /// assert_eq!(bottom[1], (Some(&0), 1));
+    /// assert_eq!(bottom.len(), 11);
+    ///
+    /// for level in sk.iter_levels() {
+    ///     // The widths of every row add up to the same total.
+    ///     assert_eq!(level.map(|(_, width)| width).sum::<usize>(), sk.len() + 1);
+    /// }
+    /// ```
+    #[inline]
+    pub fn iter_levels(&self) -> Levels<'_, T> {
+        Levels::new(unsafe { self.top_left.as_ref() }, self.height)
+    }
+
+    /// Iterator over mutable references to every element, in ascending order.
+    ///
+    /// Like `get_mut`, this is for updating the parts of elements that don't
+    /// affect their ordering. Each element is yielded as a guard which, when
+    /// dropped in debug builds, checks the element is still ordered between
+    /// its neighbours.
+    ///
+    /// This runs in `O(n)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let mut sk: SkipList<(u32, Option<u32>)> = (0..5).map(|i| (i, None)).collect();
+    ///
+    /// // Tuples compare their first field first, so the second can be freely changed.
+    /// for mut item in sk.iter_mut() {
+    ///     item.1 = Some(item.0 * 10);
+    /// }
+    /// assert_eq!(sk.at_index(3), Some(&(3, Some(30))));
+    /// ```
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        // INVARIANT: There's always a bottom row.
+        let bottom_left = VerticalIter::new(self.top_left.as_ptr()).last().unwrap();
+        IterMut::new(bottom_left, self.len)
+    }
+
+    /// Iterator over mutable references to the elements in an inclusive range,
+    /// mirroring `range`.
+    ///
+    /// Like `iter_mut`, this is for updating the parts of elements that don't
+    /// affect their ordering.
+    ///
+    /// This runs in `O(logn + k)`, where k is the width of range.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// // (idle seconds, session id, flagged)
+    /// let mut sk: SkipList<(u32, u32, bool)> = (0..10).map(|i| (i * 60, i, false)).collect();
+    ///
+    /// for mut session in sk.range_mut(&(300, 0, false), &(u32::MAX, 0, false)) {
+    ///     session.2 = true;
+    /// }
+    /// assert_eq!(sk.iter_all().filter(|session| session.2).count(), 5);
+    /// ```
+    #[inline]
+    pub fn range_mut<Q>(&mut self, start: &Q, end: &Q) -> IterMut<'_, T>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        let remaining = self.range(start, end).len();
+        // INVARIANT: The search path always reaches the bottom row.
+        let left = self.iter_left(start).last().unwrap();
+        IterMut::new(left, remaining)
+    }
+
+    /// Iterator over every run of `size` consecutive elements, in ascending order.
+    ///
+    /// Like `slice::windows`, but each window is a small `Vec`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is 0.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from(vec![1.0, 2.0, 4.0, 8.0]);
+    ///
+    /// let moving_avg: Vec<f64> = sk
+    ///     .windows(2)
+    ///     .map(|w| w.into_iter().sum::<f64>() / 2.0)
+    ///     .collect();
+    /// assert_eq!(moving_avg, vec![1.5, 3.0, 6.0]);
+    /// ```
+    #[inline]
+    pub fn windows(&self, size: usize) -> Windows<'_, T> {
+        assert!(size != 0, "window size must be non-zero");
+        Windows::new(self.iter_all(), size)
+    }
+
+    /// Iterator over the elements in chunks of `size`, in ascending order.
+    ///
+    /// Like `slice::chunks`, the last chunk is shorter if there aren't enough
+    /// elements to fill it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is 0.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from(0..5);
+    ///
+    /// let chunks: Vec<_> = sk.chunks(2).collect();
+    /// assert_eq!(chunks, vec![vec![&0, &1], vec![&2, &3], vec![&4]]);
+    /// ```
+    #[inline]
+    pub fn chunks(&self, size: usize) -> Chunks<'_, T> {
+        assert!(size != 0, "chunk size must be non-zero");
+        Chunks::new(self.iter_all(), size)
+    }
+
+    /// Iterator over all elements in the Skiplist, paired with their index.
+    ///
+    /// The indices are the same as `index_of` would give, without
+    /// searching for each element. This runs in `O(n)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from(vec!['a', 'b', 'c']);
+    ///
+    /// for (index, item) in sk.iter_with_index() {
+    ///     assert_eq!(sk.index_of(item), Some(index));
+    /// }
+    /// ```
+    #[inline]
+    pub fn iter_with_index(&self) -> Enumerate<IterAll<'_, T>> {
+        self.iter_all().enumerate()
+    }
+
+    /// Iterator over an inclusive range of elements in the SkipList.
+    ///
+    /// This runs in `O(logn + k)`, where k is the width of range.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let mut sk = SkipList::new();
+    /// for item in 0..100 {
+    ///     sk.insert(item);
+    /// }
+    ///
+    /// for item in sk.range(&20, &40) {
+    ///     println!("{}", item); // First prints 20, then 21, ... and finally 40.
+    /// }
+    ///
+    /// // Ranges are double ended, so you can also walk them backwards:
+    /// let latest: Vec<_> = sk.range(&20, &40).rev().take(3).collect();
+    /// assert_eq!(latest, vec![&40, &39, &38]);
+    /// ```
+    #[inline]
+    pub fn range<'a, Q>(&'a self, start: &'a Q, end: &'a Q) -> SkipListRange<'a, T, Q>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        SkipListRange::new(
+            unsafe { self.top_left.as_ref() },
+            (Bound::Included(start), Bound::Included(end)),
+            self.generation(),
+        )
+    }
+
+    /// Iterator over every element greater than or equal to `start`.
+    ///
+    /// This finds its starting point in `O(logn)`, so there's no need to make up
+    /// a maximum value to pass to `range`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from(vec![1, 3, 5, 7]);
+    ///
+    /// assert_eq!(vec![&5, &7], sk.iter_from(&4).collect::<Vec<_>>());
+    /// assert_eq!(vec![&3, &5, &7], sk.iter_from(&3).collect::<Vec<_>>());
+    /// assert_eq!(sk.iter_from(&8).next(), None);
+    /// ```
+    #[inline]
+    pub fn iter_from<'a, Q>(&'a self, start: &'a Q) -> SkipListRange<'a, T, Q>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        SkipListRange::new(
+            unsafe { self.top_left.as_ref() },
+            (Bound::Included(start), Bound::Unbounded),
+            self.generation(),
+        )
+    }
+
+    /// Iterator over the elements in any kind of range, like `a..b`, `a..` or `..=b`.
+    ///
+    /// The bounds can be a borrowed form of `T`, so a `SkipList<String>` can be
+    /// searched with `&str` bounds.
+    ///
+    /// This runs in `O(logn + k)`, where k is the width of range.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// use std::ops::Bound;
+    /// let sk = SkipList::from(0..10);
+    ///
+    /// assert_eq!(vec![&2, &3, &4], sk.range_bounds(2..5).collect::<Vec<_>>());
+    /// assert_eq!(vec![&8, &9], sk.range_bounds(8..).collect::<Vec<_>>());
+    /// assert_eq!(vec![&2, &1, &0], sk.range_bounds(..=2).rev().collect::<Vec<_>>());
+    ///
+    /// let words: SkipList<String> = ["apple", "kiwi", "melon", "plum"]
+    ///     .iter()
+    ///     .map(|s| s.to_string())
+    ///     .collect();
+    /// // `&str` bounds are written as a tuple, as `"a".."m"` would be a range of `&str`.
+    /// let early: Vec<_> = words
+    ///     .range_bounds::<str, _>((Bound::Included("a"), Bound::Excluded("m")))
+    ///     .collect();
+    /// assert_eq!(early, vec!["apple", "kiwi"]);
+    /// ```
+    #[inline]
+    pub fn range_bounds<Q, R>(&self, range: R) -> SkipListRange<'_, T, Q, R>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        SkipListRange::new(unsafe { self.top_left.as_ref() }, range, self.generation())
+    }
+
+    /// Iterate over a range of indices.
+    ///
+    /// This runs in `O(logn + k)`, where k is the width of range.
+    ///
+    /// This is different than `SkipList::range` as this operates on indices and not values.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let mut sk = SkipList::new();
+    /// for c in 'a'..'z' {
+    ///     sk.insert(c);
+    /// }
+    ///
+    /// for item in sk.index_range(0..5) {
+    ///     println!("{}", item); // Prints a, b, c, d, e
+    /// }
+    /// ```
+    pub fn index_range<R: RangeBounds<usize>>(&self, range: R) -> SkipListIndexRange<'_, R, T> {
+        SkipListIndexRange::new(
+            unsafe { self.top_left.as_ref() },
+            range,
+            self.len,
+            self.generation(),
+        )
+    }
+
+    /// Iterate over a range of indices measured from the end of the skiplist,
+    /// so `0` is the last item and `..100` is the last 100 items.
+    ///
+    /// Items are still yielded in ascending order. Positions past the start
+    /// of the skiplist are ignored.
+    ///
+    /// This runs in `O(logn + k)`, where k is the width of range.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from(0..10);
+    ///
+    /// let last_three: Vec<_> = sk.index_range_from_end(..3).cloned().collect();
+    /// assert_eq!(last_three, vec![7, 8, 9]);
+    ///
+    /// let middle: Vec<_> = sk.index_range_from_end(2..=4).cloned().collect();
+    /// assert_eq!(middle, vec![5, 6, 7]);
+    ///
+    /// let all: Vec<_> = sk.index_range_from_end(..100).cloned().collect();
+    /// assert_eq!(all, (0..10).collect::<Vec<_>>());
+    /// ```
+    pub fn index_range_from_end<R: RangeBounds<usize>>(
+        &self,
+        range: R,
+    ) -> SkipListIndexRange<'_, Range<usize>, T> {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.saturating_add(1),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len(),
+        };
+        let len = self.len();
+        self.index_range(len.saturating_sub(end)..len.saturating_sub(start))
+    }
+
+    /// Iterator over the missing stretches of values between consecutive
+    /// elements, as half-open ranges. Values below the first element or above
+    /// the last element aren't considered missing.
+    ///
+    /// This runs in `O(n)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let allocated_ids = SkipList::from(vec![1u32, 2, 3, 7, 8, 10]);
+    ///
+    /// let gaps: Vec<_> = allocated_ids.gaps().collect();
+    /// assert_eq!(gaps, vec![4..7, 9..10]);
+    /// ```
+    #[inline]
+    pub fn gaps(&self) -> Gaps<'_, T, fn(&T) -> Option<T>>
+    where
+        T: Successor,
+    {
+        Gaps::new(self.iter_all(), T::successor)
+    }
+
+    /// Like `gaps`, but with `successor` giving the next value after an element,
+    /// or `None` if there isn't one.
+    ///
+    /// This runs in `O(n)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// // Sequence numbers that go up in steps of 10.
+    /// let seen = SkipList::from(vec![10, 20, 50, 60]);
+    ///
+    /// let gaps: Vec<_> = seen.gaps_by(|seq| Some(seq + 10)).collect();
+    /// assert_eq!(gaps, vec![30..50]);
+    /// ```
+    #[inline]
+    pub fn gaps_by<F: FnMut(&T) -> Option<T>>(&self, successor: F) -> Gaps<'_, T, F> {
+        Gaps::new(self.iter_all(), successor)
+    }
+
+    /// Iterator over up to `k` elements on either side of `item`, along
+    /// with `item` itself if it's in the skiplist.
+    ///
+    /// `item` doesn't need to be in the skiplist; the neighbours are
+    /// the elements that would surround it.
+    ///
+    /// This runs in `O(logn + k)` time, and buffers at most `2k + 1` references.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from((0..100).step_by(10).collect::<Vec<_>>());
+    ///
+    /// let around: Vec<_> = sk.around(&50, 2).cloned().collect();
+    /// assert_eq!(around, vec![30, 40, 50, 60, 70]);
+    ///
+    /// let around: Vec<_> = sk.around(&55, 1).rev().cloned().collect();
+    /// assert_eq!(around, vec![60, 50]);
+    ///
+    /// let around: Vec<_> = sk.around(&0, 2).cloned().collect();
+    /// assert_eq!(around, vec![0, 10, 20]);
+    /// ```
+    pub fn around<Q>(&self, item: &Q, k: usize) -> Around<'_, T>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        // INVARIANT: path_to is a LeftBiasIterWidth, so there's always a
+        // node right of us.
+        let left = self.path_to(item).last().unwrap();
+        let present = unsafe { (*left.curr_node).right.unwrap().as_ref().value.eq_key(item) };
+        let rank = left.curr_width;
+        let start = rank.saturating_sub(k);
+        let end = (rank + present as usize + k).min(self.len());
+        Around::new(self.index_range(start..end).collect())
+    }
+
+    /// Iterator over an inclusive range of elements in the SkipList,
+    /// as defined by the `inclusive_fn`.
+    ///
+    /// This runs in `O(logn + k)`, where k is the width of range.
+    ///
+    /// As the skiplist is ordered in an ascending way, `inclusive_fn` should be
+    /// structured with the idea in mind that you're going to see the smallest elements
+    /// first. `inclusive_fn` should be designed to extract a *single contiguous
+    /// stretch of elements*.
+    ///
+    /// This iterator will find the smallest element in the range,
+    /// and then return elements until it finds the first element
+    /// larger than the range.
+    ///
+    /// If multiple ranges are desired, you can use `range_with` multiple times,
+    /// and simply use the last element of the previous run as the start of
+    /// the next run.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::{RangeHint, SkipList};
+    /// let mut sk = SkipList::new();
+    /// for item in 0..100 {
+    ///     sk.insert(item);
+    /// }
+    ///
+    /// let desired_range = sk.range_with(|&ele| {
+    ///     if ele <= 5 {
+    ///         RangeHint::SmallerThanRange
+    ///     } else if ele <= 30 {
+    ///         RangeHint::InRange
+    ///     } else {
+    ///         RangeHint::LargerThanRange
+    ///     }
+    /// });
+    /// for item in desired_range {
+    ///     println!("{}", item); // First prints 6, then 7, ... and finally 30.
+    /// }
+    /// ```
+    #[inline]
+    pub fn range_with<F>(&self, inclusive_fn: F) -> IterRangeWith<'_, T, F>
+    where
+        F: Fn(&T) -> RangeHint,
+    {
+        IterRangeWith::new(
+            unsafe { self.top_left.as_ref() },
+            inclusive_fn,
+            self.generation(),
+        )
+    }
+
+    /// Iterator over every element of `self` and `other` in ascending order,
+    /// without consuming either skiplist. Unlike `union`, duplicates are kept.
+    ///
+    /// This runs in `O(n + m)` time, and is lazy -- elements are produced
+    /// by walking the bottom rows of both skiplists side by side.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let left = SkipList::from(vec![1, 3, 5]);
+    /// let right = SkipList::from(vec![2, 3, 4]);
+    ///
+    /// let merged: Vec<_> = left.merge_iter(&right).cloned().collect();
+    /// assert_eq!(merged, vec![1, 2, 3, 3, 4, 5]);
+    /// ```
+    #[inline]
+    pub fn merge_iter<'a>(&'a self, other: &'a SkipList<T>) -> MergeIter<'a, T> {
+        MergeIter::new(self.iter_all(), other.iter_all())
+    }
+
+    /// Iterator over the union of `self` and `other`: every element found in
+    /// either skiplist, in ascending order and without duplicates.
+    ///
+    /// This runs in `O(n + m)` time, and is lazy -- elements are produced
+    /// by walking the bottom rows of both skiplists side by side.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let left = SkipList::from(vec![1, 3, 5]);
+    /// let right = SkipList::from(vec![2, 3, 4]);
+    ///
+    /// let union: Vec<_> = left.union(&right).cloned().collect();
+    /// assert_eq!(union, vec![1, 2, 3, 4, 5]);
+    /// ```
+    #[inline]
+    pub fn union<'a>(&'a self, other: &'a SkipList<T>) -> Union<'a, T> {
+        Union::new(self.iter_all(), other.iter_all())
+    }
+
+    /// Iterator over the intersection of `self` and `other`: every element found in
+    /// both skiplists, in ascending order.
+    ///
+    /// This walks the smaller skiplist and uses the upper levels of the larger
+    /// one to leap ahead, so it runs in `O(m log(n / m))` time where `m` is the
+    /// length of the smaller skiplist. Intersecting a small skiplist with a huge
+    /// one never touches most of the huge one.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let small = SkipList::from(vec![3, 50, 2000]);
+    /// let large = SkipList::from(0..1000);
+    ///
+    /// let common: Vec<_> = small.intersection(&large).cloned().collect();
+    /// assert_eq!(common, vec![3, 50]);
+    /// ```
+    #[inline]
+    pub fn intersection<'a>(&'a self, other: &'a SkipList<T>) -> Intersection<'a, T> {
+        if self.len() <= other.len() {
+            Intersection::new(self.iter_all(), other)
+        } else {
+            Intersection::new(other.iter_all(), self)
+        }
+    }
+
+    /// Iterator over the difference of `self` and `other`: every element in
+    /// `self` that isn't in `other`, in ascending order.
+    ///
+    /// This runs in `O(n log(m / n))` time, using the upper levels of `other`
+    /// to leap ahead between lookups.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let left = SkipList::from(vec![1, 3, 5]);
+    /// let right = SkipList::from(vec![2, 3, 4]);
+    ///
+    /// let diff: Vec<_> = left.difference(&right).cloned().collect();
+    /// assert_eq!(diff, vec![1, 5]);
+    /// ```
+    #[inline]
+    pub fn difference<'a>(&'a self, other: &'a SkipList<T>) -> Difference<'a, T> {
+        Difference::new(self.iter_all(), other)
+    }
+
+    /// Iterator over the symmetric difference of `self` and `other`: every element
+    /// in exactly one of the skiplists, in ascending order.
+    ///
+    /// This runs in `O(n + m)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let left = SkipList::from(vec![1, 3, 5]);
+    /// let right = SkipList::from(vec![2, 3, 4]);
+    ///
+    /// let diff: Vec<_> = left.symmetric_difference(&right).cloned().collect();
+    /// assert_eq!(diff, vec![1, 2, 4, 5]);
+    /// ```
+    #[inline]
+    pub fn symmetric_difference<'a>(
+        &'a self,
+        other: &'a SkipList<T>,
+    ) -> SymmetricDifference<'a, T> {
+        SymmetricDifference::new(self.iter_all(), other.iter_all())
+    }
+
+    /// Clear (deallocate all entries in) the skiplist.
+    ///
+    /// Returns the number of elements removed (length of bottom row).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::{RangeHint, SkipList};
+    /// let mut sk = SkipList::from(0..10);
+    /// assert_eq!(sk.clear(), 10);
+    /// assert_eq!(sk, SkipList::new());
+    ///
+    /// ```
+    pub fn clear(&mut self) -> usize {
+        let removed = self.len();
+        let generation = self.pool.generation().load(AtomicOrdering::Relaxed);
+        self.take_all();
+        // Carry on counting, so iterators made before clearing don't match.
+        self.pool
+            .generation()
+            .store(generation.wrapping_add(1), AtomicOrdering::Relaxed);
+        removed
+    }
+
+    /// Estimate how many bytes of heap memory the skiplist uses, from its
+    /// nodes and the storage it keeps around for reuse.
+    ///
+    /// This doesn't count heap memory owned by the elements themselves, like a
+    /// `String`'s buffer: use `memory_usage_with` for that.
+    ///
+    /// Runs in `O(n)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let mut sk = SkipList::new();
+    /// let empty = sk.memory_usage();
+    /// sk.extend(0..1000u64);
+    ///
+    /// // Every element takes a node and its value, at the very least.
+    /// assert!(sk.memory_usage() - empty > 1000 * 40);
+    /// ```
+    #[inline]
+    pub fn memory_usage(&self) -> usize {
+        self.memory_usage_with(|_| 0)
+    }
+
+    /// Like `memory_usage`, but also adds `value_bytes(item)` for every element,
+    /// for the heap memory it owns.
+    ///
+    /// Runs in `O(n)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let mut sk = SkipList::new();
+    /// sk.insert("a".repeat(1000));
+    ///
+    /// assert!(sk.memory_usage_with(String::capacity) >= sk.memory_usage() + 1000);
+    /// ```
+    pub fn memory_usage_with<F: FnMut(&T) -> usize>(&self, mut value_bytes: F) -> usize {
+        // Every row has its own pair of sentinels.
+        let mut bytes =
+            2 * self.height * std::mem::size_of::<Node<T>>() + self.pool.reserved_bytes();
+        // INVARIANT: There's always a bottom row, starting with NegInf.
+        let mut curr_node = self.iter_vertical().last().unwrap();
+        unsafe {
+            while let Some(right) = (*curr_node).right {
+                curr_node = right.as_ptr();
+                if let NodeValue::Value(header, _) = &(*curr_node).value {
+                    bytes += NodePool::tower_bytes(header.as_ref());
+                    bytes += value_bytes(header.as_ref().value());
+                }
+            }
+        }
+        bytes
+    }
+
+    /// Move every element into freshly packed storage, keeping each tower's height.
+    ///
+    /// After a long run of inserts and removals, towers end up scattered over
+    /// the heap, and the storage of removed elements lingers for reuse. This
+    /// copies the towers into one allocation, laid out in bottom-row order so
+    /// scans and searches walk through memory in order, and releases the old
+    /// storage.
+    ///
+    /// Runs in `O(n)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let mut sk = SkipList::from(0..1000);
+    /// sk.remove_many((0..1000).step_by(2));
+    /// sk.shrink_to_fit();
+    ///
+    /// assert_eq!(sk.len(), 500);
+    /// assert_eq!(sk.at_index(250), Some(&501));
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        let nodes = self.iter_levels().map(|row| row.count() - 1).sum();
+        self.repack(nodes, |_, _, _, height| height);
+    }
+
+    /// Rebuild the skiplist with perfectly balanced towers.
+    ///
+    /// Tower heights are normally random, so some searches end up a little
+    /// longer than others. This gives the element at (1-based) rank `i` a
+    /// tower one taller than the number of trailing zeros in `i`, so every
+    /// other element reaches the second level, every fourth the third, and
+    /// so on. It's meant for read-mostly lists that want the tightest
+    /// search constants after loading; later inserts pick random heights again.
+    ///
+    /// The balance follows the skiplist's probability: with `0.25`, every
+    /// fourth element reaches the second level instead. Probabilities that
+    /// aren't a power of two have no such pattern, so their towers get fresh
+    /// random heights. With `hashed_levels`, heights still come from each
+    /// element's hash, so the structure stays independent of insertion order.
+    ///
+    /// Like `shrink_to_fit`, the towers are packed into fresh storage in order.
+    ///
+    /// Runs in `O(n)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let mut sk = SkipList::from(0..1000);
+    /// sk.rebuild();
+    ///
+    /// assert_eq!(sk.len(), 1000);
+    /// assert_eq!(sk.index_of(&500), Some(500));
+    /// ```
+    pub fn rebuild(&mut self) {
+        if self.level_hash.is_none() && self.levels.balanced_level(1).is_some() {
+            let levels = &self.levels;
+            let nodes = (1..=self.len())
+                .filter_map(|rank| levels.balanced_level(rank))
+                .sum();
+            self.repack(nodes, |sk, _, index, _| {
+                // INVARIANT: We just checked the probability allows balancing.
+                sk.levels.balanced_level(index + 1).unwrap()
+            });
+        } else {
+            let nodes = self.levels.expected_nodes(self.len());
+            self.repack(nodes, |sk, item, _, _| sk.tower_height(item));
+        }
+    }
+
+    /// Freeze the skiplist into an immutable `FrozenSkipList`, for sharing
+    /// between threads once it's built.
+    ///
+    /// This calls `rebuild` first, so the frozen skiplist is packed into
+    /// one allocation with ideally balanced towers.
+    ///
+    /// Runs in `O(n)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let frozen = SkipList::from(0..10).freeze();
+    ///
+    /// assert!(frozen.contains(&5));
+    /// assert_eq!(frozen.at_index(3), Some(&3));
+    /// ```
+    pub fn freeze(mut self) -> FrozenSkipList<T> {
+        self.rebuild();
+        FrozenSkipList::new(self)
+    }
+
+    /// Move every element into freshly packed storage, in order.
+    ///
+    /// `height` is given the new skiplist and each element, with its index
+    /// and current tower height, and returns the height its new tower should
+    /// have. `nodes` is the (expected) total of those heights, so the storage
+    /// can be reserved up front.
+    fn repack<F>(&mut self, nodes: usize, mut height: F)
+    where
+        F: FnMut(&mut Self, &T, usize, usize) -> usize,
+    {
+        let generation = self.pool.generation().load(AtomicOrdering::Relaxed);
+        // The generator moves over, so its RNG carries on where it was.
+        let levels = std::mem::replace(&mut self.levels, LevelGenerator::new(0.5, 1, Some(0)));
+        let mut packed = SkipList::with_pool(levels, self.pool.fresh());
+        packed.level_hash = self.level_hash;
+        packed.pool.reserve(self.len(), nodes);
+        packed
+            .pool
+            .generation()
+            .store(generation.wrapping_add(1), AtomicOrdering::Relaxed);
+        let old = std::mem::replace(self, packed);
+        let mut edge = self.right_edge();
+        // The last node of `old` visited on every level, bottom first.
+        let mut left: Vec<*mut Node<T>> = old.iter_vertical().collect();
+        left.reverse();
+        let mut index = 0;
+        unsafe {
+            loop {
+                let bottom = (*left[0]).right.unwrap();
+                if bottom.as_ref().value.is_pos_inf() {
+                    break;
+                }
+                left[0] = bottom.as_ptr();
+                // The tower goes up as long as the next node on a level sits on top of it.
+                let mut old_height = 1;
+                while old_height < left.len() {
+                    let next = (*left[old_height]).right.unwrap();
+                    if next.as_ref().down != NonNull::new(left[old_height - 1]) {
+                        break;
+                    }
+                    left[old_height] = next.as_ptr();
+                    old_height += 1;
+                }
+                let item = (*bottom.as_ptr()).value.take_value();
+                let height = height(self, &item, index, old_height);
+                self.push_tower_with_edge(&mut edge, item, height);
+                index += 1;
+            }
+        }
+        #[cfg(debug_assertions)]
+        {
+            self.ensure_invariants()
+        }
+    }
+
+    #[inline]
+    pub(crate) fn generation(&self) -> Generation<'_> {
+        Generation::new(self.pool.generation())
+    }
+
+    #[inline]
+    fn path_to<'a, Q>(&self, item: &'a Q) -> LeftBiasIterWidth<'a, T, Q>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        LeftBiasIterWidth::new(self.top_left.as_ptr(), item)
+    }
+
+    /// Like `path_to`, but directed by a comparator. Returns the node on the
+    /// bottom row immediately left of where `f` finds `Equal`, with its index.
+    fn path_to_by<F: FnMut(&T) -> Ordering>(&self, f: &mut F) -> NodeWidth<T> {
+        let mut curr_node = self.top_left.as_ptr();
+        let mut index = 0;
+        unsafe {
+            loop {
+                // INVARIANT: We never move onto PosInf, so there's always a right.
+                let right = (*curr_node).right.unwrap();
+                let go_right = match right.as_ref().value.value() {
+                    Some(v) => f(v) == Ordering::Less,
+                    None => false,
+                };
+                if go_right {
+                    index += (*curr_node).width();
+                    curr_node = right.as_ptr();
+                } else if let Some(down) = (*curr_node).down {
+                    curr_node = down.as_ptr();
+                } else {
+                    return NodeWidth::new(curr_node, index);
+                }
+            }
+        }
+    }
+
+    /// The last node on every level, bottom first, paired with its index
+    /// (`curr_width`) in the bottom row. NegInf has index 0.
+    fn right_edge(&self) -> Vec<NodeWidth<T>> {
+        let mut edge = Vec::with_capacity(self.height);
+        let mut curr_node = self.top_left.as_ptr();
+        let mut curr_width = 0;
+        unsafe {
+            loop {
+                while let Some(right) = (*curr_node).right {
+                    if right.as_ref().value.is_pos_inf() {
+                        break;
+                    }
+                    curr_width += (*curr_node).width();
+                    curr_node = right.as_ptr();
+                }
+                edge.push(NodeWidth::new(curr_node, curr_width));
+                match (*curr_node).down {
+                    Some(down) => curr_node = down.as_ptr(),
+                    None => break,
+                }
+            }
+        }
+        edge.reverse();
+        edge
+    }
+
+    /// Append `item` to the end of the skiplist using the right edge from
+    /// `right_edge`, which is kept up to date.
+    ///
+    /// `item` must be larger than every element in the skiplist.
+    fn push_back_with_edge(&mut self, edge: &mut Vec<NodeWidth<T>>, item: T) {
+        let height = self.tower_height(&item);
+        self.push_tower_with_edge(edge, item, height);
+    }
+
+    /// Like `push_back_with_edge`, but with a tower of `height` nodes.
+    fn push_tower_with_edge(&mut self, edge: &mut Vec<NodeWidth<T>>, item: T, height: usize) {
+        if height + 1 > self.height {
+            self.add_levels(height + 1 - self.height);
+            *edge = self.right_edge();
+        }
+        let index = self.len + 1;
+        let tower = self.pool.make_tower(item, height).as_ptr();
+        for (level, edge_node) in edge.iter_mut().enumerate() {
+            unsafe {
+                let left = edge_node.curr_node;
+                if level < height {
+                    // Everything right of us is PosInf, so the new node has width 1.
+                    let new_node = tower.add(level);
+                    (*new_node).set_width(1);
+                    (*new_node).right = (*left).right;
+                    (*left).right = NonNull::new(new_node);
+                    (*left).set_width(index - edge_node.curr_width);
+                    *edge_node = NodeWidth::new(new_node, index);
+                } else {
+                    (*left).set_width((*left).width() + 1);
+                }
+            }
+        }
+        self.len += 1;
+    }
+
+    /// Pick the height of a new tower for `item`.
+    #[inline]
+    fn tower_height(&mut self, item: &T) -> usize {
+        match self.level_hash {
+            Some(hash) => self.levels.level_from_hash(hash(item)),
+            None => self.levels.next_level(),
+        }
+    }
+
+    /// Collect `path_to(item)`. Only searches a user asked for should
+    /// `observe`, so our own re-searches don't skew auto-tuning.
+    #[inline]
+    fn search_path<Q>(&mut self, item: &Q, observe: bool) -> Vec<NodeWidth<T>>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        let mut search = self.path_to(item);
+        let path: Vec<_> = search.by_ref().collect();
+        if observe {
+            self.levels
+                .observe_search(path.len() + search.moves_right(), self.len());
+        }
+        path
+    }
+
+    #[cfg(debug_assertions)]
+    fn ensure_columns_same_value(&self) {
+        let mut left_row = self.top_left;
         let mut curr_node = self.top_left;
         unsafe {
             loop {
@@ -1098,283 +3671,1467 @@ impl<T: PartialOrd + Clone> SkipList<T> {
                     let curr_value = &curr_node.as_ref().value;
                     let mut curr_down = curr_node;
                     while let Some(down) = curr_down.as_ref().down {
-                        assert!(&down.as_ref().value == curr_value);
+                        // Every node of a tower points at the same value.
+                        let same = match (&down.as_ref().value, curr_value) {
+                            (NodeValue::Value(below, _), NodeValue::Value(above, _)) => {
+                                below == above
+                            }
+                            (below, above) => {
+                                std::mem::discriminant(below) == std::mem::discriminant(above)
+                            }
+                        };
+                        assert!(same);
                         curr_down = down;
                     }
-                    curr_node = right;
+                    curr_node = right;
+                }
+                // Now, move a an entire row down.
+                if let Some(down) = left_row.as_ref().down {
+                    left_row = down;
+                    curr_node = left_row;
+                } else {
+                    break;
+                }
+            }
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    fn ensure_rows_ordered(&self) {
+        let mut left_row = self.top_left;
+        let mut curr_node = self.top_left;
+        unsafe {
+            loop {
+                while let Some(right) = curr_node.as_ref().right {
+                    assert!(curr_node.as_ref().value < right.as_ref().value);
+                    curr_node = right;
+                }
+                if let Some(down) = left_row.as_ref().down {
+                    left_row = down;
+                    curr_node = left_row;
+                } else {
+                    break;
+                }
+            }
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    fn ensure_rows_sum_len(&self) {
+        let mut left_row = self.top_left;
+        let mut curr_node = self.top_left;
+        unsafe {
+            loop {
+                let mut curr_sum = 0;
+                while let Some(right) = curr_node.as_ref().right {
+                    curr_sum += curr_node.as_ref().width();
+                    curr_node = right;
+                }
+                if let Some(down) = left_row.as_ref().down {
+                    assert_eq!(self.len(), curr_sum - 1);
+                    left_row = down;
+                    curr_node = left_row;
+                } else {
+                    break;
+                }
+            }
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    fn ensure_invariants(&self) {
+        unsafe {
+            assert!(self
+                .top_left
+                .as_ref()
+                .right
+                .unwrap()
+                .as_ref()
+                .value
+                .is_pos_inf())
+        }
+        self.ensure_rows_ordered();
+        self.ensure_columns_same_value();
+        self.ensure_rows_sum_len();
+    }
+}
+
+impl<T: Copy + Ord> SkipList<T> {
+    /// Test if `item` is in the skiplist, using a search loop specialised
+    /// for small `Copy` keys like integers.
+    ///
+    /// Behaves exactly like `contains`, but compares values loaded by
+    /// value with `Ord` rather than through `PartialOrd` on references,
+    /// which lets the compiler keep the search loop branch-light.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipList;
+    /// let sk = SkipList::from(0..100u64);
+    ///
+    /// assert!(sk.contains_copied(42));
+    /// assert!(!sk.contains_copied(100));
+    /// ```
+    #[inline]
+    pub fn contains_copied(&self, item: T) -> bool {
+        let left = self.left_of_copied(item);
+        unsafe {
+            match (*left)
+                .right
+                .unwrap_or_else(|| unreachable!())
+                .as_ref()
+                .value
+            {
+                NodeValue::Value(tower, _) => *(*tower.as_ptr()).value() == item,
+                _ => false,
+            }
+        }
+    }
+
+    /// The bottom-row node directly left of where `item` is or would be.
+    #[inline]
+    fn left_of_copied(&self, item: T) -> *mut Node<T> {
+        let mut curr_node = self.top_left.as_ptr();
+        unsafe {
+            loop {
+                // INVARIANT: every row ends in PosInf, which is never passed.
+                let right = (*curr_node).right.unwrap_or_else(|| unreachable!());
+                let down = (*curr_node).down;
+                if let Some(down) = down {
+                    prefetch(down.as_ptr());
+                }
+                let advance = match right.as_ref().value {
+                    NodeValue::Value(tower, _) => *(*tower.as_ptr()).value() < item,
+                    _ => false,
+                };
+                if advance {
+                    curr_node = right.as_ptr();
+                } else if let Some(down) = down {
+                    curr_node = down.as_ptr();
+                } else {
+                    return curr_node;
+                }
+            }
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use crate::{Median, Node, SkipList};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::HashSet;
+    use std::ops::Bound;
+
+    #[test]
+    fn insert_no_panic() {
+        let mut sl = SkipList::new();
+        for i in &[10, 30, 50, 5, 0, 3] {
+            sl.insert(*i);
+            assert!(sl.contains(i));
+        }
+        #[cfg(debug_assertions)]
+        sl.ensure_invariants();
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut sl = SkipList::new();
+        sl.insert(0usize);
+        assert!(sl.remove(&0));
+        assert!(!sl.remove(&0));
+        assert!(!sl.contains(&0));
+        sl.insert(0);
+        sl.insert(1);
+        sl.insert(2);
+        assert!(sl.remove(&1));
+        assert!(!sl.contains(&1));
+        sl.remove(&2);
+        assert!(!sl.contains(&2));
+    }
+
+    #[test]
+    fn test_inclusive_range() {
+        let mut sl = SkipList::new();
+        let values: &[i32] = &[10, 30, 50, 5, 0, 3];
+        for i in &[10, 30, 50, 5, 0, 3] {
+            sl.insert(*i);
+            assert!(sl.contains(i));
+        }
+        let lower = 3;
+        let upper = 30;
+        let v: HashSet<i32> = sl.range(&lower, &upper).cloned().collect();
+        for expected_value in values.iter().filter(|&&i| lower <= i && i <= upper) {
+            assert!(v.contains(expected_value));
+        }
+        let right_empty: HashSet<i32> = sl.range(&100, &1000).cloned().collect();
+        assert!(right_empty.is_empty());
+
+        let left_empty: HashSet<i32> = sl.range(&-2, &-1).cloned().collect();
+        assert!(left_empty.is_empty());
+
+        // Excessive range
+        let lower = -10;
+        let upper = 1000;
+        let v: HashSet<i32> = sl.range(&lower, &upper).cloned().collect();
+        for expected_value in values.iter().filter(|&&i| lower <= i && i <= upper) {
+            assert!(v.contains(expected_value));
+        }
+    }
+
+    #[test]
+    fn test_len() {
+        let mut sl = SkipList::new();
+        assert_eq!(sl.len(), 0);
+        assert!(sl.is_empty());
+        sl.insert(0);
+        assert_eq!(sl.len(), 1);
+        assert!(!sl.is_empty());
+        sl.insert(0);
+        assert_eq!(sl.len(), 1);
+        sl.insert(1);
+        assert_eq!(sl.len(), 2);
+        sl.remove(&1);
+        assert_eq!(sl.len(), 1);
+        sl.remove(&1);
+        assert_eq!(sl.len(), 1);
+        sl.remove(&0);
+        assert_eq!(sl.len(), 0);
+        sl.remove(&0);
+        assert_eq!(sl.len(), 0);
+    }
+
+    #[test]
+    fn test_eq() {
+        let mut s0 = SkipList::new();
+        let mut s1 = SkipList::new();
+        assert!(s0 == s1);
+        s0.insert(0);
+        assert!(s0 != s1);
+        s1.insert(1);
+        assert!(s0 != s1);
+        s0.insert(1);
+        s1.insert(0);
+        assert!(s0 == s1);
+        s0.insert(2);
+        s0.insert(3);
+        assert!(s0 != s1);
+    }
+
+    #[test]
+    fn test_from() {
+        let values = vec![1usize, 2, 3];
+        let sk = SkipList::from(values.clone());
+        assert_eq!(sk.iter_all().cloned().collect::<Vec<_>>(), values);
+        let values: Vec<usize> = (0..10).collect();
+        let sk = SkipList::from(0..10);
+        assert_eq!(sk.iter_all().cloned().collect::<Vec<_>>(), values);
+    }
+
+    #[test]
+    fn test_index_of() {
+        let mut sk = SkipList::new();
+        sk.insert(1);
+        sk.insert(2);
+        sk.insert(3);
+
+        assert_eq!(sk.index_of(&1), Some(0));
+        assert_eq!(sk.index_of(&2), Some(1));
+        assert_eq!(sk.index_of(&3), Some(2));
+        assert_eq!(sk.index_of(&999), None);
+        let sk: SkipList<i32> = SkipList::new();
+        assert_eq!(sk.index_of(&0), None);
+        assert_eq!(sk.index_of(&999), None);
+    }
+
+    #[test]
+    fn test_at_index() {
+        let sk = SkipList::from(0..10);
+        for i in 0..10 {
+            assert_eq!(Some(&i), sk.at_index(i));
+        }
+        assert_eq!(None, sk.at_index(11));
+
+        let mut sk = SkipList::new();
+        sk.insert('a');
+        sk.insert('b');
+        sk.insert('c');
+        assert_eq!(Some(&'a'), sk.at_index(0));
+        assert_eq!(Some(&'b'), sk.at_index(1));
+        assert_eq!(Some(&'c'), sk.at_index(2));
+        assert_eq!(None, sk.at_index(3));
+
+        assert_eq!('a', sk[0]);
+        assert_eq!('b', sk[1]);
+        assert_eq!('c', sk[2]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bad_index() {
+        let sk = SkipList::from(0..10);
+        let _ = sk[sk.len()];
+    }
+
+    #[test]
+    fn test_pop_max() {
+        let mut sk = SkipList::from(0..10);
+        assert_eq!(Some(&7), sk.at_index(7));
+        assert_eq!(vec![7, 8, 9], sk.pop_max(3));
+        assert_eq!(vec![6], sk.pop_max(1));
+        assert_eq!(vec![4, 5], sk.pop_max(2));
+        assert_eq!(vec![0, 1, 2, 3], sk.pop_max(5));
+        let mut sk = SkipList::from(0..3);
+        assert_eq!(vec![2], sk.pop_max(1));
+        let mut sk: SkipList<u32> = SkipList::new();
+        let v: Vec<u32> = Vec::new();
+        assert_eq!(v, sk.pop_max(1));
+    }
+
+    #[test]
+    fn test_pop_min() {
+        let mut sk = SkipList::from(0..10);
+        assert_eq!(vec![0, 1, 2], sk.pop_min(3));
+        assert_eq!(vec![3], sk.pop_min(1));
+        assert_eq!(vec![4, 5], sk.pop_min(2));
+        assert_eq!(vec![6, 7, 8, 9], sk.pop_min(5));
+        let v: Vec<u32> = Vec::new();
+        assert_eq!(v, sk.pop_min(1));
+    }
+
+    #[test]
+    fn test_clone() {
+        let sk = SkipList::from(0..30);
+        let clone = sk.clone();
+        assert_eq!(sk, clone);
+        assert!(!std::ptr::eq(&sk, &clone));
+        // Empty case
+        let sk = SkipList::from(0..0);
+        let clone = sk.clone();
+        assert_eq!(
+            sk, clone,
+            "Empty skiplists should clone nicely, {:?} != {:?}",
+            sk, clone
+        );
+    }
+
+    #[test]
+    fn test_peek() {
+        let sk = SkipList::from(0..10);
+        assert_eq!(Some(&0), sk.peek_first());
+        assert_eq!(Some(&9), sk.peek_last());
+    }
+
+    #[test]
+    fn test_ceiling_floor() {
+        let sk = SkipList::from((0..100).step_by(10).collect::<Vec<i32>>());
+        for i in -5i32..105 {
+            let ceiling = (i + 9).div_euclid(10) * 10;
+            let floor = i.div_euclid(10) * 10;
+            assert_eq!(sk.ceiling(&i), Some(&ceiling).filter(|&&c| c <= 90));
+            assert_eq!(sk.floor(&i), Some(&floor.min(90)).filter(|&&f| f >= 0));
+        }
+        let empty: SkipList<i32> = SkipList::new();
+        assert_eq!(empty.ceiling(&0), None);
+        assert_eq!(empty.floor(&0), None);
+    }
+
+    #[test]
+    fn test_around() {
+        let sk = SkipList::from(0..20);
+        for k in 0..5 {
+            for i in -3i32..23 {
+                let mut expected: Vec<i32> = (0..20).filter(|&e| e < i).rev().take(k).collect();
+                expected.reverse();
+                expected.extend((0..20).filter(|&e| e == i));
+                expected.extend((0..20).filter(|&e| e > i).take(k));
+                let around = sk.around(&i, k);
+                assert_eq!(around.len(), expected.len());
+                assert_eq!(around.cloned().collect::<Vec<_>>(), expected);
+            }
+        }
+        let empty: SkipList<i32> = SkipList::new();
+        assert_eq!(empty.around(&0, 3).next(), None);
+    }
+
+    #[test]
+    fn test_rank() {
+        let values = vec![3, 8, 9, 15, 22];
+        let sk = SkipList::from(values.clone());
+        for i in 0..25 {
+            assert_eq!(sk.rank(&i), values.iter().filter(|&&v| v < i).count());
+        }
+        assert_eq!(SkipList::<i32>::new().rank(&0), 0);
+    }
+
+    #[test]
+    fn test_count_range() {
+        let values: Vec<i32> = (0..60).step_by(3).collect();
+        let sk = SkipList::from(values.clone());
+        for start in -2..62 {
+            for end in -2..62 {
+                assert_eq!(
+                    sk.count_range(&start, &end),
+                    sk.range(&start, &end).count(),
+                    "count_range({}, {})",
+                    start,
+                    end
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_contains_any_in() {
+        let sk = SkipList::from((0..60).step_by(3).collect::<Vec<i32>>());
+        for start in -2..62 {
+            for end in -2..62 {
+                assert_eq!(
+                    sk.contains_any_in(&start, &end),
+                    sk.range(&start, &end).next().is_some()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(SkipList::<i32>::new().median(), None);
+        assert_eq!(SkipList::from(vec![7]).median(), Some(Median::Single(&7)));
+        assert_eq!(
+            SkipList::from(vec![7, 9]).median(),
+            Some(Median::Pair(&7, &9))
+        );
+        for len in 1..50 {
+            let sk = SkipList::from(0..len);
+            let (low, high) = ((len - 1) / 2, len / 2);
+            let expected = if len % 2 == 1 {
+                Median::Single(&high)
+            } else {
+                Median::Pair(&low, &high)
+            };
+            assert_eq!(sk.median(), Some(expected));
+        }
+    }
+
+    #[test]
+    fn test_from_end() {
+        let values: Vec<i32> = (0..20).collect();
+        let sk = SkipList::from(values.clone());
+        for i in 0..25 {
+            assert_eq!(sk.at_index_from_end(i), values.iter().rev().nth(i));
+        }
+        let last: Vec<_> = sk.index_range_from_end(..5).cloned().collect();
+        assert_eq!(last, vec![15, 16, 17, 18, 19]);
+        let last: Vec<_> = sk.index_range_from_end(1..5).cloned().collect();
+        assert_eq!(last, vec![15, 16, 17, 18]);
+        let last: Vec<_> = sk.index_range_from_end(18..).cloned().collect();
+        assert_eq!(last, vec![0, 1]);
+        assert_eq!(sk.index_range_from_end(20..30).count(), 0);
+        assert_eq!(sk.index_range_from_end(5..5).count(), 0);
+        assert!(sk.index_range_from_end(..=usize::MAX).eq(values.iter()));
+        assert_eq!(sk.index_range_from_end(usize::MAX..=usize::MAX).count(), 0);
+        assert_eq!(
+            sk.index_range_from_end((Bound::Excluded(usize::MAX), Bound::Unbounded))
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_select_many() {
+        let values: Vec<u32> = (0..500).map(|i| i * 3).collect();
+        let sk = SkipList::from(values.clone());
+        let indices: Vec<usize> = vec![0, 1, 1, 2, 50, 51, 250, 251, 499];
+        let expected: Vec<&u32> = indices.iter().map(|&i| &values[i]).collect();
+        assert_eq!(sk.select_many(&indices), expected);
+        let all: Vec<usize> = (0..500).collect();
+        assert_eq!(sk.select_many(&all), values.iter().collect::<Vec<_>>());
+        assert!(sk.select_many(&[]).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_select_many_unsorted() {
+        SkipList::from(0..10).select_many(&[3, 2]);
+    }
+
+    #[test]
+    fn test_iter_max_min_k() {
+        let sk = SkipList::from(0..50);
+        for k in 0..60 {
+            let max_k: Vec<_> = sk.iter_max_k(k).cloned().collect();
+            let min_k: Vec<_> = sk.iter_min_k(k).cloned().collect();
+            assert_eq!(max_k, (50 - k.min(50) as i32..50).collect::<Vec<_>>());
+            assert_eq!(min_k, (0..k.min(50) as i32).collect::<Vec<_>>());
+        }
+        let empty: SkipList<i32> = SkipList::new();
+        assert_eq!(empty.iter_max_k(3).next(), None);
+        assert_eq!(empty.iter_min_k(3).next(), None);
+    }
+
+    #[test]
+    fn test_choose() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let sk = SkipList::from(0..10);
+        let mut seen = [0; 10];
+        for _ in 0..1000 {
+            seen[*sk.choose(&mut rng).unwrap() as usize] += 1;
+        }
+        assert!(seen.iter().all(|&count| count > 50));
+        assert_eq!(SkipList::<i32>::new().choose(&mut rng), None);
+    }
+
+    #[test]
+    fn test_sample() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let sk = SkipList::from(0..10);
+        let mut seen = [0; 10];
+        for _ in 0..1000 {
+            let sample = sk.sample(&mut rng, 3);
+            assert_eq!(sample.len(), 3);
+            assert!(sample.windows(2).all(|pair| pair[0] < pair[1]));
+            for &item in sample {
+                seen[item as usize] += 1;
+            }
+        }
+        assert!(seen.iter().all(|&count| count > 200));
+        assert_eq!(sk.sample(&mut rng, 20).len(), 10);
+        assert!(SkipList::<i32>::new().sample(&mut rng, 3).is_empty());
+    }
+
+    #[test]
+    fn test_percentile_rank() {
+        let sk = SkipList::from(vec![1, 2, 3, 4]);
+        assert_eq!(sk.percentile_rank(&0), 0.0);
+        assert_eq!(sk.percentile_rank(&2), 0.25);
+        assert_eq!(sk.percentile_rank(&3), 0.5);
+        assert_eq!(sk.percentile_rank(&9), 1.0);
+        assert_eq!(SkipList::<i32>::new().percentile_rank(&0), 0.0);
+    }
+
+    #[test]
+    fn test_borrowed_range_queries() {
+        let sk = SkipList::from(vec!["b".to_string(), "d".to_string(), "f".to_string()]);
+        assert_eq!(sk.percentile_rank("d"), 1.0 / 3.0);
+        assert_eq!(sk.count_range("b", "d"), 2);
+        assert_eq!(sk.count_range("c", "e"), 1);
+        assert!(sk.contains_any_in("c", "d"));
+        assert!(!sk.contains_any_in("g", "z"));
+        let around: Vec<_> = sk.around("d", 1).map(String::as_str).collect();
+        assert_eq!(around, vec!["b", "d", "f"]);
+        let around: Vec<_> = sk.around("e", 0).collect();
+        assert!(around.is_empty());
+    }
+
+    #[test]
+    fn test_contains_all_any() {
+        let sk = SkipList::from((0..100).step_by(2).collect::<Vec<i32>>());
+        let evens: Vec<i32> = (0..100).step_by(2).collect();
+        assert!(sk.contains_all(&evens));
+        assert!(sk.contains_all(evens.iter().rev()));
+        assert!(sk.contains_all(Vec::<i32>::new()));
+        assert!(!sk.contains_all(vec![0, 2, 3]));
+        assert!(sk.contains_any(vec![1, 3, 98]));
+        assert!(sk.contains_any(vec![98, 3, 1]));
+        assert!(!sk.contains_any((0..100).step_by(2).map(|i| i + 1)));
+        assert!(!sk.contains_any(Vec::<i32>::new()));
+    }
+
+    #[test]
+    fn test_gaps() {
+        let sk = SkipList::from(vec![0u8, 1, 5, 6, 7, 200, 255]);
+        assert_eq!(sk.gaps().collect::<Vec<_>>(), vec![2..5, 8..200, 201..255]);
+        assert_eq!(SkipList::from(0..10).gaps().count(), 0);
+        assert_eq!(SkipList::from(vec![3]).gaps().count(), 0);
+        assert_eq!(SkipList::<i32>::new().gaps().count(), 0);
+        let sk = SkipList::from(vec![1, 2, 4, 8]);
+        let gaps: Vec<_> = sk.gaps_by(|i| Some(i * 2)).collect();
+        assert_eq!(gaps, vec![]);
+        let gaps: Vec<_> = sk.gaps_by(|i| Some(i + 1)).collect();
+        assert_eq!(gaps, vec![3..4, 5..8]);
+    }
+
+    #[test]
+    fn test_iter_with_index() {
+        let sk = SkipList::from((0..100).map(|i| i * 7).collect::<Vec<_>>());
+        let mut count = 0;
+        for (index, item) in sk.iter_with_index() {
+            assert_eq!(sk.index_of(item), Some(index));
+            assert_eq!(sk.at_index(index), Some(item));
+            count += 1;
+        }
+        assert_eq!(count, 100);
+    }
+
+    #[test]
+    fn test_iter_desc() {
+        for len in 0..100 {
+            let sk = SkipList::from(0..len);
+            let expected: Vec<i32> = (0..len).rev().collect();
+            assert_eq!(sk.iter_desc().cloned().collect::<Vec<_>>(), expected);
+        }
+        // Meeting in the middle from both ends.
+        let sk = SkipList::from(0..101);
+        let mut iter = sk.iter_all();
+        let mut front = Vec::new();
+        let mut back = Vec::new();
+        loop {
+            match (iter.next(), iter.next_back()) {
+                (Some(f), Some(b)) => {
+                    front.push(*f);
+                    back.push(*b);
+                }
+                (Some(f), None) => front.push(*f),
+                _ => break,
+            }
+            assert_eq!(iter.size_hint().0, 101 - front.len() - back.len());
+        }
+        back.reverse();
+        front.extend(back);
+        assert_eq!(front, (0..101).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_range_rev() {
+        let sk = SkipList::from((0..60).step_by(3).collect::<Vec<i32>>());
+        for start in -2..62 {
+            for end in -2..62 {
+                let mut expected: Vec<_> = sk.range(&start, &end).collect();
+                expected.reverse();
+                assert_eq!(sk.range(&start, &end).rev().collect::<Vec<_>>(), expected);
+                // Alternate between both ends, and make sure they meet exactly.
+                let mut range = sk.range(&start, &end);
+                let mut front: Vec<&i32> = Vec::new();
+                let mut back: Vec<&i32> = Vec::new();
+                loop {
+                    let f = range.next();
+                    let b = range.next_back();
+                    front.extend(f);
+                    back.extend(b);
+                    if f.is_none() && b.is_none() {
+                        break;
+                    }
                 }
-                // Now, move a an entire row down.
-                if let Some(down) = left_row.as_ref().down {
-                    left_row = down;
-                    curr_node = left_row;
+                front.extend(back.into_iter().rev());
+                expected.reverse();
+                assert_eq!(front, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_borrowed_lookups() {
+        let mut sk: SkipList<String> = ["apple", "banana", "cherry"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert!(sk.contains("banana"));
+        assert!(!sk.contains("durian"));
+        assert_eq!(sk.index_of("cherry"), Some(2));
+        assert_eq!(sk.get("apple"), Some(&"apple".to_string()));
+        assert_eq!(sk.get("avocado"), None);
+        assert_eq!(sk.rank("b"), 1);
+        assert_eq!(sk.ceiling("b").map(String::as_str), Some("banana"));
+        assert_eq!(sk.floor("b").map(String::as_str), Some("apple"));
+        assert!(sk.remove("banana"));
+        assert!(!sk.remove("banana"));
+        assert_eq!(sk.len(), 2);
+    }
+
+    #[test]
+    fn test_range_bounds() {
+        use std::ops::Bound::{self, Excluded, Included, Unbounded};
+        use std::ops::RangeBounds;
+        let values: Vec<i32> = (0..30).step_by(3).collect();
+        let sk = SkipList::from(values.clone());
+        let bounds = |i| vec![Included(i), Excluded(i), Unbounded];
+        for start in -1..31 {
+            for end in -1..31 {
+                for &start_bound in &bounds(start) {
+                    for &end_bound in &bounds(end) {
+                        let range: (Bound<i32>, Bound<i32>) = (start_bound, end_bound);
+                        let expected: Vec<&i32> =
+                            values.iter().filter(|v| range.contains(v)).collect();
+                        let got: Vec<&i32> = sk.range_bounds(range).collect();
+                        assert_eq!(got, expected, "{:?}", range);
+                        let mut rev: Vec<&i32> = sk.range_bounds(range).rev().collect();
+                        rev.reverse();
+                        assert_eq!(rev, expected, "{:?}", range);
+                    }
+                }
+            }
+        }
+        let words: SkipList<String> = ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(words.range("a", "b").count(), 2);
+    }
+
+    #[test]
+    fn test_iter_from() {
+        let sk: SkipList<i32> = (0..100).map(|i| i * 2).collect();
+        for start in -1..201 {
+            let expected: Vec<_> = sk.iter_all().filter(|&&v| v >= start).collect();
+            assert_eq!(sk.iter_from(&start).collect::<Vec<_>>(), expected);
+        }
+        let words: SkipList<String> = ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(words.iter_from("b").collect::<Vec<_>>(), vec!["b", "c"]);
+    }
+
+    #[test]
+    fn test_iter_levels() {
+        let sk = SkipList::from(0..200u32);
+        let levels: Vec<Vec<_>> = sk.iter_levels().map(|level| level.collect()).collect();
+        assert_eq!(levels.len(), sk.height);
+        assert_eq!(levels[0], vec![(None, sk.len() + 1)]);
+        let bottom: Vec<_> = levels
+            .last()
+            .unwrap()
+            .iter()
+            .filter_map(|(v, _)| *v)
+            .collect();
+        assert_eq!(bottom, sk.iter_all().collect::<Vec<_>>());
+        // Every row is a subset of the one below it
+        for pair in levels.windows(2) {
+            assert!(pair[0].len() <= pair[1].len());
+            for (value, _) in &pair[0] {
+                assert!(pair[1].iter().any(|(below, _)| below == value));
+            }
+        }
+    }
+
+    #[test]
+    fn test_with_seed() {
+        let build = |seed| {
+            let mut sk = SkipList::with_seed(seed);
+            let mut rng = StdRng::seed_from_u64(3116);
+            for _ in 0..2000 {
+                let item = rng.gen_range(0u32, 500);
+                if rng.gen_bool(0.3) {
+                    sk.remove(&item);
                 } else {
-                    break;
+                    sk.insert(item);
                 }
             }
+            sk
+        };
+        let levels = |sk: &SkipList<u32>| -> Vec<Vec<(Option<u32>, usize)>> {
+            sk.iter_levels()
+                .map(|level| level.map(|(v, width)| (v.copied(), width)).collect())
+                .collect()
+        };
+        let (left, right) = (build(1), build(1));
+        assert_eq!(levels(&left), levels(&right));
+        assert_eq!(levels(&left.clone()), levels(&right.clone()));
+        assert_ne!(levels(&left), levels(&build(2)));
+    }
+
+    #[test]
+    fn test_windows_chunks() {
+        let values: Vec<u32> = (0..23).collect();
+        let sk = SkipList::from(values.clone());
+        for size in 1..30 {
+            let mut windows = sk.windows(size);
+            let expected: Vec<Vec<&u32>> =
+                values.windows(size).map(|w| w.iter().collect()).collect();
+            assert_eq!(windows.len(), expected.len());
+            windows.next();
+            assert_eq!(windows.len(), expected.len().saturating_sub(1));
+            assert_eq!(sk.windows(size).collect::<Vec<_>>(), expected);
+
+            let expected: Vec<Vec<&u32>> =
+                values.chunks(size).map(|c| c.iter().collect()).collect();
+            assert_eq!(sk.chunks(size).len(), expected.len());
+            assert_eq!(sk.chunks(size).collect::<Vec<_>>(), expected);
         }
+        assert_eq!(SkipList::<u32>::new().windows(1).next(), None);
     }
 
-    #[cfg(debug_assertions)]
-    fn ensure_rows_ordered(&self) {
-        let mut left_row = self.top_left;
-        let mut curr_node = self.top_left;
+    #[test]
+    fn test_merge_iter() {
+        let mut rng = StdRng::seed_from_u64(0x3090);
+        for _ in 0..20 {
+            let left: Vec<u32> = (0..rng.gen_range(0, 50))
+                .map(|_| rng.gen_range(0, 40))
+                .collect();
+            let right: Vec<u32> = (0..rng.gen_range(0, 50))
+                .map(|_| rng.gen_range(0, 40))
+                .collect();
+            let (left, right) = (SkipList::from(left), SkipList::from(right));
+            let mut expected: Vec<_> = left.iter_all().chain(right.iter_all()).collect();
+            expected.sort();
+            let merged = left.merge_iter(&right);
+            assert_eq!(merged.len(), expected.len());
+            assert_eq!(merged.collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn test_find_by() {
+        let sk: SkipList<(u32, String)> = (0..50).map(|i| (i * 2, i.to_string())).collect();
+        for key in 0..100 {
+            let found = sk.find_by(|(k, _)| k.cmp(&key));
+            let index = sk.index_of_by(|(k, _)| k.cmp(&key));
+            if key % 2 == 0 {
+                assert_eq!(found, Some(&(key, (key / 2).to_string())));
+                assert_eq!(index, Some(key as usize / 2));
+            } else {
+                assert_eq!(found, None);
+                assert_eq!(index, None);
+            }
+        }
+        let empty: SkipList<u32> = SkipList::new();
+        assert_eq!(empty.find_by(|k| k.cmp(&0)), None);
+    }
+
+    #[test]
+    fn test_towers_are_contiguous() {
+        let mut sk = SkipList::with_capacity(50);
+        sk.extend((0..50).rev());
+        sk.extend(50..100);
+        sk.extend_from_sorted(100..150);
         unsafe {
-            loop {
-                while let Some(right) = curr_node.as_ref().right {
-                    assert!(curr_node.as_ref().value < right.as_ref().value);
-                    curr_node = right;
-                }
-                if let Some(down) = left_row.as_ref().down {
-                    left_row = down;
-                    curr_node = left_row;
-                } else {
-                    break;
+            let mut row = sk.top_left.as_ptr();
+            while let Some(down) = (*row).down {
+                let mut node = (*row).right.unwrap().as_ptr();
+                while (*node).value.value().is_some() {
+                    let below = (*node).down.unwrap().as_ptr();
+                    assert_eq!(node, below.add(1));
+                    node = (*node).right.unwrap().as_ptr();
                 }
+                row = down.as_ptr();
+            }
+        }
+    }
+
+    #[test]
+    fn test_value_stored_once() {
+        use std::rc::Rc;
+        let shared = Rc::new(());
+        let mut sk = SkipList::new();
+        for i in 0..200 {
+            sk.insert((i, Rc::clone(&shared)));
+        }
+        // However tall the towers, each element's value exists once.
+        assert_eq!(Rc::strong_count(&shared), 201);
+        for i in (0..200).step_by(2) {
+            assert!(sk.remove(&(i, Rc::clone(&shared))));
+        }
+        assert_eq!(Rc::strong_count(&shared), 101);
+        drop(sk);
+        assert_eq!(Rc::strong_count(&shared), 1);
+    }
+
+    #[test]
+    fn test_non_clone_values() {
+        #[derive(Debug, PartialEq, PartialOrd)]
+        struct Handle(u32);
+
+        let mut sk: SkipList<Handle> = (0..100).map(Handle).collect();
+        assert!(sk.insert(Handle(100)));
+        assert!(!sk.insert(Handle(5)));
+        assert!(sk.remove(&Handle(5)));
+        assert_eq!(sk.take(&Handle(6)), Some(Handle(6)));
+        assert_eq!(sk.remove_at(0), Some(Handle(0)));
+        assert_eq!(sk.pop_min(2), vec![Handle(1), Handle(2)]);
+        assert_eq!(sk.pop_max(2), vec![Handle(99), Handle(100)]);
+        assert_eq!(sk.pop_front(), Some(Handle(3)));
+        assert_eq!(sk.pop_back(), Some(Handle(98)));
+        if let Some(mut handle) = sk.get_mut(&Handle(50)) {
+            assert_eq!(*handle, Handle(50));
+            handle.0 = 50;
+        }
+        let expected: Vec<_> = (4..98).filter(|&i| i != 5 && i != 6).collect();
+        assert!(sk
+            .iter_all()
+            .map(|handle| handle.0)
+            .eq(expected.iter().copied()));
+        assert!(sk.into_iter().map(|handle| handle.0).eq(expected));
+        let mut sk: SkipList<Handle> = (0..10).map(Handle).collect();
+        assert_eq!(sk.pop_max(20).len(), 10);
+        assert!(sk.is_empty());
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn test_node_size() {
+        let expected = if cfg!(feature = "compact_widths") {
+            32
+        } else {
+            40
+        };
+        assert_eq!(std::mem::size_of::<Node<u64>>(), expected);
+    }
+
+    #[test]
+    fn test_removals_shrink_levels() {
+        // The row below the top one is empty only if the skiplist is at its
+        // starting height.
+        fn check(sk: &SkipList<u32>) {
+            let mut levels = sk.iter_levels();
+            assert_eq!(levels.next().unwrap().count(), 1);
+            assert!(sk.height == 3 || levels.next().unwrap().count() > 1);
+        }
+        let mut sk = SkipList::with_seed(0x3133);
+        sk.extend(0..2000u32);
+        let height = sk.height;
+        for i in 0..1000 {
+            sk.remove(&i);
+            check(&sk);
+        }
+        assert_eq!(sk.remove_many(1000..1500), 500);
+        check(&sk);
+        sk.pop_max(450);
+        check(&sk);
+        sk.pop_min(40);
+        check(&sk);
+        assert!(sk.height < height);
+        assert!(sk.iter_all().copied().eq(1540..1550));
+        sk.retain_nodes(|i| *i == 1545);
+        check(&sk);
+        assert!(sk.iter_all().eq(&[1545]));
+    }
+
+    #[test]
+    fn test_shrink_to_fit() {
+        let mut sk = SkipList::with_seed(0x3134);
+        let mut rng = StdRng::seed_from_u64(0x3134);
+        for _ in 0..5000 {
+            let item = rng.gen_range(0u32, 1000);
+            if rng.gen() {
+                sk.insert(item);
+            } else {
+                sk.remove(&item);
             }
         }
+        let levels = |sk: &SkipList<u32>| -> Vec<Vec<_>> {
+            sk.iter_levels()
+                .map(|row| row.map(|(item, width)| (item.copied(), width)).collect())
+                .collect()
+        };
+        let before = levels(&sk);
+        sk.shrink_to_fit();
+        assert_eq!(levels(&sk), before);
+        assert_eq!(sk.pool.recycled_len(), 0);
+        // Past NegInf, the bottom row is laid out in order.
+        let bottom = sk.iter_vertical().last().unwrap();
+        let mut addresses = Vec::new();
+        let mut curr_node = unsafe { (*bottom).right.unwrap() };
+        while let Some(right) = unsafe { curr_node.as_ref().right } {
+            addresses.push(curr_node.as_ptr() as usize);
+            curr_node = right;
+        }
+        assert_eq!(addresses.len(), sk.len());
+        assert!(addresses.windows(2).all(|pair| pair[0] < pair[1]));
     }
 
-    #[cfg(debug_assertions)]
-    fn ensure_rows_sum_len(&self) {
-        let mut left_row = self.top_left;
-        let mut curr_node = self.top_left;
-        unsafe {
-            loop {
-                let mut curr_sum = 0;
-                while let Some(right) = curr_node.as_ref().right {
-                    curr_sum += curr_node.as_ref().width;
-                    curr_node = right;
-                }
-                if let Some(down) = left_row.as_ref().down {
-                    assert_eq!(self.len(), curr_sum - 1);
-                    left_row = down;
-                    curr_node = left_row;
-                } else {
-                    break;
-                }
-            }
+    #[test]
+    fn test_send() {
+        fn assert_send<S: Send>() {}
+        assert_send::<SkipList<String>>();
+        assert_send::<crate::iter::IntoIter<String>>();
+        assert_send::<crate::SkipMap<String, Vec<u8>>>();
+        assert_send::<crate::SkipMultiSet<u32>>();
+        assert_send::<crate::CountedMultiSet<u32>>();
+        assert_send::<crate::DeterministicSkipList<u32>>();
+        assert_send::<crate::OrderedMultiMap<u32, u32>>();
+        assert_send::<crate::UnrolledSkipList<u32>>();
+
+        let shared = std::sync::Arc::new(std::sync::Mutex::new(SkipList::from(0..10)));
+        let workers: Vec<_> = (10..14)
+            .map(|i| {
+                let shared = std::sync::Arc::clone(&shared);
+                std::thread::spawn(move || shared.lock().unwrap().insert(i))
+            })
+            .collect();
+        for worker in workers {
+            assert!(worker.join().unwrap());
         }
+        let sk = std::thread::spawn(move || {
+            let sk = std::sync::Arc::try_unwrap(shared).ok().unwrap();
+            sk.into_inner().unwrap()
+        })
+        .join()
+        .unwrap();
+        assert_eq!(
+            sk.into_iter().collect::<Vec<_>>(),
+            (0..14).collect::<Vec<_>>()
+        );
     }
 
-    #[cfg(debug_assertions)]
-    fn ensure_invariants(&self) {
-        unsafe {
-            assert!(self.top_left.as_ref().right.unwrap().as_ref().value == NodeValue::PosInf)
+    #[test]
+    fn test_sync() {
+        fn assert_sync<S: Sync>() {}
+        assert_sync::<SkipList<String>>();
+        assert_sync::<crate::SkipMap<String, Vec<u8>>>();
+        assert_sync::<crate::SkipMultiSet<u32>>();
+        assert_sync::<crate::UnrolledSkipList<u32>>();
+
+        let shared = std::sync::Arc::new(SkipList::from(0..1000u32));
+        let readers: Vec<_> = (0..4)
+            .map(|t| {
+                let shared = std::sync::Arc::clone(&shared);
+                std::thread::spawn(move || {
+                    let copy = (*shared).clone();
+                    (t..1000).step_by(4).all(|i| {
+                        shared.contains(&i)
+                            && shared.index_of(&i) == Some(i as usize)
+                            && shared.range(&i, &(i + 2)).count() == (1000 - i).min(3) as usize
+                    }) && copy == *shared
+                })
+            })
+            .collect();
+        for reader in readers {
+            assert!(reader.join().unwrap());
         }
-        self.ensure_rows_ordered();
-        self.ensure_columns_same_value();
-        self.ensure_rows_sum_len();
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::SkipList;
-    use std::collections::HashSet;
+    #[test]
+    fn test_rebuild_balances_towers() {
+        let mut sk = SkipList::with_seed(0x3140);
+        sk.extend((0..1000u32).rev());
+        sk.rebuild();
+        #[cfg(debug_assertions)]
+        sk.ensure_invariants();
+        assert_eq!(
+            sk.iter_all().copied().collect::<Vec<_>>(),
+            (0..1000).collect::<Vec<_>>()
+        );
+        // Level `k` holds every `2^k`th element, ranked from 1.
+        let mut rows: Vec<Vec<u32>> = sk
+            .iter_levels()
+            .map(|row| row.filter_map(|(item, _)| item.copied()).collect())
+            .filter(|row: &Vec<_>| !row.is_empty())
+            .collect();
+        rows.reverse();
+        assert_eq!(rows.len(), 10);
+        for (k, row) in rows.iter().enumerate() {
+            let expected: Vec<u32> = (1..=1000)
+                .filter(|i| i % (1 << k) == 0)
+                .map(|i| i - 1)
+                .collect();
+            assert_eq!(row, &expected);
+        }
+    }
 
     #[test]
-    fn insert_no_panic() {
-        let mut sl = SkipList::new();
-        for i in &[10, 30, 50, 5, 0, 3] {
-            sl.insert(*i);
-            assert!(sl.contains(&i));
+    fn test_rebuild_follows_level_config() {
+        let rows = |sk: &SkipList<u32>| -> Vec<Vec<u32>> {
+            sk.iter_levels()
+                .map(|row| row.filter_map(|(item, _)| item.copied()).collect())
+                .filter(|row: &Vec<_>| !row.is_empty())
+                .collect()
+        };
+        // Every fourth element is promoted with a probability of 0.25.
+        let mut sk = SkipList::builder().probability(0.25).build();
+        sk.extend(0..1000u32);
+        sk.rebuild();
+        let mut quarters = rows(&sk);
+        quarters.reverse();
+        assert_eq!(quarters.len(), 5);
+        for (k, row) in quarters.iter().enumerate() {
+            let expected: Vec<u32> = (1..=1000)
+                .filter(|i| i % (1 << (2 * k)) == 0)
+                .map(|i| i - 1)
+                .collect();
+            assert_eq!(row, &expected);
         }
+
+        // Hashed heights are kept, so the structure still doesn't depend on order.
+        let mut hashed = SkipList::builder().hashed_levels().build();
+        hashed.extend((0..1000u32).rev());
+        let expected = rows(&hashed);
+        hashed.rebuild();
+        assert_eq!(rows(&hashed), expected);
+        assert_eq!(rows(&hashed.freeze()), expected);
+
+        // Other probabilities just get fresh heights, within the cap.
+        let mut sk = SkipList::builder().probability(0.9).max_height(4).build();
+        sk.extend(0..1000u32);
+        sk.rebuild();
         #[cfg(debug_assertions)]
-        sl.ensure_invariants();
+        sk.ensure_invariants();
+        assert!(sk.iter_all().copied().eq(0..1000));
+        assert!(sk.height <= 5);
     }
 
     #[test]
-    fn test_remove() {
-        let mut sl = SkipList::new();
-        sl.insert(0usize);
-        assert!(sl.remove(&0));
-        assert!(!sl.remove(&0));
-        assert!(!sl.contains(&0));
-        sl.insert(0);
-        sl.insert(1);
-        sl.insert(2);
-        assert!(sl.remove(&1));
-        assert!(!sl.contains(&1));
-        sl.remove(&2);
-        assert!(!sl.contains(&2));
+    fn test_pop_iters_are_lazy() {
+        let mut sk = SkipList::from(0..100);
+        {
+            let mut popped = sk.pop_max_iter(10);
+            assert_eq!(10, popped.len());
+            assert_eq!(Some(99), popped.next());
+            assert_eq!(9, popped.len());
+        }
+        assert_eq!(99, sk.len());
+        #[cfg(debug_assertions)]
+        sk.ensure_invariants();
+        {
+            let mut popped = sk.pop_min_iter(3);
+            assert_eq!(Some(0), popped.next());
+        }
+        assert_eq!(98, sk.len());
+        #[cfg(debug_assertions)]
+        sk.ensure_invariants();
+        assert_eq!(
+            (1..99).collect::<Vec<_>>(),
+            sk.pop_min_iter(1000).collect::<Vec<_>>()
+        );
+        assert!(sk.is_empty());
+        assert_eq!(None, sk.pop_max_iter(5).next());
     }
 
     #[test]
-    fn test_inclusive_range() {
-        let mut sl = SkipList::new();
-        let values: &[i32] = &[10, 30, 50, 5, 0, 3];
-        for i in &[10, 30, 50, 5, 0, 3] {
-            sl.insert(*i);
-            assert!(sl.contains(&i));
+    fn test_contains_copied() {
+        let sk = (0..1000i64).map(|i| i * 3).collect::<SkipList<_>>();
+        for i in -5..3005 {
+            assert_eq!(sk.contains_copied(i), sk.contains(&i));
         }
-        let lower = 3;
-        let upper = 30;
-        let v: HashSet<i32> = sl.range(&lower, &upper).cloned().collect();
-        for expected_value in values.iter().filter(|&&i| lower <= i && i <= upper) {
-            assert!(v.contains(expected_value));
+        assert!(!SkipList::<u8>::new().contains_copied(0));
+    }
+
+    #[test]
+    fn test_prefetched_searches() {
+        // Tall, sparse towers and churn, so searches prefetch past the ends of
+        // rows and into towers that were freed and reused.
+        let mut sk = SkipList::builder().probability(0.25).seed(3137).build();
+        let mut rng = StdRng::seed_from_u64(3137);
+        let mut expected = std::collections::BTreeSet::new();
+        for _ in 0..4000 {
+            let item = rng.gen_range(0u32, 1000);
+            if rng.gen_range(0, 3) == 0 {
+                assert_eq!(sk.remove(&item), expected.remove(&item));
+            } else {
+                assert_eq!(sk.insert(item), expected.insert(item));
+            }
         }
-        let right_empty: HashSet<i32> = sl.range(&100, &1000).cloned().collect();
-        assert!(right_empty.is_empty());
+        for item in 0..1002 {
+            let left = unsafe { (*sk.left_of_copied(item)).value.value() };
+            assert_eq!(left, expected.range(..item).next_back());
+            assert_eq!(sk.contains_copied(item), expected.contains(&item));
+            assert_eq!(sk.contains(&item), expected.contains(&item));
+            assert_eq!(sk.rank(&item), expected.range(..item).count());
+        }
+    }
 
-        let left_empty: HashSet<i32> = sl.range(&-2, &-1).cloned().collect();
-        assert!(left_empty.is_empty());
+    #[test]
+    fn test_memory_usage() {
+        let node = std::mem::size_of::<Node<String>>();
+        let mut sk = SkipList::new();
+        assert_eq!(sk.memory_usage(), 2 * sk.height * node);
+        sk.extend((0..100).map(|i| i.to_string()));
+        let usage = sk.memory_usage();
+        assert!(usage >= 100 * (node + std::mem::size_of::<String>()));
+        let strings: usize = sk.iter_all().map(String::capacity).sum();
+        assert_eq!(sk.memory_usage_with(String::capacity), usage + strings);
+        // Removed towers are kept for reuse, until the skiplist is repacked.
+        // Only the sentinels of emptied rows are released.
+        let height = sk.height;
+        for i in 0..50 {
+            sk.remove(&i.to_string());
+        }
+        let released = 2 * (height - sk.height) * node;
+        assert_eq!(sk.memory_usage() + released, usage);
+        sk.shrink_to_fit();
+        assert!(sk.memory_usage() < usage);
+    }
 
-        // Excessive range
-        let lower = -10;
-        let upper = 1000;
-        let v: HashSet<i32> = sl.range(&lower, &upper).cloned().collect();
-        for expected_value in values.iter().filter(|&&i| lower <= i && i <= upper) {
-            assert!(v.contains(expected_value));
+    #[test]
+    fn test_recycles_freed_towers() {
+        let mut sk: SkipList<u32> = (0..100).collect();
+        for i in (0..100).step_by(2) {
+            assert!(sk.remove(&i));
         }
+        assert_eq!(sk.pool.recycled_len(), 50);
+        sk.extend(100..150);
+        assert!(sk.pool.recycled_len() < 50);
+        let expected: Vec<_> = (1..100).step_by(2).chain(100..150).collect();
+        assert!(sk.iter_all().copied().eq(expected));
     }
 
     #[test]
-    fn test_len() {
-        let mut sl = SkipList::new();
-        assert_eq!(sl.len(), 0);
-        assert!(sl.is_empty());
-        sl.insert(0);
-        assert_eq!(sl.len(), 1);
-        assert!(!sl.is_empty());
-        sl.insert(0);
-        assert_eq!(sl.len(), 1);
-        sl.insert(1);
-        assert_eq!(sl.len(), 2);
-        sl.remove(&1);
-        assert_eq!(sl.len(), 1);
-        sl.remove(&1);
-        assert_eq!(sl.len(), 1);
-        sl.remove(&0);
-        assert_eq!(sl.len(), 0);
-        sl.remove(&0);
-        assert_eq!(sl.len(), 0);
+    fn test_with_capacity() {
+        let mut sk = SkipList::with_capacity(100);
+        for i in (0..200).rev() {
+            sk.insert(i.to_string());
+        }
+        for i in (0..200).step_by(3) {
+            assert!(sk.remove(&i.to_string()));
+        }
+        assert_eq!(sk.len(), 133);
+        let mut other: SkipList<String> = (150..300).map(|i| i.to_string()).collect();
+        other.pop_min(10);
+        sk.union_in_place(other);
+        sk.pop_max(5);
+        let mut expected: Vec<String> = (0..200)
+            .filter(|i| i % 3 != 0)
+            .chain(160..300)
+            .map(|i| i.to_string())
+            .collect();
+        expected.sort();
+        expected.dedup();
+        expected.truncate(expected.len() - 5);
+        assert_eq!(sk.iter_all().cloned().collect::<Vec<_>>(), expected);
     }
 
     #[test]
-    fn test_eq() {
-        let mut s0 = SkipList::new();
-        let mut s1 = SkipList::new();
-        assert!(s0 == s1);
-        s0.insert(0);
-        assert!(s0 != s1);
-        s1.insert(1);
-        assert!(s0 != s1);
-        s0.insert(1);
-        s1.insert(0);
-        assert!(s0 == s1);
-        s0.insert(2);
-        s0.insert(3);
-        assert!(s0 != s1);
+    fn test_skiplist_macro() {
+        let sk = skiplist![5, 3, 4, 3,];
+        assert_eq!(sk, SkipList::from(3..6));
+        let sk: SkipList<String> = skiplist![];
+        assert!(sk.is_empty());
     }
 
     #[test]
-    fn test_from() {
-        let values = vec![1usize, 2, 3];
-        let sk = SkipList::from(values.clone().into_iter());
-        assert_eq!(sk.iter_all().cloned().collect::<Vec<_>>(), values);
-        let values: Vec<usize> = (0..10).collect();
-        let sk = SkipList::from(0..10);
-        assert_eq!(sk.iter_all().cloned().collect::<Vec<_>>(), values);
+    fn test_from_collections() {
+        assert_eq!(SkipList::from(vec![3, 1, 2, 1]), SkipList::from(1..4));
+        assert_eq!(SkipList::from(vec![1, 1, 2, 3]), SkipList::from(1..=3));
+        let slice: &[i32] = &[5, 4, 6];
+        assert_eq!(SkipList::from(slice), SkipList::from(4..7));
+        assert_eq!(SkipList::from([2, 0, 1]), SkipList::from(0..3));
+        let sk = SkipList::from(vec!["b".to_string(), "a".to_string()]);
+        assert_eq!(sk.peek_first(), Some(&"a".to_string()));
+        assert!(SkipList::<u8>::from(Vec::new()).is_empty());
     }
 
     #[test]
-    fn test_index_of() {
-        let mut sk = SkipList::new();
-        sk.insert(1);
-        sk.insert(2);
-        sk.insert(3);
+    fn test_vec_from() {
+        let sk: SkipList<u32> = SkipList::from(0..4);
+        assert_eq!(vec![0, 1, 2, 3], Vec::from(sk));
+    }
 
-        assert_eq!(sk.index_of(&1), Some(0));
-        assert_eq!(sk.index_of(&2), Some(1));
-        assert_eq!(sk.index_of(&3), Some(2));
-        assert_eq!(sk.index_of(&999), None);
-        let sk = SkipList::new();
-        assert_eq!(sk.index_of(&0), None);
-        assert_eq!(sk.index_of(&999), None);
+    #[test]
+    fn test_set_operators() {
+        let a = SkipList::from(vec![1, 2, 3, 5]);
+        let b = SkipList::from(vec![2, 3, 4]);
+        assert_eq!(&a | &b, SkipList::from(1..6));
+        assert_eq!(&a & &b, SkipList::from(2..4));
+        assert_eq!(&a - &b, SkipList::from(vec![1, 5]));
+        assert_eq!(&b - &a, SkipList::from(vec![4]));
+        assert_eq!(&a ^ &b, SkipList::from(vec![1, 4, 5]));
+        let empty = SkipList::new();
+        assert_eq!(&a | &empty, a);
+        assert_eq!(&a & &empty, empty);
     }
 
     #[test]
-    fn test_at_index() {
-        let sk = SkipList::from(0..10);
-        for i in 0..10 {
-            assert_eq!(Some(&i), sk.at_index(i));
-        }
-        assert_eq!(None, sk.at_index(11));
+    fn test_union_in_place() {
+        let mut sk = SkipList::from(vec![1, 3, 5, 7]);
+        sk.union_in_place(SkipList::from(vec![0, 2, 3, 4, 7, 8]));
+        assert_eq!(sk, (0..9).filter(|&i| i != 6).collect());
+        assert_eq!(sk.len(), 8);
 
         let mut sk = SkipList::new();
-        sk.insert('a');
-        sk.insert('b');
-        sk.insert('c');
-        assert_eq!(Some(&'a'), sk.at_index(0));
-        assert_eq!(Some(&'b'), sk.at_index(1));
-        assert_eq!(Some(&'c'), sk.at_index(2));
-        assert_eq!(None, sk.at_index(3));
+        sk.union_in_place(SkipList::from(0..50));
+        assert_eq!(sk, SkipList::from(0..50));
+        sk.union_in_place(SkipList::new());
+        assert_eq!(sk, SkipList::from(0..50));
 
-        assert_eq!('a', sk[0]);
-        assert_eq!('b', sk[1]);
-        assert_eq!('c', sk[2]);
+        let mut sk: SkipList<String> = (0..10).map(|i| i.to_string()).collect();
+        sk.union_in_place((5..20).map(|i| i.to_string()).collect());
+        assert_eq!(sk.len(), 20);
     }
 
     #[test]
-    #[should_panic]
-    fn test_bad_index() {
-        let sk = SkipList::from(0..10);
-        sk[sk.len()];
+    fn test_union_in_place_caps_heights() {
+        let mut sk = SkipList::builder().max_height(1).build();
+        sk.extend(0..100);
+        sk.union_in_place(SkipList::from_sorted_iter(100..2000));
+        #[cfg(debug_assertions)]
+        sk.ensure_invariants();
+        assert_eq!(sk.height, 3);
+        assert!(sk.iter_all().copied().eq(0..2000));
+
+        // Towers can grow past their old heights too.
+        let mut sk = SkipList::builder().probability(0.9).build();
+        let mut short = SkipList::builder().max_height(1).build();
+        short.extend(0..500);
+        sk.union_in_place(short);
+        assert!(sk.height > 3);
+        assert!(sk.iter_all().copied().eq(0..500));
+        assert_eq!(sk.index_of(&250), Some(250));
     }
 
     #[test]
-    fn test_pop_max() {
+    fn test_union_in_place_hashed_levels() {
+        let hashed = || SkipList::builder().hashed_levels().build();
+        let mut inserted = hashed();
+        for i in (0..1000u32).rev() {
+            inserted.insert(i);
+        }
+        let mut unioned = hashed();
+        unioned.extend((0..1000).step_by(2));
+        // `other` has random heights, which mustn't carry over.
+        let mut other = SkipList::with_seed(0x3036);
+        other.extend((1..1000).step_by(2));
+        unioned.union_in_place(other);
+        assert_eq!(unioned.height, inserted.height);
+        assert!(unioned
+            .iter_levels()
+            .zip(inserted.iter_levels())
+            .all(|(left, right)| left.eq(right)));
+    }
+
+    #[test]
+    fn test_intersect_in_place() {
+        let mut sk = SkipList::from(0..20);
+        sk.intersect_in_place(&SkipList::from(vec![-1, 0, 5, 6, 19, 25]));
+        assert_eq!(sk, SkipList::from(vec![0, 5, 6, 19]));
+        assert_eq!(sk.len(), 4);
+        assert_eq!(sk.index_of(&19), Some(3));
+
+        sk.intersect_in_place(&SkipList::new());
+        assert!(sk.is_empty());
+        assert_eq!(sk, SkipList::new());
+
+        let mut sk = SkipList::from(0..20);
+        sk.intersect_in_place(&SkipList::from(0..40));
+        assert_eq!(sk, SkipList::from(0..20));
+    }
+
+    #[test]
+    fn test_difference_in_place() {
         let mut sk = SkipList::from(0..10);
-        assert_eq!(Some(&7), sk.at_index(7));
-        assert_eq!(vec![7, 8, 9], sk.pop_max(3));
-        assert_eq!(vec![6], sk.pop_max(1));
-        assert_eq!(vec![4, 5], sk.pop_max(2));
-        assert_eq!(vec![0, 1, 2, 3], sk.pop_max(5));
-        let mut sk = SkipList::from(0..3);
-        assert_eq!(vec![2], sk.pop_max(1));
+        sk.difference_in_place(&SkipList::from(vec![-1, 0, 5, 6, 9, 25]));
+        assert_eq!(sk, SkipList::from(vec![1, 2, 3, 4, 7, 8]));
+        assert_eq!(sk.len(), 6);
+        assert_eq!(sk.index_of(&7), Some(4));
+
+        sk.difference_in_place(&SkipList::new());
+        assert_eq!(sk.len(), 6);
+        sk.difference_in_place(&SkipList::from(0..10));
+        assert!(sk.is_empty());
+    }
+
+    #[test]
+    fn test_retain_panic_keeps_the_rest() {
+        let mut sk = SkipList::with_seed(0x3037);
+        sk.extend(0..500u32);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            sk.retain_nodes(|&mut i| {
+                assert!(i < 300, "Panicking partway through");
+                i % 3 == 0
+            })
+        }));
+        assert!(result.is_err());
+        #[cfg(debug_assertions)]
+        sk.ensure_invariants();
+        let expected: Vec<_> = (0..300).step_by(3).chain(300..500).collect();
+        assert!(sk.iter_all().eq(&expected));
+        assert_eq!(sk.len(), expected.len());
+        for (i, item) in expected.iter().enumerate() {
+            assert_eq!(sk.at_index(i), Some(item));
+        }
+        assert_eq!(sk.retain_nodes(|i| *i >= 300), 100);
+        assert!(sk.iter_all().copied().eq(300..500));
+    }
+
+    #[test]
+    fn test_extend() {
+        let mut sk = SkipList::new();
+        sk.extend(0..100);
+        assert_eq!(sk, SkipList::from(0..100));
+        assert_eq!(sk.len(), 100);
+        assert_eq!(sk.index_of(&50), Some(50));
+        assert_eq!(sk.at_index(99), Some(&99));
+
+        // Unsorted and overlapping input
+        sk.extend(vec![150, 120, 99, -5, 100]);
+        assert_eq!(sk.len(), 104);
+        assert_eq!(sk.peek_first(), Some(&-5));
+        assert_eq!(sk.peek_last(), Some(&150));
+        sk.extend(151..200);
+        assert_eq!(sk.len(), 153);
+        assert_eq!(sk.index_of(&199), Some(152));
+
         let mut sk: SkipList<u32> = SkipList::new();
-        let v: Vec<u32> = Vec::new();
-        assert_eq!(v, sk.pop_max(1));
+        sk.extend(Vec::<u32>::new());
+        assert!(sk.is_empty());
     }
 
     #[test]
-    fn test_pop_min() {
-        let mut sk = SkipList::from(0..10);
-        assert_eq!(vec![0, 1, 2], sk.pop_min(3));
-        assert_eq!(vec![3], sk.pop_min(1));
-        assert_eq!(vec![4, 5], sk.pop_min(2));
-        assert_eq!(vec![6, 7, 8, 9], sk.pop_min(5));
-        let v: Vec<u32> = Vec::new();
-        assert_eq!(v, sk.pop_min(1));
+    fn test_extend_ref() {
+        let values = vec![5, 1, 3];
+        let mut sk: SkipList<i32> = SkipList::new();
+        sk.extend(values.iter());
+        sk.extend(&values);
+        sk.extend(&[0, 9]);
+        assert_eq!(sk, SkipList::from(vec![0, 1, 3, 5, 9]));
+
+        let strings = vec!["b".to_string(), "a".to_string()];
+        let mut sk: SkipList<String> = SkipList::new();
+        sk.extend(&strings);
+        assert_eq!(Vec::from(sk), vec!["a".to_string(), "b".to_string()]);
     }
 
     #[test]
-    fn test_clone() {
-        let sk = SkipList::from(0..30);
-        let clone = sk.clone();
-        assert_eq!(sk, clone);
-        assert!(!std::ptr::eq(&sk, &clone));
-        // Empty case
-        let sk = SkipList::from(0..0);
-        let clone = sk.clone();
-        assert_eq!(
-            sk, clone,
-            "Empty skiplists should clone nicely, {:?} != {:?}",
-            sk, clone
-        );
+    fn test_from_sorted_iter() {
+        let sk = SkipList::from_sorted_iter(0..200);
+        assert_eq!(sk, SkipList::from(0..200));
+        for i in 0..200 {
+            assert_eq!(sk.index_of(&i), Some(i));
+        }
+        let sk = SkipList::from_sorted_iter(vec![1, 1, 2, 3, 3, 3]);
+        assert_eq!(Vec::from(sk), vec![1, 2, 3]);
+        let sk = SkipList::<u32>::from_sorted_iter(Vec::new());
+        assert!(sk.is_empty());
     }
 
     #[test]
-    fn test_peek() {
-        let sk = SkipList::from(0..10);
-        assert_eq!(Some(&0), sk.peek_first());
-        assert_eq!(Some(&9), sk.peek_last());
+    #[should_panic]
+    fn test_from_sorted_iter_unsorted() {
+        SkipList::from_sorted_iter(vec![1, 3, 2]);
     }
 
     #[test]
-    fn test_vec_from() {
-        let sk: SkipList<u32> = SkipList::from(0..4);
-        assert_eq!(vec![0, 1, 2, 3], Vec::from(sk));
+    fn test_extend_from_sorted() {
+        let mut sk: SkipList<i32> = (0..100).step_by(10).collect();
+        sk.extend_from_sorted(vec![-3, -2, 5, 6, 7, 10, 55, 56, 90, 150, 151]);
+        let mut expected: Vec<i32> = (0..100).step_by(10).collect();
+        expected.extend(vec![-3, -2, 5, 6, 7, 55, 56, 150, 151]);
+        expected.sort();
+        assert_eq!(Vec::from(sk.clone()), expected);
+        for (i, item) in expected.iter().enumerate() {
+            assert_eq!(sk.index_of(item), Some(i));
+        }
+
+        // Out of order items are still inserted.
+        sk.extend_from_sorted(vec![1000, 1, 1000, 2]);
+        assert!(sk.contains(&1) && sk.contains(&2) && sk.contains(&1000));
+        assert_eq!(sk.len(), expected.len() + 3);
+
+        let mut sk = SkipList::new();
+        sk.extend_from_sorted(0..50);
+        assert_eq!(sk, SkipList::from(0..50));
+    }
+
+    #[test]
+    fn test_insert_many() {
+        let mut sk = SkipList::new();
+        assert_eq!(sk.insert_many(0..10), 10);
+        assert_eq!(sk.insert_many(0..10), 0);
+        assert_eq!(sk.insert_many(vec![20, 5, 20, 15]), 2);
+        assert_eq!(sk.len(), 12);
+        assert_eq!(sk.insert_many(Vec::new()), 0);
+    }
+
+    #[test]
+    fn test_remove_many() {
+        let mut sk = SkipList::from(0..100);
+        assert_eq!(sk.remove_many((0..100).step_by(2)), 50);
+        assert_eq!(sk, (1..100).step_by(2).collect());
+        for (i, item) in (1..100).step_by(2).enumerate() {
+            assert_eq!(sk.index_of(&item), Some(i));
+        }
+        assert_eq!(sk.remove_many([99, 1, 99, 50, 3]), 3);
+        assert_eq!(sk.len(), 47);
+        assert!(!sk.contains(&1) && !sk.contains(&3) && !sk.contains(&99));
+        assert_eq!(sk.remove_many(Vec::<i32>::new()), 0);
+        assert_eq!(sk.remove_many(0..100), 47);
+        assert!(sk.is_empty());
     }
 
     #[test]
@@ -1384,7 +5141,7 @@ mod tests {
         // but you double free as you're copying the string struct
         // and dropping the original. So you end up with double frees.
         let mut string_sk = SkipList::new();
-        for c in b'a'..b'z' {
+        for c in b'a'..=b'z' {
             string_sk.insert((c as char).to_string());
         }
         string_sk.pop_back();
@@ -0,0 +1,1071 @@
+use crate::iter::{IntoIter, IterAll, SkipListIndexRange, SkipListRange, VerticalIter};
+use crate::{Node, SkipList};
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::{FromIterator, FusedIterator};
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
+
+/// Create a `SkipMap` containing the given key-value pairs, analogous to `vec!`.
+///
+/// If a key is repeated, the last value for it wins.
+///
+/// ```rust
+/// use convenient_skiplist::{skipmap, SkipMap};
+/// let map = skipmap! { "b" => 2, "a" => 1, "b" => 3 };
+/// assert_eq!(vec![(&"a", &1), (&"b", &3)], map.iter().collect::<Vec<_>>());
+/// let empty: SkipMap<u32, u32> = skipmap! {};
+/// assert!(empty.is_empty());
+/// ```
+#[macro_export]
+macro_rules! skipmap {
+    () => {
+        $crate::SkipMap::new()
+    };
+    ($($key:expr => $value:expr),+ $(,)?) => {
+        ::std::vec![$(($key, $value)),+]
+            .into_iter()
+            .collect::<$crate::SkipMap<_, _>>()
+    };
+}
+
+/// A key-value pair stored in the skiplist, ordered by its key alone.
+#[derive(Clone)]
+struct Entry<K, V> {
+    key: K,
+    value: V,
+}
+
+impl<K: PartialEq, V> PartialEq for Entry<K, V> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<K: PartialOrd, V> PartialOrd for Entry<K, V> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.key.partial_cmp(&other.key)
+    }
+}
+
+/// A borrowed key, which lets entries be searched for with `SkipList`'s
+/// `Borrow`-based lookups without building a whole entry.
+#[repr(transparent)]
+pub(crate) struct KeyRef<Q: ?Sized>(Q);
+
+impl<Q: ?Sized> KeyRef<Q> {
+    #[inline]
+    pub(crate) fn new(key: &Q) -> &KeyRef<Q> {
+        // SAFETY: KeyRef is a transparent wrapper around Q.
+        unsafe { &*(key as *const Q as *const KeyRef<Q>) }
+    }
+}
+
+impl<Q: PartialEq + ?Sized> PartialEq for KeyRef<Q> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<Q: PartialOrd + ?Sized> PartialOrd for KeyRef<Q> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.0.partial_cmp(&other.0)
+    }
+}
+
+impl<K: Borrow<Q>, V, Q: ?Sized> Borrow<KeyRef<Q>> for Entry<K, V> {
+    #[inline]
+    fn borrow(&self) -> &KeyRef<Q> {
+        KeyRef::new(self.key.borrow())
+    }
+}
+
+/// Bounds on keys, seen as bounds on entries.
+pub(crate) struct KeyBounds<Q: ?Sized, R> {
+    range: R,
+    _key: PhantomData<fn(&Q)>,
+}
+
+impl<Q: ?Sized, R: RangeBounds<Q>> KeyBounds<Q, R> {
+    #[inline]
+    pub(crate) fn new(range: R) -> Self {
+        Self {
+            range,
+            _key: PhantomData,
+        }
+    }
+}
+
+#[inline]
+fn key_bound<Q: ?Sized>(bound: Bound<&Q>) -> Bound<&KeyRef<Q>> {
+    match bound {
+        Bound::Included(key) => Bound::Included(KeyRef::new(key)),
+        Bound::Excluded(key) => Bound::Excluded(KeyRef::new(key)),
+        Bound::Unbounded => Bound::Unbounded,
+    }
+}
+
+impl<Q: ?Sized, R: RangeBounds<Q>> RangeBounds<KeyRef<Q>> for KeyBounds<Q, R> {
+    #[inline]
+    fn start_bound(&self) -> Bound<&KeyRef<Q>> {
+        key_bound(self.range.start_bound())
+    }
+
+    #[inline]
+    fn end_bound(&self) -> Bound<&KeyRef<Q>> {
+        key_bound(self.range.end_bound())
+    }
+}
+
+/// An ordered map built on a skiplist, with `O(logn)` lookups by key and by rank.
+///
+/// Keys and values are stored together in the same towers as a `SkipList`.
+/// Each pair is stored once, in its tower's header, and shared by every node
+/// of the tower, so neither keys nor values need to be `Clone`, and a value
+/// mutated in place is seen from every level.
+///
+/// # Example
+///
+/// ```rust
+/// use convenient_skiplist::SkipMap;
+/// let mut map = SkipMap::new();
+///
+/// assert_eq!(map.insert("b", 2), None);
+/// assert_eq!(map.insert("a", 1), None);
+/// assert_eq!(map.insert("b", 3), Some(2));
+///
+/// assert_eq!(map.get("b"), Some(&3));
+/// assert_eq!(map.at_index(0), Some((&"a", &1)));
+/// assert_eq!(map.remove("a"), Some(1));
+/// assert_eq!(map.len(), 1);
+/// ```
+pub struct SkipMap<K, V> {
+    inner: SkipList<Entry<K, V>>,
+}
+
+impl<K: PartialOrd, V> SkipMap<K, V> {
+    /// Make a new, empty SkipMap.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipMap;
+    /// let map: SkipMap<u32, String> = SkipMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    #[inline]
+    pub fn new() -> SkipMap<K, V> {
+        SkipMap {
+            inner: SkipList::new(),
+        }
+    }
+
+    /// Build a map from key-value pairs already sorted by key in ascending order.
+    ///
+    /// Like `SkipList::from_sorted_iter`, the map is built bottom-up without
+    /// any searching, which makes this the fast path for loading snapshots.
+    ///
+    /// Runs in `O(n)` expected time.
+    ///
+    /// Consecutive pairs with the same key are skipped, keeping the first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a key is smaller than the key before it.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipMap;
+    /// let map = SkipMap::from_sorted_iter((0..1000).map(|key| (key, key * 2)));
+    ///
+    /// assert_eq!(map.len(), 1000);
+    /// assert_eq!(map.get(&500), Some(&1000));
+    /// ```
+    #[inline]
+    pub fn from_sorted_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> SkipMap<K, V> {
+        SkipMap {
+            inner: SkipList::from_sorted_iter(
+                iter.into_iter().map(|(key, value)| Entry { key, value }),
+            ),
+        }
+    }
+
+    /// Insert `value` under `key`, returning the value previously stored
+    /// under `key`, if any.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipMap;
+    /// let mut map = SkipMap::new();
+    ///
+    /// assert_eq!(map.insert(1, "one"), None);
+    /// assert_eq!(map.insert(1, "uno"), Some("one"));
+    /// assert_eq!(map.get(&1), Some(&"uno"));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(old) = self.get_mut(&key) {
+            return Some(std::mem::replace(old, value));
+        }
+        self.inner.insert(Entry { key, value });
+        None
+    }
+
+    /// Get a reference to the value stored under `key`.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipMap;
+    /// let mut map = SkipMap::new();
+    /// map.insert("a".to_string(), 1);
+    ///
+    /// assert_eq!(map.get("a"), Some(&1));
+    /// assert_eq!(map.get("b"), None);
+    /// ```
+    #[inline]
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.inner.get(KeyRef::new(key)).map(|entry| &entry.value)
+    }
+
+    /// Get the stored key and its value, for the key equal to `key`.
+    ///
+    /// This is handy when looking up with a borrowed form of the key,
+    /// like `&str` for a `SkipMap<String, V>`.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipMap;
+    /// let mut map = SkipMap::new();
+    /// map.insert("a".to_string(), 1);
+    ///
+    /// assert_eq!(map.get_key_value("a"), Some((&"a".to_string(), &1)));
+    /// assert_eq!(map.get_key_value("b"), None);
+    /// ```
+    #[inline]
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.inner
+            .get(KeyRef::new(key))
+            .map(|entry| (&entry.key, &entry.value))
+    }
+
+    /// Get a mutable reference to the value stored under `key`.
+    ///
+    /// Values don't take part in the ordering, so unlike `SkipList::get_mut`
+    /// this hands out a plain `&mut V`.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipMap;
+    /// let mut map = SkipMap::new();
+    /// map.insert("hits", 0);
+    ///
+    /// *map.get_mut("hits").unwrap() += 1;
+    /// assert_eq!(map.get("hits"), Some(&1));
+    /// assert_eq!(map.get_mut("misses"), None);
+    /// ```
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        let key = KeyRef::new(key);
+        // INVARIANT: path_to always ends on the bottom row, with a node right of it.
+        let left = self.inner.path_to(key).last().unwrap();
+        unsafe {
+            let right = &mut (*(*left.curr_node).right.unwrap().as_ptr()).value;
+            if right.eq_key(key) {
+                Some(&mut right.get_value_mut().value)
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Check if there's a value stored under `key`.
+    ///
+    /// Runs in `O(logn)` time.
+    #[inline]
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.inner.contains(KeyRef::new(key))
+    }
+
+    /// Remove `key` from the map, returning the value that was stored under it.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipMap;
+    /// let mut map = SkipMap::new();
+    /// map.insert(1, "one");
+    ///
+    /// assert_eq!(map.remove(&1), Some("one"));
+    /// assert_eq!(map.remove(&1), None);
+    /// ```
+    #[inline]
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.inner.take(KeyRef::new(key)).map(|entry| entry.value)
+    }
+
+    /// Get the key-value pair at `index`, in key order.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipMap;
+    /// let map: SkipMap<_, _> = vec![(3, 'c'), (1, 'a'), (2, 'b')].into_iter().collect();
+    ///
+    /// assert_eq!(map.at_index(0), Some((&1, &'a')));
+    /// assert_eq!(map.at_index(2), Some((&3, &'c')));
+    /// assert_eq!(map.at_index(3), None);
+    /// ```
+    #[inline]
+    pub fn at_index(&self, index: usize) -> Option<(&K, &V)> {
+        self.inner
+            .at_index(index)
+            .map(|entry| (&entry.key, &entry.value))
+    }
+
+    /// Find the index of `key` among the keys of the map, if it's there.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::skipmap;
+    /// let map = skipmap! { "a" => 1, "c" => 3, "f" => 6 };
+    ///
+    /// assert_eq!(map.index_of_key("c"), Some(1));
+    /// assert_eq!(map.index_of_key("b"), None);
+    /// ```
+    #[inline]
+    pub fn index_of_key<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.inner.index_of(KeyRef::new(key))
+    }
+
+    /// Iterate over the key-value pairs in a range of indices, in key order.
+    ///
+    /// This runs in `O(logn + k)`, where k is the width of range.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipMap;
+    /// let map: SkipMap<u32, char> = (0..26).zip('a'..='z').collect();
+    ///
+    /// let top_three: Vec<_> = map.index_range(..3).map(|(_, c)| *c).collect();
+    /// assert_eq!(top_three, vec!['a', 'b', 'c']);
+    /// assert_eq!(map.index_range(24..).next_back(), Some((&25, &'z')));
+    /// ```
+    #[inline]
+    pub fn index_range<R: RangeBounds<usize>>(&self, range: R) -> IndexRange<'_, K, V, R> {
+        IndexRange {
+            inner: self.inner.index_range(range),
+        }
+    }
+
+    /// Iterate over the key-value pairs whose keys fall in `range`, in key order.
+    ///
+    /// Takes any kind of range, like `a..b`, `a..` or `..=b`, and the bounds can
+    /// be a borrowed form of `K`.
+    ///
+    /// This runs in `O(logn + k)`, where k is the width of range.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::{skipmap, SkipMap};
+    /// use std::ops::Bound;
+    /// let map = skipmap! { "a" => 1, "c" => 3, "f" => 6, "h" => 8 };
+    ///
+    /// let pairs: Vec<_> = map.range("a".."f").collect();
+    /// assert_eq!(pairs, vec![(&"a", &1), (&"c", &3)]);
+    /// assert_eq!(map.range("d"..).rev().next(), Some((&"h", &8)));
+    ///
+    /// let owned: SkipMap<String, u32> = map.iter().map(|(k, v)| (k.to_string(), *v)).collect();
+    /// // `&str` bounds are written as a tuple, as `"a".."f"` would be a range of `&str`.
+    /// let keys: Vec<_> = owned
+    ///     .range::<str, _>((Bound::Excluded("a"), Bound::Included("f")))
+    ///     .map(|(k, _)| k.as_str())
+    ///     .collect();
+    /// assert_eq!(keys, vec!["c", "f"]);
+    /// ```
+    #[inline]
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V, Q, R>
+    where
+        K: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        Range {
+            inner: self.inner.range_bounds(KeyBounds::new(range)),
+        }
+    }
+
+    /// Keep only the key-value pairs for which `keep` returns `true`.
+    ///
+    /// `keep` is called once per pair, in key order, and can modify the value.
+    /// Everything is removed in a single pass, rather than one `remove` per pair.
+    /// If `keep` panics, the pair it panicked on and every pair after it are kept.
+    ///
+    /// Runs in `O(n)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipMap;
+    /// let mut map: SkipMap<u32, u32> = (0..10).map(|key| (key, key)).collect();
+    ///
+    /// map.retain(|key, value| {
+    ///     *value *= 10;
+    ///     key % 3 == 0
+    /// });
+    /// assert_eq!(vec![&0, &30, &60, &90], map.values().collect::<Vec<_>>());
+    /// ```
+    pub fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.inner
+            .retain_nodes(|entry| keep(&entry.key, &mut entry.value));
+    }
+
+    /// Remove every key-value pair from the map, yielding them in key order.
+    ///
+    /// The map is empty as soon as this is called, even if the iterator
+    /// isn't run to completion.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::skipmap;
+    /// let mut map = skipmap! { 'b' => 2, 'a' => 1 };
+    ///
+    /// assert_eq!(vec![('a', 1), ('b', 2)], map.drain().collect::<Vec<_>>());
+    /// assert!(map.is_empty());
+    /// ```
+    #[inline]
+    pub fn drain(&mut self) -> Drain<'_, K, V> {
+        Drain {
+            inner: std::mem::take(&mut self.inner).into_iter(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Return the number of key-value pairs in the map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns true if the map is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Iterate over the keys of the map, in order.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::skipmap;
+    /// let map = skipmap! { 'b' => 2, 'a' => 1 };
+    ///
+    /// assert_eq!(vec![&'a', &'b'], map.keys().collect::<Vec<_>>());
+    /// ```
+    #[inline]
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys { inner: self.iter() }
+    }
+
+    /// Iterate over the values of the map, in key order.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::skipmap;
+    /// let map = skipmap! { 'b' => 2, 'a' => 1 };
+    ///
+    /// assert_eq!(vec![&1, &2], map.values().collect::<Vec<_>>());
+    /// ```
+    #[inline]
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values { inner: self.iter() }
+    }
+
+    /// Iterate over mutable references to the values of the map, in key order.
+    ///
+    /// Values don't take part in the ordering, so they can be changed freely.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::skipmap;
+    /// let mut map = skipmap! { 'b' => 2, 'a' => 1 };
+    ///
+    /// for value in map.values_mut() {
+    ///     *value *= 10;
+    /// }
+    /// assert_eq!(vec![&10, &20], map.values().collect::<Vec<_>>());
+    /// ```
+    #[inline]
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut {
+            // INVARIANT: There's always a bottom row, starting with NegInf.
+            curr_node: VerticalIter::new(self.inner.top_left.as_ptr())
+                .last()
+                .unwrap(),
+            remaining: self.inner.len(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Iterate over the key-value pairs of the map, in key order.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipMap;
+    /// let mut map = SkipMap::new();
+    /// map.insert('b', 2);
+    /// map.insert('a', 1);
+    ///
+    /// assert_eq!(vec![(&'a', &1), (&'b', &2)], map.iter().collect::<Vec<_>>());
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.inner.iter_all(),
+        }
+    }
+}
+
+impl<K: PartialOrd, V> Default for SkipMap<K, V> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: PartialOrd + Clone, V: Clone> Clone for SkipMap<K, V> {
+    fn clone(&self) -> Self {
+        SkipMap {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<K: PartialOrd + fmt::Debug, V: fmt::Debug> fmt::Debug for SkipMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: PartialOrd, V> FromIterator<(K, V)> for SkipMap<K, V> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = SkipMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: PartialOrd, V> Extend<(K, V)> for SkipMap<K, V> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K: PartialOrd, V> IntoIterator for &'a SkipMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the key-value pairs of a `SkipMap`, made by `SkipMap::iter`.
+pub struct Iter<'a, K, V> {
+    inner: IterAll<'a, Entry<K, V>>,
+}
+
+impl<'a, K: PartialOrd, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|entry| (&entry.key, &entry.value))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.inner.nth(n).map(|entry| (&entry.key, &entry.value))
+    }
+}
+
+impl<'a, K: PartialOrd, V> DoubleEndedIterator for Iter<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next_back()
+            .map(|entry| (&entry.key, &entry.value))
+    }
+}
+
+impl<'a, K: PartialOrd, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+impl<'a, K: PartialOrd, V> FusedIterator for Iter<'a, K, V> {}
+
+/// Iterator over the keys of a `SkipMap`, made by `SkipMap::keys`.
+pub struct Keys<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K: PartialOrd, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(key, _)| key)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.inner.nth(n).map(|(key, _)| key)
+    }
+}
+
+impl<'a, K: PartialOrd, V> DoubleEndedIterator for Keys<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(key, _)| key)
+    }
+}
+
+impl<'a, K: PartialOrd, V> ExactSizeIterator for Keys<'a, K, V> {}
+
+impl<'a, K: PartialOrd, V> FusedIterator for Keys<'a, K, V> {}
+
+/// Iterator over the values of a `SkipMap`, made by `SkipMap::values`.
+pub struct Values<'a, K, V> {
+    inner: Iter<'a, K, V>,
+}
+
+impl<'a, K: PartialOrd, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, value)| value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.inner.nth(n).map(|(_, value)| value)
+    }
+}
+
+impl<'a, K: PartialOrd, V> DoubleEndedIterator for Values<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(_, value)| value)
+    }
+}
+
+impl<'a, K: PartialOrd, V> ExactSizeIterator for Values<'a, K, V> {}
+
+impl<'a, K: PartialOrd, V> FusedIterator for Values<'a, K, V> {}
+
+/// Iterator over mutable references to the values of a `SkipMap`, made by `SkipMap::values_mut`.
+pub struct ValuesMut<'a, K, V> {
+    /// The last node yielded on the bottom row, or NegInf.
+    curr_node: *mut Node<Entry<K, V>>,
+    remaining: usize,
+    _marker: PhantomData<&'a mut SkipMap<K, V>>,
+}
+
+impl<'a, K, V> Iterator for ValuesMut<'a, K, V> {
+    type Item = &'a mut V;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        unsafe {
+            // As there's elements remaining, the node to our right is never PosInf.
+            self.curr_node = (*self.curr_node).right.unwrap().as_ptr();
+            Some(&mut (*self.curr_node).value.get_value_mut().value)
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for ValuesMut<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for ValuesMut<'a, K, V> {}
+
+/// Iterator moving the key-value pairs out of a `SkipMap`, made by `SkipMap::drain`.
+pub struct Drain<'a, K, V> {
+    inner: IntoIter<Entry<K, V>>,
+    _marker: PhantomData<&'a mut SkipMap<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Drain<'a, K, V> {
+    type Item = (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|entry| (entry.key, entry.value))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Drain<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|entry| (entry.key, entry.value))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Drain<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for Drain<'a, K, V> {}
+
+/// Iterator over the key-value pairs in a range of keys, made by `SkipMap::range`.
+pub struct Range<'a, K, V, Q: ?Sized, R> {
+    inner: SkipListRange<'a, Entry<K, V>, KeyRef<Q>, KeyBounds<Q, R>>,
+}
+
+impl<'a, K, V, Q, R> Iterator for Range<'a, K, V, Q, R>
+where
+    K: PartialOrd + Borrow<Q>,
+    Q: PartialOrd + ?Sized,
+    R: RangeBounds<Q>,
+{
+    type Item = (&'a K, &'a V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|entry| (&entry.key, &entry.value))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K, V, Q, R> DoubleEndedIterator for Range<'a, K, V, Q, R>
+where
+    K: PartialOrd + Borrow<Q>,
+    Q: PartialOrd + ?Sized,
+    R: RangeBounds<Q>,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next_back()
+            .map(|entry| (&entry.key, &entry.value))
+    }
+}
+
+impl<'a, K, V, Q, R> ExactSizeIterator for Range<'a, K, V, Q, R>
+where
+    K: PartialOrd + Borrow<Q>,
+    Q: PartialOrd + ?Sized,
+    R: RangeBounds<Q>,
+{
+}
+
+/// Iterator over the key-value pairs in a range of indices, made by `SkipMap::index_range`.
+pub struct IndexRange<'a, K, V, R: RangeBounds<usize>> {
+    inner: SkipListIndexRange<'a, R, Entry<K, V>>,
+}
+
+impl<'a, K, V, R: RangeBounds<usize>> Iterator for IndexRange<'a, K, V, R> {
+    type Item = (&'a K, &'a V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|entry| (&entry.key, &entry.value))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K, V, R: RangeBounds<usize>> DoubleEndedIterator for IndexRange<'a, K, V, R> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next_back()
+            .map(|entry| (&entry.key, &entry.value))
+    }
+}
+
+impl<'a, K, V, R: RangeBounds<usize>> ExactSizeIterator for IndexRange<'a, K, V, R> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::SkipMap;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::BTreeMap;
+    use std::ops::RangeBounds;
+
+    #[test]
+    fn test_skipmap_matches_btreemap() {
+        let mut map = SkipMap::new();
+        let mut expected = BTreeMap::new();
+        let mut rng = StdRng::seed_from_u64(0x3099);
+        for _ in 0..5000 {
+            let key = rng.gen_range(0u32, 300);
+            let value: u64 = rng.gen();
+            match rng.gen_range(0, 3) {
+                0 => assert_eq!(map.remove(&key), expected.remove(&key)),
+                _ => assert_eq!(map.insert(key, value), expected.insert(key, value)),
+            }
+            assert_eq!(map.get(&key), expected.get(&key));
+            assert_eq!(map.len(), expected.len());
+        }
+        assert!(map.iter().eq(expected.iter()));
+        assert!(map.iter().rev().eq(expected.iter().rev()));
+        for (index, pair) in expected.iter().enumerate() {
+            assert_eq!(map.at_index(index), Some(pair));
+        }
+        assert_eq!(map.at_index(expected.len()), None);
+    }
+
+    #[test]
+    fn test_skipmap_macro() {
+        let map = skipmap! {
+            "c" => 3,
+            "a" => 1,
+            "b" => 2,
+            "a" => 10,
+        };
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.get("a"), Some(&10));
+        assert!(map.iter().eq(vec![(&"a", &10), (&"b", &2), (&"c", &3)]));
+        let single = skipmap! { 1u8 => 'x' };
+        assert_eq!(single.at_index(0), Some((&1, &'x')));
+        let empty: SkipMap<u8, char> = skipmap! {};
+        assert!(empty.is_empty());
+    }
+
+    fn in_range<R: RangeBounds<u32>>(map: &SkipMap<u32, u32>, range: R) -> Vec<(&u32, &u32)> {
+        map.iter().filter(|(key, _)| range.contains(key)).collect()
+    }
+
+    #[test]
+    fn test_skipmap_range() {
+        let map: SkipMap<u32, u32> = (0..100).map(|key| (key * 2, key)).collect();
+        for &(start, end) in [(0, 0), (3, 9), (10, 11), (150, 250), (199, 198)].iter() {
+            assert_eq!(
+                map.range(start..end).collect::<Vec<_>>(),
+                in_range(&map, start..end)
+            );
+            let mut rev: Vec<_> = map.range(start..=end).rev().collect();
+            rev.reverse();
+            assert_eq!(rev, in_range(&map, start..=end));
+            assert_eq!(map.range(start..).len(), in_range(&map, start..).len());
+            assert_eq!(map.range(..end).collect::<Vec<_>>(), in_range(&map, ..end));
+        }
+    }
+
+    #[test]
+    fn test_skipmap_keys_values() {
+        let mut map: SkipMap<u32, u32> = (0..500).rev().map(|key| (key, key)).collect();
+        assert!(map.keys().copied().eq(0..500));
+        assert_eq!(map.values_mut().len(), 500);
+        for value in map.values_mut() {
+            *value *= 3;
+        }
+        assert!(map
+            .values()
+            .rev()
+            .copied()
+            .eq((0..500).rev().map(|key| key * 3)));
+        assert!(map
+            .range(100..200)
+            .rev()
+            .map(|(_, value)| *value)
+            .eq((100..200).rev().map(|key| key * 3)));
+        // Lookups that stop on upper levels still see the new values.
+        for key in 0..500 {
+            assert_eq!(map.at_index(key as usize), Some((&key, &(key * 3))));
+            assert_eq!(map.get(&key), Some(&(key * 3)));
+        }
+    }
+
+    #[test]
+    fn test_skipmap_rank() {
+        let map: SkipMap<u32, u32> = (0..200).map(|key| (key * 3, key)).collect();
+        for key in 0..600 {
+            let expected = if key % 3 == 0 {
+                Some(key as usize / 3)
+            } else {
+                None
+            };
+            assert_eq!(map.index_of_key(&key), expected);
+        }
+        for &(start, end) in [(0, 0), (0, 10), (55, 60), (190, 250)].iter() {
+            let pairs: Vec<_> = map.index_range(start..end).collect();
+            let expected: Vec<_> = map.iter().skip(start).take(end - start).collect();
+            assert_eq!(pairs, expected);
+            assert_eq!(map.index_range(start..end).len(), expected.len());
+            assert!(map
+                .index_range(start..end)
+                .rev()
+                .eq(expected.into_iter().rev()));
+        }
+    }
+
+    #[test]
+    fn test_skipmap_get_mut() {
+        let mut map: SkipMap<u32, Vec<u32>> = (0..300).map(|key| (key, vec![])).collect();
+        for key in 0..300 {
+            map.get_mut(&key).unwrap().push(key);
+        }
+        assert_eq!(map.get_mut(&300), None);
+        for key in 0..300 {
+            assert_eq!(map.get_key_value(&key), Some((&key, &vec![key])));
+            assert_eq!(map.at_index(key as usize), Some((&key, &vec![key])));
+        }
+    }
+
+    #[test]
+    fn test_skipmap_retain_drain() {
+        let mut map: SkipMap<u32, u32> = (0..1000).map(|key| (key, 0)).collect();
+        let mut seen = Vec::new();
+        map.retain(|key, value| {
+            seen.push(*key);
+            *value = key + 1;
+            key % 7 == 2
+        });
+        assert_eq!(seen, (0..1000).collect::<Vec<_>>());
+        let expected: Vec<_> = (0..1000).filter(|key| key % 7 == 2).collect();
+        assert_eq!(map.len(), expected.len());
+        for (index, key) in expected.iter().enumerate() {
+            assert_eq!(map.at_index(index), Some((key, &(key + 1))));
+            assert_eq!(map.get(key), Some(&(key + 1)));
+        }
+        let mut drain = map.drain();
+        assert_eq!(drain.len(), expected.len());
+        assert_eq!(drain.next_back(), Some((996, 997)));
+        drop(drain);
+        assert!(map.is_empty());
+        map.insert(1, 1);
+        assert_eq!(map.drain().collect::<Vec<_>>(), vec![(1, 1)]);
+    }
+
+    #[test]
+    fn test_skipmap_from_sorted_iter() {
+        let pairs = (0..2000u32).map(|key| (key / 2, key));
+        let map = SkipMap::from_sorted_iter(pairs);
+        assert_eq!(map.len(), 1000);
+        // The first pair for each key is kept.
+        assert!(map
+            .iter()
+            .map(|(key, value)| (*key, *value))
+            .eq((0..1000).map(|key| (key, key * 2))));
+        for key in 0..1000 {
+            assert_eq!(map.index_of_key(&key), Some(key as usize));
+        }
+        let empty: SkipMap<u32, u32> = SkipMap::from_sorted_iter(None);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_skipmap_from_unsorted_iter() {
+        SkipMap::from_sorted_iter(vec![(1, 'a'), (0, 'b')]);
+    }
+
+    #[test]
+    fn test_skipmap_borrowed_keys() {
+        let mut map: SkipMap<String, usize> = SkipMap::new();
+        for (index, word) in ["pear", "apple", "fig"].iter().enumerate() {
+            map.insert(word.to_string(), index);
+        }
+        assert_eq!(map.get("fig"), Some(&2));
+        assert!(map.contains_key("apple"));
+        assert_eq!(map.remove("pear"), Some(0));
+        assert!(!map.contains_key("pear"));
+        assert_eq!(format!("{:?}", map), r#"{"apple": 1, "fig": 2}"#);
+    }
+}
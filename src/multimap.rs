@@ -0,0 +1,378 @@
+use crate::iter::{IterAll, SkipListRange};
+use crate::map::{KeyBounds, KeyRef};
+use crate::SkipList;
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::{FromIterator, FusedIterator};
+use std::ops::Bound;
+
+/// A key-value pair stored in the skiplist, ordered by key and then by value.
+#[derive(Clone)]
+struct Pair<K, V> {
+    key: K,
+    value: V,
+}
+
+impl<K: PartialEq, V: PartialEq> PartialEq for Pair<K, V> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key && self.value == other.value
+    }
+}
+
+impl<K: PartialOrd, V: PartialOrd> PartialOrd for Pair<K, V> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match self.key.partial_cmp(&other.key) {
+            Some(Ordering::Equal) => self.value.partial_cmp(&other.value),
+            ordering => ordering,
+        }
+    }
+}
+
+// Pairs are sorted by key first, so searching by key alone still finds
+// every pair with that key in one contiguous run.
+impl<K: Borrow<Q>, V, Q: ?Sized> Borrow<KeyRef<Q>> for Pair<K, V> {
+    #[inline]
+    fn borrow(&self) -> &KeyRef<Q> {
+        KeyRef::new(self.key.borrow())
+    }
+}
+
+/// The bounds matching exactly one key.
+type SingleKey<'a, Q> = KeyBounds<Q, (Bound<&'a Q>, Bound<&'a Q>)>;
+
+/// An ordered map where several values can live under one key, like a
+/// secondary index from an attribute to the ids that have it.
+///
+/// Pairs are ordered by key and then by value, so the values under a key
+/// come out sorted, and inserting the exact same pair twice does nothing.
+///
+/// # Example
+///
+/// ```rust
+/// use convenient_skiplist::OrderedMultiMap;
+/// let mut by_colour = OrderedMultiMap::new();
+/// by_colour.insert("red", 3);
+/// by_colour.insert("blue", 1);
+/// by_colour.insert("red", 2);
+///
+/// assert_eq!(by_colour.get_all("red").collect::<Vec<_>>(), vec![&2, &3]);
+/// assert!(by_colour.remove_entry(&"red", &3));
+/// assert_eq!(by_colour.get_all("red").collect::<Vec<_>>(), vec![&2]);
+/// assert_eq!(by_colour.len(), 2);
+/// ```
+pub struct OrderedMultiMap<K, V> {
+    inner: SkipList<Pair<K, V>>,
+}
+
+impl<K: PartialOrd, V: PartialOrd> OrderedMultiMap<K, V> {
+    /// Make a new, empty OrderedMultiMap.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::OrderedMultiMap;
+    /// let map: OrderedMultiMap<u32, u32> = OrderedMultiMap::new();
+    /// assert!(map.is_empty());
+    /// ```
+    #[inline]
+    pub fn new() -> OrderedMultiMap<K, V> {
+        OrderedMultiMap {
+            inner: SkipList::new(),
+        }
+    }
+
+    /// Add `value` under `key`, alongside any values already there.
+    ///
+    /// Returns `false` if this exact pair was already in the map.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::OrderedMultiMap;
+    /// let mut map = OrderedMultiMap::new();
+    ///
+    /// assert!(map.insert(1, 'a'));
+    /// assert!(map.insert(1, 'b'));
+    /// assert!(!map.insert(1, 'a'));
+    /// assert_eq!(map.len(), 2);
+    /// ```
+    #[inline]
+    pub fn insert(&mut self, key: K, value: V) -> bool {
+        self.inner.insert(Pair { key, value })
+    }
+
+    /// Iterate over every value stored under `key`, in order.
+    ///
+    /// This runs in `O(logn + k)`, where k is the number of values under `key`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::OrderedMultiMap;
+    /// let map: OrderedMultiMap<_, _> = vec![(1, 'b'), (2, 'z'), (1, 'a')].into_iter().collect();
+    ///
+    /// assert_eq!(map.get_all(&1).collect::<Vec<_>>(), vec![&'a', &'b']);
+    /// assert_eq!(map.get_all(&1).len(), 2);
+    /// assert_eq!(map.get_all(&3).next(), None);
+    /// ```
+    #[inline]
+    pub fn get_all<'a, Q>(&'a self, key: &'a Q) -> GetAll<'a, K, V, Q>
+    where
+        K: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        GetAll {
+            inner: self
+                .inner
+                .range_bounds(KeyBounds::new((Bound::Included(key), Bound::Included(key)))),
+        }
+    }
+
+    /// Check if there's at least one value stored under `key`.
+    ///
+    /// Runs in `O(logn)` time.
+    #[inline]
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.inner.contains(KeyRef::new(key))
+    }
+
+    /// Remove the pair of `key` and `value`, leaving any other values under
+    /// `key` alone. Returns `true` if the pair was in the map.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::OrderedMultiMap;
+    /// let mut map: OrderedMultiMap<_, _> = vec![(1, 'a'), (1, 'b')].into_iter().collect();
+    ///
+    /// assert!(map.remove_entry(&1, &'a'));
+    /// assert!(!map.remove_entry(&1, &'a'));
+    /// assert!(map.contains_key(&1));
+    /// ```
+    #[inline]
+    pub fn remove_entry(&mut self, key: &K, value: &V) -> bool
+    where
+        K: Clone,
+        V: Clone,
+    {
+        self.inner.remove(&Pair {
+            key: key.clone(),
+            value: value.clone(),
+        })
+    }
+
+    /// Return the number of key-value pairs in the map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns true if the map is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Iterate over every key-value pair of the map, in order.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::OrderedMultiMap;
+    /// let map: OrderedMultiMap<_, _> = vec![(2, 'c'), (1, 'b'), (1, 'a')].into_iter().collect();
+    ///
+    /// let pairs: Vec<_> = map.iter().collect();
+    /// assert_eq!(pairs, vec![(&1, &'a'), (&1, &'b'), (&2, &'c')]);
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.inner.iter_all(),
+        }
+    }
+}
+
+impl<K: PartialOrd, V: PartialOrd> Default for OrderedMultiMap<K, V> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: PartialOrd + Clone, V: PartialOrd + Clone> Clone for OrderedMultiMap<K, V> {
+    fn clone(&self) -> Self {
+        OrderedMultiMap {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<K, V> fmt::Debug for OrderedMultiMap<K, V>
+where
+    K: PartialOrd + fmt::Debug,
+    V: PartialOrd + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: PartialOrd, V: PartialOrd> FromIterator<(K, V)> for OrderedMultiMap<K, V> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = OrderedMultiMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: PartialOrd, V: PartialOrd> Extend<(K, V)> for OrderedMultiMap<K, V> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K: PartialOrd, V: PartialOrd> IntoIterator for &'a OrderedMultiMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the key-value pairs of an `OrderedMultiMap`, made by `OrderedMultiMap::iter`.
+pub struct Iter<'a, K, V> {
+    inner: IterAll<'a, Pair<K, V>>,
+}
+
+impl<'a, K: PartialOrd, V: PartialOrd> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|pair| (&pair.key, &pair.value))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.inner.nth(n).map(|pair| (&pair.key, &pair.value))
+    }
+}
+
+impl<'a, K: PartialOrd, V: PartialOrd> DoubleEndedIterator for Iter<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|pair| (&pair.key, &pair.value))
+    }
+}
+
+impl<'a, K: PartialOrd, V: PartialOrd> ExactSizeIterator for Iter<'a, K, V> {}
+
+impl<'a, K: PartialOrd, V: PartialOrd> FusedIterator for Iter<'a, K, V> {}
+
+/// Iterator over the values under one key of an `OrderedMultiMap`, made by `OrderedMultiMap::get_all`.
+pub struct GetAll<'a, K, V, Q: ?Sized> {
+    inner: SkipListRange<'a, Pair<K, V>, KeyRef<Q>, SingleKey<'a, Q>>,
+}
+
+impl<'a, K, V, Q> Iterator for GetAll<'a, K, V, Q>
+where
+    K: PartialOrd + Borrow<Q>,
+    V: PartialOrd,
+    Q: PartialOrd + ?Sized,
+{
+    type Item = &'a V;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|pair| &pair.value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K, V, Q> DoubleEndedIterator for GetAll<'a, K, V, Q>
+where
+    K: PartialOrd + Borrow<Q>,
+    V: PartialOrd,
+    Q: PartialOrd + ?Sized,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|pair| &pair.value)
+    }
+}
+
+impl<'a, K, V, Q> ExactSizeIterator for GetAll<'a, K, V, Q>
+where
+    K: PartialOrd + Borrow<Q>,
+    V: PartialOrd,
+    Q: PartialOrd + ?Sized,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::OrderedMultiMap;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::collections::{BTreeMap, BTreeSet};
+
+    #[test]
+    fn test_multimap_matches_btree() {
+        let mut map = OrderedMultiMap::new();
+        let mut expected: BTreeMap<u32, BTreeSet<u32>> = BTreeMap::new();
+        let mut rng = StdRng::seed_from_u64(0x3106);
+        for _ in 0..5000 {
+            let key = rng.gen_range(0u32, 50);
+            let value = rng.gen_range(0u32, 20);
+            if rng.gen_range(0, 3) == 0 {
+                let removed = expected
+                    .get_mut(&key)
+                    .is_some_and(|values| values.remove(&value));
+                assert_eq!(map.remove_entry(&key, &value), removed);
+            } else {
+                assert_eq!(
+                    map.insert(key, value),
+                    expected.entry(key).or_default().insert(value)
+                );
+            }
+            let values: Vec<_> = expected.get(&key).into_iter().flatten().collect();
+            assert!(map.get_all(&key).eq(values.iter().copied()));
+            assert!(map.get_all(&key).rev().eq(values.iter().rev().copied()));
+            assert_eq!(map.contains_key(&key), !values.is_empty());
+        }
+        let pairs = expected
+            .iter()
+            .flat_map(|(key, values)| values.iter().map(move |value| (key, value)));
+        assert!(map.iter().eq(pairs));
+        assert_eq!(
+            map.len(),
+            expected.values().map(|values| values.len()).sum::<usize>()
+        );
+    }
+}
@@ -0,0 +1,614 @@
+use crate::iter::IterAll;
+use crate::map::{KeyBounds, KeyRef};
+use crate::SkipList;
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::{FromIterator, FusedIterator};
+use std::ops::Bound;
+
+/// An element stored in the skiplist, tagged with when it was inserted
+/// so that equal elements are still distinct nodes.
+#[derive(Clone)]
+struct Dup<T> {
+    value: T,
+    seq: u64,
+}
+
+impl<T: PartialEq> PartialEq for Dup<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value && self.seq == other.seq
+    }
+}
+
+impl<T: PartialOrd> PartialOrd for Dup<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match self.value.partial_cmp(&other.value) {
+            Some(Ordering::Equal) => self.seq.partial_cmp(&other.seq),
+            ordering => ordering,
+        }
+    }
+}
+
+// Equal elements sit next to each other, so searching by value alone
+// lands on the first of them.
+impl<T: Borrow<Q>, Q: ?Sized> Borrow<KeyRef<Q>> for Dup<T> {
+    #[inline]
+    fn borrow(&self) -> &KeyRef<Q> {
+        KeyRef::new(self.value.borrow())
+    }
+}
+
+/// A skiplist that keeps duplicate elements, also known as a multiset or bag.
+///
+/// Unlike `SkipList`, `insert` always succeeds and equal elements live side
+/// by side, so a leaderboard can hold several players with the same score
+/// without tie-breaking tuples. Indexing counts every copy.
+///
+/// Equal elements are kept in the order they were inserted: `insert` places
+/// a new element after any equal ones, and `remove` takes out the oldest.
+/// That makes this usable as a fair priority queue, where ties are served
+/// first come, first served.
+///
+/// # Example
+///
+/// ```rust
+/// use convenient_skiplist::SkipMultiSet;
+/// let mut scores = SkipMultiSet::new();
+/// scores.insert(10);
+/// scores.insert(30);
+/// scores.insert(10);
+///
+/// assert_eq!(scores.len(), 3);
+/// assert_eq!(scores.iter().collect::<Vec<_>>(), vec![&10, &10, &30]);
+/// assert_eq!(scores.index_of(&30), Some(2));
+/// assert!(scores.remove(&10));
+/// assert_eq!(scores.len(), 2);
+/// ```
+pub struct SkipMultiSet<T> {
+    inner: SkipList<Dup<T>>,
+    /// The tag for the next element inserted.
+    next_seq: u64,
+}
+
+impl<T: PartialOrd> SkipMultiSet<T> {
+    /// Make a new, empty SkipMultiSet.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipMultiSet;
+    /// let ms: SkipMultiSet<u32> = SkipMultiSet::new();
+    /// assert!(ms.is_empty());
+    /// ```
+    #[inline]
+    pub fn new() -> SkipMultiSet<T> {
+        SkipMultiSet {
+            inner: SkipList::new(),
+            next_seq: 0,
+        }
+    }
+
+    /// Insert `item`, even if an equal element is already in the multiset.
+    ///
+    /// `item` goes after every element equal to it.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipMultiSet;
+    /// let mut ms = SkipMultiSet::new();
+    /// ms.insert('a');
+    /// ms.insert('a');
+    ///
+    /// assert_eq!(ms.len(), 2);
+    /// ```
+    #[inline]
+    pub fn insert(&mut self, item: T) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.inner.insert(Dup { value: item, seq });
+    }
+
+    /// Check if at least one element equal to `item` is in the multiset.
+    ///
+    /// Runs in `O(logn)` time.
+    #[inline]
+    pub fn contains<Q>(&self, item: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.inner.contains(KeyRef::new(item))
+    }
+
+    /// Remove one element equal to `item`. Returns `true` if there was one to remove.
+    ///
+    /// Of the elements equal to `item`, the one inserted first is removed.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipMultiSet;
+    /// let mut ms: SkipMultiSet<_> = vec![1, 1, 2].into_iter().collect();
+    ///
+    /// assert!(ms.remove(&1));
+    /// assert!(ms.remove(&1));
+    /// assert!(!ms.remove(&1));
+    /// assert_eq!(ms.len(), 1);
+    /// ```
+    #[inline]
+    pub fn remove<Q>(&mut self, item: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.inner.take(KeyRef::new(item)).is_some()
+    }
+
+    /// Remove every element equal to `item`, returning how many were removed.
+    ///
+    /// The copies sit next to each other, so they're unlinked as one run on
+    /// each level instead of being removed one at a time.
+    ///
+    /// Runs in `O(logn + k)` time, where k is the number of copies.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipMultiSet;
+    /// let mut ms: SkipMultiSet<_> = vec![1, 2, 2, 2, 3].into_iter().collect();
+    ///
+    /// assert_eq!(ms.remove_all(&2), 3);
+    /// assert_eq!(ms.remove_all(&2), 0);
+    /// assert_eq!(ms.iter().collect::<Vec<_>>(), vec![&1, &3]);
+    /// ```
+    pub fn remove_all<Q>(&mut self, item: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        let start = self.rank(item);
+        let end = start + self.count(item);
+        self.inner.splice_out(start, end)
+    }
+
+    /// Collapse every run of equal elements down to its first element,
+    /// returning how many copies were removed.
+    ///
+    /// This is a single pass over the skiplist, unlinking the towers of
+    /// the extra copies as it goes.
+    ///
+    /// Runs in `O(n)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipMultiSet;
+    /// let mut ms: SkipMultiSet<_> = vec![3, 1, 3, 2, 3, 1].into_iter().collect();
+    ///
+    /// assert_eq!(ms.dedup(), 3);
+    /// assert_eq!(ms.iter().collect::<Vec<_>>(), vec![&1, &2, &3]);
+    /// ```
+    pub fn dedup(&mut self) -> usize {
+        // The last element kept stays in the skiplist, so it can be compared against.
+        let mut last: Option<*const T> = None;
+        self.inner.retain_nodes(|dup| {
+            let keep = last.is_none_or(|last| unsafe { &*last } != &dup.value);
+            if keep {
+                last = Some(&dup.value);
+            }
+            keep
+        })
+    }
+
+    /// Count how many elements equal to `item` are in the multiset.
+    ///
+    /// This is the difference of two rank queries, so it runs in `O(logn)`
+    /// time no matter how many copies there are.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipMultiSet;
+    /// let ms: SkipMultiSet<_> = vec![1, 2, 2, 2, 3].into_iter().collect();
+    ///
+    /// assert_eq!(ms.count(&2), 3);
+    /// assert_eq!(ms.count(&3), 1);
+    /// assert_eq!(ms.count(&4), 0);
+    /// ```
+    #[inline]
+    pub fn count<Q>(&self, item: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        // The range's length comes from the index of the first copy and
+        // the index past the last, found with two searches.
+        self.inner
+            .range_bounds(KeyBounds::new((
+                Bound::Included(item),
+                Bound::Included(item),
+            )))
+            .len()
+    }
+
+    /// Pop the smallest element off of the multiset. Among equal elements,
+    /// the one inserted first is popped first.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipMultiSet;
+    /// use std::cmp::Ordering;
+    ///
+    /// #[derive(Clone, Debug, PartialEq)]
+    /// struct Job {
+    ///     priority: u32,
+    ///     name: &'static str,
+    /// }
+    ///
+    /// impl PartialOrd for Job {
+    ///     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    ///         self.priority.partial_cmp(&other.priority)
+    ///     }
+    /// }
+    ///
+    /// let mut queue = SkipMultiSet::new();
+    /// queue.insert(Job { priority: 1, name: "first" });
+    /// queue.insert(Job { priority: 0, name: "urgent" });
+    /// queue.insert(Job { priority: 1, name: "second" });
+    ///
+    /// let order: Vec<_> = std::iter::from_fn(|| queue.pop_front()).map(|job| job.name).collect();
+    /// assert_eq!(order, vec!["urgent", "first", "second"]);
+    /// ```
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.inner.pop_front().map(|dup| dup.value)
+    }
+
+    /// Return the number of elements in the multiset, counting every copy.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns true if the multiset is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Find the index of the first element equal to `item`.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipMultiSet;
+    /// let ms: SkipMultiSet<_> = vec![1, 2, 2, 3].into_iter().collect();
+    ///
+    /// assert_eq!(ms.index_of(&2), Some(1));
+    /// assert_eq!(ms.index_of(&3), Some(3));
+    /// assert_eq!(ms.index_of(&4), None);
+    /// ```
+    #[inline]
+    pub fn index_of<Q>(&self, item: &Q) -> Option<usize>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.inner.index_of(KeyRef::new(item))
+    }
+
+    /// Count how many elements are strictly less than `item`.
+    ///
+    /// Runs in `O(logn)` time.
+    #[inline]
+    pub fn rank<Q>(&self, item: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.inner.rank(KeyRef::new(item))
+    }
+
+    /// Get the element at `index`, counting every copy.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::SkipMultiSet;
+    /// let ms: SkipMultiSet<_> = vec![3, 1, 3].into_iter().collect();
+    ///
+    /// assert_eq!(ms.at_index(1), Some(&3));
+    /// assert_eq!(ms.at_index(3), None);
+    /// ```
+    #[inline]
+    pub fn at_index(&self, index: usize) -> Option<&T> {
+        self.inner.at_index(index).map(|dup| &dup.value)
+    }
+
+    /// Iterate over every element of the multiset in order, including copies.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            inner: self.inner.iter_all(),
+        }
+    }
+}
+
+impl<T: PartialOrd> Default for SkipMultiSet<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PartialOrd + Clone> Clone for SkipMultiSet<T> {
+    fn clone(&self) -> Self {
+        SkipMultiSet {
+            inner: self.inner.clone(),
+            next_seq: self.next_seq,
+        }
+    }
+}
+
+impl<T: PartialOrd + fmt::Debug> fmt::Debug for SkipMultiSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialOrd> FromIterator<T> for SkipMultiSet<T> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut ms = SkipMultiSet::new();
+        ms.extend(iter);
+        ms
+    }
+}
+
+impl<T: PartialOrd> Extend<T> for SkipMultiSet<T> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.insert(item);
+        }
+    }
+}
+
+impl<'a, T: PartialOrd> IntoIterator for &'a SkipMultiSet<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the elements of a `SkipMultiSet`, made by `SkipMultiSet::iter`.
+pub struct Iter<'a, T> {
+    inner: IterAll<'a, Dup<T>>,
+}
+
+impl<'a, T: PartialOrd> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|dup| &dup.value)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.inner.nth(n).map(|dup| &dup.value)
+    }
+}
+
+impl<'a, T: PartialOrd> DoubleEndedIterator for Iter<'a, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|dup| &dup.value)
+    }
+}
+
+impl<'a, T: PartialOrd> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T: PartialOrd> FusedIterator for Iter<'a, T> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::SkipMultiSet;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::cmp::Ordering;
+
+    #[derive(Clone, Debug)]
+    struct Job {
+        priority: u32,
+        id: u32,
+    }
+
+    impl PartialEq for Job {
+        fn eq(&self, other: &Self) -> bool {
+            self.priority == other.priority
+        }
+    }
+
+    impl PartialOrd for Job {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            self.priority.partial_cmp(&other.priority)
+        }
+    }
+
+    #[test]
+    fn test_multiset_fifo() {
+        let mut queue = SkipMultiSet::new();
+        let mut rng = StdRng::seed_from_u64(0x3113);
+        let mut expected: Vec<Vec<u32>> = vec![Vec::new(); 5];
+        for id in 0..2000 {
+            let priority = rng.gen_range(0, 5);
+            if rng.gen_range(0, 4) == 0 {
+                // Removing an equal element takes the oldest.
+                let removed = queue.remove(&Job { priority, id: 0 });
+                let oldest = &mut expected[priority as usize];
+                assert_eq!(removed, !oldest.is_empty());
+                if removed {
+                    oldest.remove(0);
+                }
+            } else {
+                queue.insert(Job { priority, id });
+                expected[priority as usize].push(id);
+            }
+        }
+        let ids: Vec<_> = queue.iter().map(|job| job.id).collect();
+        assert_eq!(ids, expected.concat());
+        let popped: Vec<_> = std::iter::from_fn(|| queue.pop_front())
+            .map(|job| job.id)
+            .collect();
+        assert_eq!(popped, expected.concat());
+    }
+
+    #[test]
+    fn test_multiset_matches_sorted_vec() {
+        let mut ms = SkipMultiSet::new();
+        let mut expected: Vec<u32> = Vec::new();
+        let mut rng = StdRng::seed_from_u64(0x3109);
+        for _ in 0..5000 {
+            let item = rng.gen_range(0u32, 40);
+            if rng.gen_range(0, 20) == 0 {
+                let before = expected.len();
+                expected.retain(|&x| x != item);
+                assert_eq!(ms.remove_all(&item), before - expected.len());
+            } else if rng.gen_range(0, 3) == 0 {
+                let position = expected.iter().position(|&x| x == item);
+                assert_eq!(ms.remove(&item), position.is_some());
+                if let Some(position) = position {
+                    expected.remove(position);
+                }
+            } else {
+                ms.insert(item);
+                let position = expected.partition_point(|&x| x <= item);
+                expected.insert(position, item);
+            }
+            assert_eq!(ms.len(), expected.len());
+            assert_eq!(ms.contains(&item), expected.contains(&item));
+            assert_eq!(ms.index_of(&item), expected.iter().position(|&x| x == item));
+            assert_eq!(ms.rank(&item), expected.partition_point(|&x| x < item));
+            assert_eq!(
+                ms.count(&item),
+                expected.iter().filter(|&&x| x == item).count()
+            );
+        }
+        assert!(ms.iter().eq(expected.iter()));
+        assert!(ms.iter().rev().eq(expected.iter().rev()));
+        assert_eq!(ms.count(&40), 0);
+        for (index, item) in expected.iter().enumerate() {
+            assert_eq!(ms.at_index(index), Some(item));
+        }
+        let before = expected.len();
+        expected.dedup();
+        assert_eq!(ms.dedup(), before - expected.len());
+        assert!(ms.iter().eq(expected.iter()));
+        assert!(expected.iter().all(|item| ms.count(item) == 1));
+    }
+
+    #[test]
+    fn test_multiset_count() {
+        let mut ms = SkipMultiSet::new();
+        for copies in 0..300 {
+            for _ in 0..copies % 7 {
+                ms.insert(copies.to_string());
+            }
+        }
+        for copies in 0..300 {
+            assert_eq!(ms.count(copies.to_string().as_str()), copies % 7);
+        }
+        assert_eq!(ms.count("none"), 0);
+        // Copies only need to compare equal to be counted.
+        let jobs: SkipMultiSet<_> = (0..50)
+            .map(|id| Job {
+                priority: id % 3,
+                id,
+            })
+            .collect();
+        assert_eq!(jobs.count(&Job { priority: 1, id: 0 }), 17);
+        assert_eq!(jobs.count(&Job { priority: 3, id: 0 }), 0);
+    }
+
+    #[test]
+    fn test_multiset_remove_all() {
+        let mut ms: SkipMultiSet<_> = (0..600)
+            .map(|id| Job {
+                priority: id % 4,
+                id,
+            })
+            .collect();
+        assert_eq!(ms.remove_all(&Job { priority: 2, id: 0 }), 150);
+        assert_eq!(ms.remove_all(&Job { priority: 2, id: 0 }), 0);
+        assert_eq!(ms.remove_all(&Job { priority: 9, id: 0 }), 0);
+        assert_eq!(ms.len(), 450);
+        // The copies left behind keep their order, and nothing else went.
+        let ids: Vec<_> = ms.iter().map(|job| job.id).collect();
+        let expected: Vec<_> = [0, 1, 3]
+            .iter()
+            .flat_map(|&priority| (priority..600).step_by(4))
+            .collect();
+        assert_eq!(ids, expected);
+        for (index, job) in ms.iter().enumerate() {
+            assert_eq!(ms.at_index(index).map(|at| at.id), Some(job.id));
+        }
+        assert_eq!(ms.remove_all(&Job { priority: 0, id: 0 }), 150);
+        assert_eq!(ms.remove_all(&Job { priority: 3, id: 0 }), 150);
+        assert_eq!(ms.remove_all(&Job { priority: 1, id: 0 }), 150);
+        assert!(ms.is_empty());
+        assert_eq!(ms.iter().next(), None);
+    }
+
+    #[test]
+    fn test_multiset_dedup() {
+        let mut ms: SkipMultiSet<_> = (0..500)
+            .map(|id| Job {
+                priority: (id * 7) % 10,
+                id,
+            })
+            .collect();
+        assert_eq!(ms.dedup(), 490);
+        assert_eq!(ms.dedup(), 0);
+        // The first copy inserted is the one kept.
+        let kept: Vec<_> = ms.iter().map(|job| (job.priority, job.id)).collect();
+        let expected: Vec<_> = (0..10)
+            .map(|priority| (priority, priority * 3 % 10))
+            .collect();
+        assert_eq!(kept, expected);
+        for job in ms.iter() {
+            assert_eq!(ms.count(job), 1);
+        }
+        // The skiplist is still usable afterwards.
+        ms.insert(Job {
+            priority: 4,
+            id: 500,
+        });
+        assert_eq!(ms.count(&Job { priority: 4, id: 0 }), 2);
+        assert_eq!(ms.at_index(5).map(|job| job.id), Some(500));
+        assert_eq!(SkipMultiSet::<u32>::new().dedup(), 0);
+    }
+}
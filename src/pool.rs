@@ -0,0 +1,555 @@
+use crate::{Node, NodeValue};
+#[cfg(feature = "allocator_api")]
+use std::alloc::Allocator;
+use std::alloc::{alloc, dealloc, handle_alloc_error, Layout};
+#[cfg(feature = "allocator_api")]
+use std::fmt;
+use std::mem::ManuallyDrop;
+use std::ptr::NonNull;
+use std::sync::atomic::AtomicUsize;
+
+/// The start of a tower's allocation, which holds the element's value and
+/// is followed by the tower's nodes, bottom first.
+///
+/// Every node of the tower points back here for its value, so it's only
+/// stored once. The value is dropped and the allocation released once all
+/// of the nodes are freed, in whatever order that happens.
+pub(crate) struct TowerHeader<T> {
+    value: ManuallyDrop<T>,
+    /// How many nodes follow the header.
+    height: usize,
+    /// How many nodes, in the allocation or not, share the value and
+    /// haven't been freed yet.
+    live: usize,
+    /// Whether the tower was carved out of one of the pool's chunks.
+    pooled: bool,
+    /// Whether the value was moved out.
+    taken: bool,
+}
+
+impl<T> TowerHeader<T> {
+    #[inline]
+    pub(crate) fn value(&self) -> &T {
+        debug_assert!(!self.taken);
+        &self.value
+    }
+
+    #[inline]
+    pub(crate) fn value_mut(&mut self) -> &mut T {
+        debug_assert!(!self.taken);
+        &mut self.value
+    }
+
+    /// Move the value out. Its nodes can only be freed afterwards.
+    #[inline]
+    pub(crate) fn take(&mut self) -> T {
+        assert!(!self.taken, "Failed to take value! This shouldn't happen.");
+        self.taken = true;
+        unsafe { ManuallyDrop::take(&mut self.value) }
+    }
+}
+
+/// A custom allocator for nodes, boxed up so `SkipList` doesn't need a type
+/// parameter for it.
+#[cfg(feature = "allocator_api")]
+pub(crate) trait NodeAllocator: Allocator + Send + Sync {
+    fn clone_box(&self) -> Box<dyn NodeAllocator>;
+}
+
+#[cfg(feature = "allocator_api")]
+impl<A: Allocator + Clone + Send + Sync + 'static> NodeAllocator for A {
+    fn clone_box(&self) -> Box<dyn NodeAllocator> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(feature = "allocator_api")]
+impl Clone for Box<dyn NodeAllocator> {
+    fn clone(&self) -> Self {
+        (**self).clone_box()
+    }
+}
+
+#[cfg(feature = "allocator_api")]
+impl fmt::Debug for dyn NodeAllocator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("NodeAllocator")
+    }
+}
+
+/// Storage for nodes, so a search walking down a tower stays in one
+/// allocation, and building a large skiplist needs few heap allocations.
+///
+/// Each element's tower is allocated in one go, as a [`TowerHeader`] holding
+/// the value followed by the tower's nodes. Towers are carved in order out of the newest reserved chunk;
+/// once it's used up (or if there never was one), each tower gets its own
+/// heap allocation instead. A tower with its own allocation is released when
+/// its last node is freed, while a pooled tower is only released with the
+/// whole pool.
+///
+/// Once every node of a tower is freed, its allocation is kept on a free
+/// list for the next tower of the same height, so workloads that insert
+/// about as much as they remove settle into reusing the same memory. Up to
+/// `MAX_RECYCLED_TOWERS` allocations are kept; any more are released.
+///
+/// As an arena, the pool instead allocates a new chunk (twice as large as the
+/// last) whenever it runs out, so every tower is pooled.
+///
+/// Everything, including the sentinel nodes, is allocated through the
+/// pool's allocator, which is the global one unless the `allocator_api`
+/// feature is used to pick another.
+///
+/// The pool also counts how many nodes it has made and freed, which
+/// iterators use to detect the skiplist changing underneath them.
+pub(crate) struct NodePool<T> {
+    chunks: Vec<(NonNull<u8>, Layout)>,
+    /// How many bytes of the last chunk have been handed out.
+    used: usize,
+    arena: bool,
+    /// Allocations of towers whose nodes were all freed, by height.
+    recycled: Vec<Vec<NonNull<TowerHeader<T>>>>,
+    /// How many allocations `recycled` holds in total.
+    recycled_len: usize,
+    #[cfg(feature = "allocator_api")]
+    allocator: Option<Box<dyn NodeAllocator>>,
+    generation: AtomicUsize,
+    _marker: std::marker::PhantomData<Node<T>>,
+}
+
+impl<T> Default for NodePool<T> {
+    fn default() -> Self {
+        NodePool {
+            chunks: Vec::new(),
+            used: 0,
+            arena: false,
+            recycled: Vec::new(),
+            recycled_len: 0,
+            #[cfg(feature = "allocator_api")]
+            allocator: None,
+            generation: AtomicUsize::new(0),
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T> Drop for NodePool<T> {
+    fn drop(&mut self) {
+        for (height, towers) in std::mem::take(&mut self.recycled).into_iter().enumerate() {
+            let (layout, _) = Self::tower_layout(height);
+            for header in towers {
+                unsafe {
+                    if !header.as_ref().pooled {
+                        self.deallocate(header.as_ptr() as *mut u8, layout);
+                    }
+                }
+            }
+        }
+        for (chunk, layout) in std::mem::take(&mut self.chunks) {
+            unsafe { self.deallocate(chunk.as_ptr(), layout) };
+        }
+    }
+}
+
+/// The smallest chunk an arena allocates, in bytes.
+const MIN_ARENA_CHUNK: usize = 4096;
+
+/// The most allocations of freed towers a pool keeps around for reuse.
+const MAX_RECYCLED_TOWERS: usize = 1024;
+
+impl<T> NodePool<T> {
+    /// Carve every tower out of the pool's own chunks.
+    #[inline]
+    pub(crate) fn set_arena(&mut self) {
+        self.arena = true;
+    }
+
+    #[cfg(test)]
+    pub(crate) fn is_arena(&self) -> bool {
+        self.arena
+    }
+
+    #[cfg(test)]
+    pub(crate) fn recycled_len(&self) -> usize {
+        self.recycled_len
+    }
+
+    #[cfg(feature = "allocator_api")]
+    #[inline]
+    pub(crate) fn set_allocator(&mut self, allocator: Box<dyn NodeAllocator>) {
+        self.allocator = Some(allocator);
+    }
+
+    /// Make an empty pool configured like this one.
+    pub(crate) fn fresh(&self) -> Self {
+        let mut pool = NodePool::default();
+        pool.arena = self.arena;
+        #[cfg(feature = "allocator_api")]
+        {
+            pool.allocator = self.allocator.clone();
+        }
+        pool
+    }
+
+    /// Whether nodes made by `other` can be freed by this pool, once it
+    /// takes over `other`'s storage with `append`.
+    #[inline]
+    pub(crate) fn can_adopt(&self, other: &NodePool<T>) -> bool {
+        // Two custom allocators can't be told apart, so only the global one is known to match.
+        #[cfg(feature = "allocator_api")]
+        {
+            self.allocator.is_none() && other.allocator.is_none()
+        }
+        #[cfg(not(feature = "allocator_api"))]
+        {
+            let _ = other;
+            true
+        }
+    }
+
+    #[inline]
+    fn allocate(&self, layout: Layout) -> *mut u8 {
+        #[cfg(feature = "allocator_api")]
+        if let Some(allocator) = &self.allocator {
+            return match allocator.allocate(layout) {
+                Ok(block) => block.as_ptr() as *mut u8,
+                Err(_) => handle_alloc_error(layout),
+            };
+        }
+        let block = unsafe { alloc(layout) };
+        if block.is_null() {
+            handle_alloc_error(layout);
+        }
+        block
+    }
+
+    /// # Safety
+    ///
+    /// `block` must have been allocated by this pool (or the pool it took
+    /// over with `append`) with `layout`.
+    #[inline]
+    unsafe fn deallocate(&self, block: *mut u8, layout: Layout) {
+        #[cfg(feature = "allocator_api")]
+        if let Some(allocator) = &self.allocator {
+            allocator.deallocate(NonNull::new_unchecked(block), layout);
+            return;
+        }
+        dealloc(block, layout);
+    }
+
+    /// The layout of a tower of `height` nodes, and the offset of its bottom node.
+    #[inline]
+    fn tower_layout(height: usize) -> (Layout, usize) {
+        let nodes = Layout::array::<Node<T>>(height).expect("tower too tall");
+        let (layout, offset) = Layout::new::<TowerHeader<T>>()
+            .extend(nodes)
+            .expect("tower too tall");
+        (layout.pad_to_align(), offset)
+    }
+
+    /// How many bytes can still be handed out without allocating.
+    #[inline]
+    fn available(&self) -> usize {
+        self.chunks
+            .last()
+            .map_or(0, |(_, layout)| layout.size() - self.used)
+    }
+
+    /// Make sure there's room for `towers` more towers, holding `nodes` nodes in total.
+    pub(crate) fn reserve(&mut self, towers: usize, nodes: usize) {
+        let (single, _) = Self::tower_layout(1);
+        // Every tower pays for one header, and at worst a node's worth of padding.
+        let bytes = towers * (single.size() + single.align())
+            + nodes.saturating_sub(towers) * std::mem::size_of::<Node<T>>();
+        if bytes == 0 || self.available() >= bytes {
+            return;
+        }
+        self.push_chunk(bytes);
+    }
+
+    /// Start handing out bytes from a new chunk of `bytes` bytes.
+    fn push_chunk(&mut self, bytes: usize) {
+        let (single, _) = Self::tower_layout(1);
+        let layout = Layout::from_size_align(bytes, single.align()).expect("capacity overflow");
+        let chunk = unsafe { NonNull::new_unchecked(self.allocate(layout)) };
+        self.chunks.push((chunk, layout));
+        self.used = 0;
+    }
+
+    /// Carve `layout` out of the newest chunk, if it has room left.
+    #[inline]
+    fn carve(&mut self, layout: Layout) -> Option<*mut u8> {
+        let (chunk, chunk_layout) = self.chunks.last()?;
+        let start = (self.used + layout.align() - 1) & !(layout.align() - 1);
+        if start + layout.size() > chunk_layout.size() {
+            return None;
+        }
+        self.used = start + layout.size();
+        Some(unsafe { chunk.as_ptr().add(start) })
+    }
+
+    /// Make a tower of `height` nodes holding `value`, and return its bottom node.
+    ///
+    /// The nodes are contiguous, so the node on level `i` is `i` nodes after the
+    /// bottom one. Each has its `down` link set, but no right neighbour and a
+    /// width of zero.
+    pub(crate) fn make_tower(&mut self, value: T, height: usize) -> NonNull<Node<T>> {
+        debug_assert!(height > 0);
+        let (layout, offset) = Self::tower_layout(height);
+        let recycled = self.recycled.get_mut(height).and_then(Vec::pop);
+        if recycled.is_some() {
+            self.recycled_len -= 1;
+        }
+        let (block, pooled) = match recycled {
+            Some(header) => (header.as_ptr() as *mut u8, unsafe {
+                header.as_ref().pooled
+            }),
+            None => self.new_tower(layout),
+        };
+        self.bump_generation();
+        unsafe {
+            let header = block as *mut TowerHeader<T>;
+            header.write(TowerHeader {
+                value: ManuallyDrop::new(value),
+                height,
+                live: height,
+                pooled,
+                taken: false,
+            });
+            let header = NonNull::new_unchecked(header);
+            let bottom = block.add(offset) as *mut Node<T>;
+            for level in 0..height {
+                bottom.add(level).write(Node {
+                    right: None,
+                    down: level
+                        .checked_sub(1)
+                        .map(|below| NonNull::new_unchecked(bottom.add(below))),
+                    value: NodeValue::Value(header, 0),
+                });
+            }
+            NonNull::new_unchecked(bottom)
+        }
+    }
+
+    /// The bytes a live tower allocated on its own, or zero if it's pooled.
+    #[inline]
+    pub(crate) fn tower_bytes(header: &TowerHeader<T>) -> usize {
+        if header.pooled {
+            0
+        } else {
+            Self::tower_layout(header.height).0.size()
+        }
+    }
+
+    /// The bytes held by the pool itself: its chunks, and the towers
+    /// waiting on the free list.
+    pub(crate) fn reserved_bytes(&self) -> usize {
+        let chunks: usize = self.chunks.iter().map(|(_, layout)| layout.size()).sum();
+        let recycled: usize = self
+            .recycled
+            .iter()
+            .flatten()
+            .map(|header| unsafe { Self::tower_bytes(header.as_ref()) })
+            .sum();
+        chunks + recycled
+    }
+
+    /// Allocate a tower with `layout`, returning it and whether it's pooled.
+    fn new_tower(&mut self, layout: Layout) -> (*mut u8, bool) {
+        match self.carve(layout) {
+            Some(block) => (block, true),
+            None if self.arena => {
+                let last = self.chunks.last().map_or(0, |(_, chunk)| chunk.size());
+                self.push_chunk((last * 2).max(MIN_ARENA_CHUNK).max(layout.size()));
+                // INVARIANT: The new chunk is large enough for the tower.
+                (self.carve(layout).unwrap(), true)
+            }
+            None => (self.allocate(layout), false),
+        }
+    }
+
+    /// Make a node above `top`, outside its tower's allocation, sharing its value.
+    ///
+    /// The node has no right neighbour and a width of zero.
+    ///
+    /// # Safety
+    ///
+    /// `top` must be a live node holding a value.
+    pub(crate) unsafe fn make_node_above(&mut self, top: NonNull<Node<T>>) -> NonNull<Node<T>> {
+        self.bump_generation();
+        let header = match top.as_ref().value {
+            NodeValue::Value(header, _) => header,
+            _ => unreachable!("Failed to get value! This shouldn't happen."),
+        };
+        (*header.as_ptr()).live += 1;
+        self.make_loose(Node {
+            right: None,
+            down: Some(top),
+            value: NodeValue::Value(header, 0),
+        })
+    }
+
+    /// Make the NegInf and PosInf nodes of a new row, and return the NegInf one.
+    pub(crate) fn make_sentinels(&mut self, width: usize) -> NonNull<Node<T>> {
+        let right = self.make_loose(Node {
+            right: None,
+            down: None,
+            value: NodeValue::PosInf(1),
+        });
+        let mut left = Node {
+            right: Some(right),
+            down: None,
+            value: NodeValue::NegInf(0),
+        };
+        left.set_width(width);
+        self.make_loose(left)
+    }
+
+    /// Allocate a node on its own, outside of any tower's allocation.
+    #[inline]
+    fn make_loose(&mut self, node: Node<T>) -> NonNull<Node<T>> {
+        let block = self.allocate(Layout::new::<Node<T>>()) as *mut Node<T>;
+        unsafe {
+            block.write(node);
+            NonNull::new_unchecked(block)
+        }
+    }
+
+    /// Bumped every time a node is made or freed.
+    #[inline]
+    pub(crate) fn generation(&self) -> &AtomicUsize {
+        &self.generation
+    }
+
+    #[inline]
+    fn bump_generation(&mut self) {
+        let generation = self.generation.get_mut();
+        *generation = generation.wrapping_add(1);
+    }
+
+    /// Free a node made by this pool.
+    ///
+    /// # Safety
+    ///
+    /// `node` must not be used afterwards.
+    #[inline]
+    pub(crate) unsafe fn free(&mut self, node: NonNull<Node<T>>) {
+        self.bump_generation();
+        let header = match node.as_ref().value {
+            NodeValue::Value(header, _) => header.as_ptr(),
+            _ => {
+                self.deallocate(node.as_ptr() as *mut u8, Layout::new::<Node<T>>());
+                return;
+            }
+        };
+        let (layout, offset) = Self::tower_layout((*header).height);
+        let bottom = (header as *mut u8).add(offset) as *mut Node<T>;
+        let block = bottom..bottom.add((*header).height);
+        if !block.contains(&node.as_ptr()) {
+            self.deallocate(node.as_ptr() as *mut u8, Layout::new::<Node<T>>());
+        }
+        (*header).live -= 1;
+        if (*header).live > 0 {
+            return;
+        }
+        if !(*header).taken {
+            ManuallyDrop::drop(&mut (*header).value);
+        }
+        let height = (*header).height;
+        if self.recycled_len < MAX_RECYCLED_TOWERS {
+            if self.recycled.len() <= height {
+                self.recycled.resize_with(height + 1, Vec::new);
+            }
+            self.recycled[height].push(NonNull::new_unchecked(header));
+            self.recycled_len += 1;
+        } else if !(*header).pooled {
+            self.deallocate(header as *mut u8, layout);
+        }
+    }
+
+    /// Take ownership of all of `other`'s storage, so nodes made by
+    /// `other` can be freed by `self`.
+    pub(crate) fn append(&mut self, other: &mut NodePool<T>) {
+        if self.chunks.is_empty() {
+            self.used = other.used;
+        }
+        // Keep our newest chunk last, as we're still handing out its bytes.
+        other.chunks.append(&mut self.chunks);
+        self.chunks = std::mem::take(&mut other.chunks);
+        other.used = 0;
+        // `other`'s recycled towers are now ours to reuse or release.
+        let recycled = std::mem::take(&mut other.recycled);
+        if self.recycled.len() < recycled.len() {
+            self.recycled.resize_with(recycled.len(), Vec::new);
+        }
+        for (height, mut towers) in recycled.into_iter().enumerate() {
+            self.recycled[height].append(&mut towers);
+        }
+        self.recycled_len += std::mem::take(&mut other.recycled_len);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NodePool, MAX_RECYCLED_TOWERS};
+    use std::ptr::NonNull;
+    use std::rc::Rc;
+
+    /// Free every node of the tower whose bottom node is `bottom`.
+    unsafe fn free_tower<T>(
+        pool: &mut NodePool<T>,
+        bottom: NonNull<crate::Node<T>>,
+        height: usize,
+    ) {
+        for level in 0..height {
+            pool.free(NonNull::new_unchecked(bottom.as_ptr().add(level)));
+        }
+    }
+
+    #[test]
+    fn test_pool_recycles_towers() {
+        let mut pool = NodePool::default();
+        let value = Rc::new(());
+        let first = pool.make_tower(Rc::clone(&value), 3);
+        unsafe {
+            let above = pool.make_node_above(NonNull::new_unchecked(first.as_ptr().add(2)));
+            free_tower(&mut pool, first, 3);
+            // The node above the tower still shares the value.
+            assert_eq!(pool.recycled_len(), 0);
+            assert_eq!(Rc::strong_count(&value), 2);
+            pool.free(above);
+        }
+        assert_eq!(pool.recycled_len(), 1);
+        assert_eq!(Rc::strong_count(&value), 1);
+        // Only a tower of the same height reuses the allocation.
+        let other = pool.make_tower(Rc::clone(&value), 2);
+        assert_eq!(pool.recycled_len(), 1);
+        let second = pool.make_tower(Rc::clone(&value), 3);
+        assert_eq!(second, first);
+        assert_eq!(pool.recycled_len(), 0);
+        unsafe {
+            free_tower(&mut pool, other, 2);
+            free_tower(&mut pool, second, 3);
+        }
+        assert_eq!(pool.recycled_len(), 2);
+        assert_eq!(Rc::strong_count(&value), 1);
+    }
+
+    #[test]
+    fn test_pool_caps_free_list() {
+        let mut pool = NodePool::default();
+        let towers: Vec<_> = (0..MAX_RECYCLED_TOWERS + 10)
+            .map(|i| pool.make_tower(i, 1))
+            .collect();
+        for tower in towers {
+            unsafe { pool.free(tower) };
+        }
+        // The rest were released, so only the kept towers count as reserved.
+        assert_eq!(pool.recycled_len(), MAX_RECYCLED_TOWERS);
+        let (layout, _) = NodePool::<usize>::tower_layout(1);
+        assert_eq!(pool.reserved_bytes(), MAX_RECYCLED_TOWERS * layout.size());
+        let tower = pool.make_tower(0, 1);
+        assert_eq!(pool.recycled_len(), MAX_RECYCLED_TOWERS - 1);
+        unsafe { pool.free(tower) };
+        assert_eq!(pool.recycled_len(), MAX_RECYCLED_TOWERS);
+    }
+}
@@ -0,0 +1,133 @@
+//! Parallel iteration with `rayon`, enabled with the `rayon` feature.
+
+use crate::iter::SkipListIndexRange;
+use crate::SkipList;
+use ::rayon::iter::plumbing::{bridge, Consumer, Producer, ProducerCallback, UnindexedConsumer};
+use ::rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
+use std::ops::Range;
+
+/// Parallel iterator over references to the elements of a skiplist, in
+/// ascending order, made by `par_iter` (from `rayon::prelude`).
+///
+/// The skiplist is split by index, and node widths find where each piece
+/// starts in `O(logn)` time, so every thread gets an even share however
+/// the elements are spread out.
+///
+/// # Example
+///
+/// ```rust
+/// use convenient_skiplist::SkipList;
+/// use rayon::prelude::*;
+///
+/// let sk = SkipList::from(0..10_000u64);
+/// let sum: u64 = sk.par_iter().sum();
+///
+/// assert_eq!(sum, (0..10_000).sum());
+/// assert_eq!(sk.par_iter().position_first(|&x| x == 42), Some(42));
+/// ```
+pub struct ParIter<'a, T> {
+    list: &'a SkipList<T>,
+}
+
+impl<'a, T: PartialOrd + Sync> IntoParallelIterator for &'a SkipList<T> {
+    type Item = &'a T;
+    type Iter = ParIter<'a, T>;
+
+    #[inline]
+    fn into_par_iter(self) -> Self::Iter {
+        ParIter { list: self }
+    }
+}
+
+impl<'a, T: PartialOrd + Sync> ParallelIterator for ParIter<'a, T> {
+    type Item = &'a T;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        bridge(self, consumer)
+    }
+
+    #[inline]
+    fn opt_len(&self) -> Option<usize> {
+        Some(self.list.len())
+    }
+}
+
+impl<'a, T: PartialOrd + Sync> IndexedParallelIterator for ParIter<'a, T> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    fn drive<C: Consumer<Self::Item>>(self, consumer: C) -> C::Result {
+        bridge(self, consumer)
+    }
+
+    fn with_producer<CB: ProducerCallback<Self::Item>>(self, callback: CB) -> CB::Output {
+        callback.callback(IndexProducer {
+            list: self.list,
+            range: 0..self.list.len(),
+        })
+    }
+}
+
+/// A contiguous run of indices of the skiplist.
+struct IndexProducer<'a, T> {
+    list: &'a SkipList<T>,
+    range: Range<usize>,
+}
+
+impl<'a, T: PartialOrd + Sync> Producer for IndexProducer<'a, T> {
+    type Item = &'a T;
+    type IntoIter = SkipListIndexRange<'a, Range<usize>, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.list.index_range(self.range)
+    }
+
+    fn split_at(self, index: usize) -> (Self, Self) {
+        let mid = self.range.start + index;
+        (
+            IndexProducer {
+                list: self.list,
+                range: self.range.start..mid,
+            },
+            IndexProducer {
+                list: self.list,
+                range: mid..self.range.end,
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::SkipList;
+    use rayon::prelude::*;
+
+    #[test]
+    fn test_par_iter_matches_iter() {
+        let sk: SkipList<_> = (0..5000u32).filter(|x| x % 7 != 0).collect();
+        let expected: Vec<_> = sk.iter_all().collect();
+        assert_eq!(sk.par_iter().collect::<Vec<_>>(), expected);
+        assert_eq!(sk.par_iter().len(), sk.len());
+        assert_eq!(
+            sk.par_iter().map(|&x| u64::from(x)).sum::<u64>(),
+            expected.iter().map(|&&x| u64::from(x)).sum::<u64>()
+        );
+        assert_eq!(
+            sk.par_iter().rev().skip(10).step_by(3).collect::<Vec<_>>(),
+            expected
+                .iter()
+                .rev()
+                .skip(10)
+                .step_by(3)
+                .copied()
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(SkipList::<u32>::new().par_iter().count(), 0);
+    }
+}
@@ -1,7 +1,10 @@
-use crate::SkipList;
+use crate::{SkipList, SkipMap};
+use serde::de::{MapAccess, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::marker::PhantomData;
 
-impl<T: Serialize + Clone + PartialOrd> Serialize for SkipList<T> {
+impl<T: Serialize + PartialOrd> Serialize for SkipList<T> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
@@ -11,20 +14,70 @@ impl<T: Serialize + Clone + PartialOrd> Serialize for SkipList<T> {
     }
 }
 
-impl<'de, T: Deserialize<'de> + PartialOrd + Clone> Deserialize<'de> for SkipList<T> {
+impl<'de, T: Deserialize<'de> + PartialOrd> Deserialize<'de> for SkipList<T> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
         let eles: Vec<T> = Deserialize::deserialize(deserializer)?;
-        Ok(SkipList::from(eles.into_iter()))
+        Ok(SkipList::from(eles))
+    }
+}
+
+impl<K, V> Serialize for SkipMap<K, V>
+where
+    K: Serialize + PartialOrd,
+    V: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_map(self.iter())
+    }
+}
+
+struct SkipMapVisitor<K, V>(PhantomData<fn() -> SkipMap<K, V>>);
+
+impl<'de, K, V> Visitor<'de> for SkipMapVisitor<K, V>
+where
+    K: Deserialize<'de> + PartialOrd,
+    V: Deserialize<'de>,
+{
+    type Value = SkipMap<K, V>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a map")
+    }
+
+    fn visit_map<A>(self, mut access: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut map = SkipMap::new();
+        while let Some((key, value)) = access.next_entry()? {
+            map.insert(key, value);
+        }
+        Ok(map)
+    }
+}
+
+impl<'de, K, V> Deserialize<'de> for SkipMap<K, V>
+where
+    K: Deserialize<'de> + PartialOrd,
+    V: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(SkipMapVisitor(PhantomData))
     }
 }
 
 #[cfg(test)]
 mod test_serde {
-    use crate::SkipList;
-    use serde_json;
+    use crate::{SkipList, SkipMap};
     #[test]
     fn test_serde() {
         let mut s = SkipList::new();
@@ -35,4 +88,21 @@ mod test_serde {
         let back = serde_json::from_str(&ser).expect("Failed to deserialize!");
         assert_eq!(s, back);
     }
+
+    #[test]
+    fn test_serde_map() {
+        let map: SkipMap<String, u32> = ["pear", "apple", "fig"]
+            .iter()
+            .enumerate()
+            .map(|(i, key)| (key.to_string(), i as u32))
+            .collect();
+        let ser = serde_json::to_string(&map).expect("Failed to serialize!");
+        assert_eq!(ser, r#"{"apple":1,"fig":2,"pear":0}"#);
+        let back: SkipMap<String, u32> =
+            serde_json::from_str(&ser).expect("Failed to deserialize!");
+        assert!(map.iter().eq(back.iter()));
+        // Objects written in any order come back sorted by key.
+        let back: SkipMap<String, u32> = serde_json::from_str(r#"{"b":2,"a":1}"#).unwrap();
+        assert_eq!(back.keys().collect::<Vec<_>>(), vec!["a", "b"]);
+    }
 }
@@ -0,0 +1,618 @@
+use crate::iter::{LeftBiasIter, VerticalIter};
+use crate::map::KeyRef;
+use crate::{Node, SkipList};
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::{FromIterator, FusedIterator};
+use std::marker::PhantomData;
+use std::slice;
+
+/// The most elements a block holds.
+const BLOCK_CAPACITY: usize = 32;
+
+/// A sorted run of elements, stored together in one tower of the skiplist.
+///
+/// Blocks are never empty, and are ordered by their first element.
+struct Block<T> {
+    items: Vec<T>,
+}
+
+impl<T> Block<T> {
+    #[inline]
+    fn first(&self) -> &T {
+        &self.items[0]
+    }
+
+    /// Find `item` in the block, or the index it would be inserted at.
+    #[inline]
+    fn search<Q>(&self, item: &Q) -> Result<usize, usize>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        let index = self.items.partition_point(|x| x.borrow() < item);
+        match self.items.get(index) {
+            Some(x) if x.borrow() == item => Ok(index),
+            _ => Err(index),
+        }
+    }
+}
+
+impl<T: PartialEq> PartialEq for Block<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.first() == other.first()
+    }
+}
+
+impl<T: PartialOrd> PartialOrd for Block<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.first().partial_cmp(other.first())
+    }
+}
+
+impl<T: Borrow<Q>, Q: ?Sized> Borrow<KeyRef<Q>> for Block<T> {
+    #[inline]
+    fn borrow(&self) -> &KeyRef<Q> {
+        KeyRef::new(self.first().borrow())
+    }
+}
+
+type BlockNode<T> = *mut Node<Block<T>>;
+
+/// A skiplist that stores its elements in sorted blocks of up to 32, one
+/// block per node on the bottom row.
+///
+/// The levels above only index the blocks, so a search follows a handful of
+/// pointers to the right block and then binary searches it, and a scan reads
+/// whole blocks at a time. Large skiplists take far fewer cache misses than
+/// with a `SkipList`, which follows a pointer per element. Node widths count
+/// the elements in each block, so indexing and rank queries still run in
+/// `O(logn)` time.
+///
+/// Inserting into a full block splits it in two, and a removal merges a
+/// block into the next one when they fit in half a block together.
+///
+/// # Example
+///
+/// ```rust
+/// use convenient_skiplist::UnrolledSkipList;
+/// let mut sk = UnrolledSkipList::new();
+/// for i in 0..1000u32 {
+///     sk.insert(i);
+/// }
+/// assert!(sk.remove(&500));
+///
+/// assert!(sk.contains(&999));
+/// assert_eq!(sk.len(), 999);
+/// assert_eq!(sk.at_index(500), Some(&501));
+/// assert_eq!(sk.rank(&750), 749);
+/// ```
+pub struct UnrolledSkipList<T> {
+    /// The skiplist's length and widths count every element, while its
+    /// bottom row only has a node per block.
+    inner: SkipList<Block<T>>,
+}
+
+impl<T: PartialOrd> UnrolledSkipList<T> {
+    /// Make a new, empty UnrolledSkipList.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::UnrolledSkipList;
+    /// let sk: UnrolledSkipList<u32> = UnrolledSkipList::new();
+    /// assert!(sk.is_empty());
+    /// ```
+    #[inline]
+    pub fn new() -> UnrolledSkipList<T> {
+        UnrolledSkipList {
+            inner: SkipList::new(),
+        }
+    }
+
+    /// Insert `item`, returning `false` if it was already there.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::UnrolledSkipList;
+    /// let mut sk = UnrolledSkipList::new();
+    ///
+    /// assert!(sk.insert(1));
+    /// assert!(!sk.insert(1));
+    /// assert_eq!(sk.len(), 1);
+    /// ```
+    pub fn insert(&mut self, item: T) -> bool {
+        let (node, path) = match self.locate(&item) {
+            Some(found) => found,
+            None => {
+                self.inner.insert(Block { items: vec![item] });
+                return true;
+            }
+        };
+        unsafe {
+            let block = (*node).value.get_value_mut();
+            let index = match block.search(&item) {
+                Ok(_) => return false,
+                Err(index) => index,
+            };
+            if block.items.len() < BLOCK_CAPACITY {
+                block.items.insert(index, item);
+                self.resize(&path, 1);
+                return true;
+            }
+            // Split the full block in two. Appending past the last block starts
+            // a new one instead, so sorted input fills every block up.
+            let is_last = (*node).right.unwrap().as_ref().value.value().is_none();
+            let split = if is_last && index == BLOCK_CAPACITY {
+                BLOCK_CAPACITY
+            } else {
+                BLOCK_CAPACITY / 2
+            };
+            let mut upper = block.items.split_off(split);
+            self.resize(&path, -(upper.len() as isize));
+            if index < split {
+                block.items.insert(index, item);
+                self.resize(&path, 1);
+            } else {
+                upper.insert(index - split, item);
+            }
+            let len = upper.len();
+            // The elements stay put on the heap as the block moves into the skiplist.
+            let first = &*upper.as_ptr();
+            self.inner.insert(Block { items: upper });
+            let path = self.path_to(first);
+            self.resize(&path, len as isize - 1);
+        }
+        true
+    }
+
+    /// Remove `item`, returning `true` if it was there.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::UnrolledSkipList;
+    /// let mut sk: UnrolledSkipList<_> = (0..100).collect();
+    ///
+    /// assert!(sk.remove(&50));
+    /// assert!(!sk.remove(&50));
+    /// assert_eq!(sk.len(), 99);
+    /// ```
+    pub fn remove<Q>(&mut self, item: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        let (node, path) = match self.locate(item) {
+            Some(found) => found,
+            None => return false,
+        };
+        unsafe {
+            let block = (*node).value.get_value_mut();
+            let index = match block.search(item) {
+                Ok(index) => index,
+                Err(_) => return false,
+            };
+            if block.items.len() == 1 {
+                self.inner.take(KeyRef::new(item));
+                return true;
+            }
+            block.items.remove(index);
+            self.resize(&path, -1);
+            self.merge_next(node);
+        }
+        true
+    }
+
+    /// Check if `item` is in the skiplist.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::UnrolledSkipList;
+    /// let sk: UnrolledSkipList<_> = (0..100).step_by(2).collect();
+    ///
+    /// assert!(sk.contains(&42));
+    /// assert!(!sk.contains(&43));
+    /// ```
+    #[inline]
+    pub fn contains<Q>(&self, item: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        self.inner
+            .floor(KeyRef::new(item))
+            .is_some_and(|block| block.search(item).is_ok())
+    }
+
+    /// Return the number of elements in the skiplist.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns true if the skiplist is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Count how many elements are strictly less than `item`.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::UnrolledSkipList;
+    /// let sk: UnrolledSkipList<_> = (0..100).step_by(2).collect();
+    ///
+    /// assert_eq!(sk.rank(&42), 21);
+    /// assert_eq!(sk.rank(&43), 22);
+    /// ```
+    pub fn rank<Q>(&self, item: &Q) -> usize
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        // Every element of the blocks starting before `item` is counted, so
+        // take back the ones in the last of them that aren't less than `item`.
+        let rank = self.inner.rank(KeyRef::new(item));
+        match self.inner.floor(KeyRef::new(item)) {
+            Some(block) if block.first().borrow() < item => {
+                let index = block.search(item).unwrap_or_else(|index| index);
+                rank - (block.items.len() - index)
+            }
+            _ => rank,
+        }
+    }
+
+    /// Find the index of `item`, if it's in the skiplist.
+    ///
+    /// Runs in `O(logn)` time.
+    #[inline]
+    pub fn index_of<Q>(&self, item: &Q) -> Option<usize>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        if self.contains(item) {
+            Some(self.rank(item))
+        } else {
+            None
+        }
+    }
+
+    /// Get the element at `index`.
+    ///
+    /// Runs in `O(logn)` time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::UnrolledSkipList;
+    /// let sk: UnrolledSkipList<_> = (0..100).rev().collect();
+    ///
+    /// assert_eq!(sk.at_index(42), Some(&42));
+    /// assert_eq!(sk.at_index(100), None);
+    /// ```
+    pub fn at_index(&self, index: usize) -> Option<&T> {
+        if index >= self.len() {
+            return None;
+        }
+        // Find the node left of the block holding `index`.
+        let mut distance_left = index + 1;
+        let mut curr_node = self.inner.top_left.as_ptr();
+        unsafe {
+            loop {
+                while (*curr_node).width() < distance_left {
+                    distance_left -= (*curr_node).width();
+                    // INVARIANT: `index` < len, so this never walks past PosInf.
+                    curr_node = (*curr_node).right.unwrap().as_ptr();
+                }
+                match (*curr_node).down {
+                    Some(down) => curr_node = down.as_ptr(),
+                    None => break,
+                }
+            }
+            // On the bottom row, the width is the length of the block to our right.
+            let block = (*(*curr_node).right.unwrap().as_ptr()).value.get_value();
+            Some(&block.items[distance_left - 1])
+        }
+    }
+
+    /// Peek at the smallest element.
+    #[inline]
+    pub fn peek_first(&self) -> Option<&T> {
+        self.at_index(0)
+    }
+
+    /// Peek at the largest element.
+    #[inline]
+    pub fn peek_last(&self) -> Option<&T> {
+        self.len()
+            .checked_sub(1)
+            .and_then(|index| self.at_index(index))
+    }
+
+    /// Iterate over the elements in order, a block at a time.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use convenient_skiplist::UnrolledSkipList;
+    /// let sk: UnrolledSkipList<_> = vec![3, 1, 2].into_iter().collect();
+    ///
+    /// assert_eq!(sk.iter().collect::<Vec<_>>(), vec![&1, &2, &3]);
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            // INVARIANT: There's always a bottom row, starting with NegInf.
+            curr_node: VerticalIter::new(self.inner.top_left.as_ptr())
+                .last()
+                .unwrap(),
+            block: [].iter(),
+            remaining: self.len(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// The nodes left of the block starting with `key`, from the top down.
+    #[inline]
+    fn path_to<Q>(&self, key: &Q) -> Vec<BlockNode<T>>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        LeftBiasIter::new(self.inner.top_left.as_ptr(), KeyRef::new(key)).collect()
+    }
+
+    /// Find the node of the block `item` is in, or belongs in: the last block
+    /// starting at or before `item`, or else the first block. Returns it along
+    /// with the path to it, or `None` if there are no blocks.
+    fn locate<Q>(&self, item: &Q) -> Option<(BlockNode<T>, Vec<BlockNode<T>>)>
+    where
+        T: Borrow<Q>,
+        Q: PartialOrd + ?Sized,
+    {
+        if self.is_empty() {
+            return None;
+        }
+        let path = self.path_to(item);
+        // INVARIANT: The path always ends on the bottom row, with a node right of it.
+        let left = *path.last().unwrap();
+        unsafe {
+            let right = (*left).right.unwrap().as_ptr();
+            match (*left).value.value() {
+                Some(block) if !(*right).value.eq_key(KeyRef::new(item)) => {
+                    Some((left, self.path_to::<T>(block.first())))
+                }
+                // `item` starts the block to our right, or comes before every block.
+                _ => Some((right, path)),
+            }
+        }
+    }
+
+    /// Account for the block after `path` growing by `delta` elements.
+    fn resize(&mut self, path: &[BlockNode<T>], delta: isize) {
+        // Every node on the path is left of the block and spans over it,
+        // so they're exactly the widths that change.
+        for &node in path {
+            unsafe { (*node).set_width(((*node).width() as isize + delta) as usize) };
+        }
+        self.inner.len = (self.inner.len as isize + delta) as usize;
+    }
+
+    /// Merge the block at `node` and the block after it, if together they'd
+    /// fill at most half a block.
+    fn merge_next(&mut self, node: BlockNode<T>) {
+        unsafe {
+            let next = match (*node).right.unwrap().as_ref().value.value() {
+                Some(next) => next,
+                None => return,
+            };
+            let block = (*node).value.get_value_mut();
+            let len = next.items.len();
+            if block.items.len() + len > BLOCK_CAPACITY / 2 {
+                return;
+            }
+            // Shrink the next block to a single element, so it's removed like any other.
+            let first = &*(next.first() as *const T);
+            let next_path = self.path_to(first);
+            self.resize(&next_path, 1 - len as isize);
+            // INVARIANT: The next block is in the skiplist, starting with `first`.
+            let mut next = self.inner.take(KeyRef::new(first)).unwrap();
+            // Taking the block can drop emptied levels, so find our path afresh.
+            let path = self.path_to::<T>(block.first());
+            block.items.append(&mut next.items);
+            self.resize(&path, len as isize);
+        }
+    }
+}
+
+impl<T: PartialOrd> Default for UnrolledSkipList<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PartialOrd + Clone> Clone for UnrolledSkipList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: PartialOrd + fmt::Debug> fmt::Debug for UnrolledSkipList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialOrd> FromIterator<T> for UnrolledSkipList<T> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut sk = UnrolledSkipList::new();
+        sk.extend(iter);
+        sk
+    }
+}
+
+impl<T: PartialOrd> Extend<T> for UnrolledSkipList<T> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.insert(item);
+        }
+    }
+}
+
+impl<'a, T: PartialOrd> IntoIterator for &'a UnrolledSkipList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the elements of an `UnrolledSkipList`, made by `UnrolledSkipList::iter`.
+pub struct Iter<'a, T> {
+    /// The node of the block being yielded on the bottom row, or NegInf.
+    curr_node: *const Node<Block<T>>,
+    block: slice::Iter<'a, T>,
+    remaining: usize,
+    _marker: PhantomData<&'a UnrolledSkipList<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.block.next() {
+                self.remaining -= 1;
+                return Some(item);
+            }
+            if self.remaining == 0 {
+                return None;
+            }
+            unsafe {
+                // As there's elements remaining, the node to our right is never PosInf.
+                self.curr_node = (*self.curr_node).right.unwrap().as_ptr();
+                self.block = (*self.curr_node).value.get_value().items.iter();
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+impl<'a, T> FusedIterator for Iter<'a, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::Block;
+    use crate::{SkipList, UnrolledSkipList};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_unrolled_matches_sorted_vec() {
+        let mut sk = UnrolledSkipList::new();
+        let mut expected: Vec<u32> = Vec::new();
+        let mut rng = StdRng::seed_from_u64(0x3131);
+        for round in 0..20_000 {
+            // Grow for a while, then shrink, so blocks both split and merge.
+            let item = rng.gen_range(0u32, 2000);
+            let position = expected.binary_search(&item);
+            if rng.gen_range(0, 10) < if round < 10_000 { 3 } else { 7 } {
+                assert_eq!(sk.remove(&item), position.is_ok());
+                if let Ok(position) = position {
+                    expected.remove(position);
+                }
+            } else {
+                assert_eq!(sk.insert(item), position.is_err());
+                if let Err(position) = position {
+                    expected.insert(position, item);
+                }
+            }
+            assert_eq!(sk.len(), expected.len());
+            assert_eq!(sk.contains(&item), expected.binary_search(&item).is_ok());
+            assert_eq!(sk.rank(&item), expected.partition_point(|&x| x < item));
+            assert_eq!(sk.index_of(&item), expected.binary_search(&item).ok());
+        }
+        for (index, item) in expected.iter().enumerate() {
+            assert_eq!(sk.at_index(index), Some(item));
+        }
+        assert_eq!(sk.at_index(expected.len()), None);
+        assert_eq!(sk.peek_first(), expected.first());
+        assert_eq!(sk.peek_last(), expected.last());
+        assert!(sk.iter().eq(expected.iter()));
+        assert_eq!(sk.iter().len(), expected.len());
+        assert!(sk.clone().iter().eq(expected.iter()));
+    }
+
+    #[test]
+    fn test_unrolled_sorted_input_fills_blocks() {
+        let sk: UnrolledSkipList<u32> = (0..320).collect();
+        // The bottom row has a node per block, after NegInf.
+        let blocks = sk.inner.iter_levels().last().unwrap().count() - 1;
+        assert_eq!(blocks, 320 / super::BLOCK_CAPACITY);
+        assert!(sk.iter().copied().eq(0..320));
+    }
+
+    #[test]
+    fn test_unrolled_merge_drops_top_level() {
+        let mut merges_dropping_levels = 0;
+        for seed in 0..64 {
+            // Two blocks, [0, 32) and [32], so the 17th removal merges them.
+            let mut sk = UnrolledSkipList {
+                inner: SkipList::with_seed(seed),
+            };
+            sk.extend(0..33u32);
+            let second_is_tallest = {
+                // The top row is always empty, so the tallest towers are one below.
+                let tallest = sk.inner.iter_levels().nth(1).unwrap();
+                tallest.filter_map(|(block, _)| block).map(Block::first).eq([&32])
+            };
+            for item in 0..16 {
+                assert!(sk.remove(&item));
+            }
+            let height = sk.inner.height;
+            assert!(sk.remove(&16));
+            // The next block is gone, and with it any levels only it reached.
+            assert_eq!(sk.inner.len(), 16);
+            assert_eq!(sk.inner.iter_levels().last().unwrap().count(), 2);
+            if sk.inner.height < height {
+                assert!(second_is_tallest);
+                merges_dropping_levels += 1;
+            }
+            assert!(sk.iter().copied().eq(17..33));
+            assert_eq!(sk.at_index(15), Some(&32));
+            assert_eq!(sk.rank(&32), 15);
+            sk.insert(0);
+            assert!(sk.iter().copied().eq(std::iter::once(0).chain(17..33)));
+        }
+        assert!(merges_dropping_levels > 0);
+    }
+}